@@ -0,0 +1,85 @@
+// Simulator throughput, run with `cargo bench --bench clock`
+//
+// Clocks a model of a register feeding a chain of adders and muxes, and compares reading
+// inputs by `Input` (map lookups, as components not using resolved ports do) to reading
+// by handles resolved at elaboration (as the core components do while clocking).
+use std::{hint::black_box, time::Instant};
+use syncrim::common::{ComponentStore, Input, SignalUnsigned, Simulator};
+use syncrim::components::*;
+
+const STAGES: usize = 200;
+const CYCLES: usize = 10_000;
+const READS: usize = 1_000_000;
+
+fn model() -> ComponentStore {
+    let mut cs = ComponentStore {
+        store: vec![
+            Constant::rc_new("one", (0.0, 0.0), 1),
+            Constant::rc_new("select", (0.0, 0.0), 1 as SignalUnsigned),
+            Register::rc_new(
+                "reg",
+                (0.0, 0.0),
+                Input::new(&format!("mux{}", STAGES - 1), MUX_OUT_ID),
+            ),
+        ],
+    };
+    let mut previous = Input::new("reg", REGISTER_OUT_ID);
+    for i in 0..STAGES {
+        let add = format!("add{}", i);
+        let mux = format!("mux{}", i);
+        cs.store.push(Add::rc_new(
+            &add,
+            (0.0, 0.0),
+            previous.clone(),
+            Input::new("one", CONSTANT_OUT_ID),
+        ));
+        cs.store.push(Mux::rc_new(
+            &mux,
+            (0.0, 0.0),
+            Input::new("select", CONSTANT_OUT_ID),
+            vec![previous, Input::new(&add, ADD_OUT_ID)],
+        ));
+        previous = Input::new(&mux, MUX_OUT_ID);
+    }
+    cs
+}
+
+fn main() {
+    let mut simulator = Simulator::new(model()).unwrap();
+
+    let start = Instant::now();
+    for _ in 0..CYCLES {
+        simulator.clock();
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "clock: {} components, {:.0} cycles/s",
+        simulator.ordered_components.len(),
+        CYCLES as f64 / elapsed.as_secs_f64()
+    );
+
+    let inputs: Vec<Input> = (0..STAGES)
+        .map(|i| Input::new(&format!("add{}", i), ADD_OUT_ID))
+        .collect();
+    let handles: Vec<_> = inputs
+        .iter()
+        .map(|input| simulator.resolve_input(input).unwrap())
+        .collect();
+
+    let start = Instant::now();
+    for i in 0..READS {
+        black_box(simulator.get_input_value(&inputs[i % STAGES]));
+    }
+    let lookup = start.elapsed();
+    let start = Instant::now();
+    for i in 0..READS {
+        black_box(simulator.get_handle_value(handles[i % STAGES]));
+    }
+    let resolved = start.elapsed();
+    println!(
+        "read by input: {:.1} ns, read by handle: {:.1} ns, speedup {:.1}x",
+        lookup.as_nanos() as f64 / READS as f64,
+        resolved.as_nanos() as f64 / READS as f64,
+        lookup.as_secs_f64() / resolved.as_secs_f64()
+    );
+}
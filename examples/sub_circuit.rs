@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+#[cfg(feature = "gui-egui")]
+use syncrim::gui_egui::editor::Library;
+use syncrim::{
+    common::{ComponentStore, Input},
+    components::*,
+    fern::fern_setup,
+};
+
+fn main() {
+    fern_setup();
+
+    // adder, reading input ports a and b
+    let adder = PathBuf::from("adder.json");
+    ComponentStore {
+        store: vec![
+            Add::rc_new(
+                "add",
+                (0.0, 0.0),
+                Input::new("a", "in"),
+                Input::new("b", "in"),
+            ),
+            Probe::rc_new("p", (0.0, 40.0), Input::new("add", ADD_OUT_ID)),
+        ],
+    }
+    .save_file(&adder);
+
+    // 3 + 4 + 5, by two adders
+    let cs = ComponentStore {
+        store: vec![
+            Constant::rc_new("c1", (60.0, 100.0), 3),
+            Constant::rc_new("c2", (60.0, 140.0), 4),
+            Constant::rc_new("c3", (60.0, 180.0), 5),
+            SubCircuit::rc_new(
+                "add1",
+                (200.0, 120.0),
+                &adder,
+                vec![
+                    ("a", Input::new("c1", CONSTANT_OUT_ID)),
+                    ("b", Input::new("c2", CONSTANT_OUT_ID)),
+                ],
+                vec![("sum", Input::new("add", ADD_OUT_ID))],
+            ),
+            SubCircuit::rc_new(
+                "add2",
+                (340.0, 160.0),
+                &adder,
+                vec![
+                    ("a", Input::new("add1", "sum")),
+                    ("b", Input::new("c3", CONSTANT_OUT_ID)),
+                ],
+                vec![("sum", Input::new("add", ADD_OUT_ID))],
+            ),
+            Probe::rc_new("p", (460.0, 160.0), Input::new("add2", "sum")),
+        ],
+    };
+
+    let path = PathBuf::from("sub_circuit.json");
+    cs.save_file(&path);
+
+    #[cfg(feature = "gui-egui")]
+    syncrim::gui_egui::gui(cs, &path, Library::default()).ok();
+
+    #[cfg(feature = "gui-vizia")]
+    syncrim::gui_vizia::gui(cs, &path);
+}
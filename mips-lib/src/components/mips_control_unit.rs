@@ -7,6 +7,7 @@ use syncrim::common::EguiComponent;
 use syncrim::common::{
     Component, Condition, Id, Input, InputPort, OutputType, Ports, SignalValue, Simulator,
 };
+use syncrim::coverage::CoverBin;
 
 use super::alu_op; // values used in communication to the alu
 use super::data_op; // values used in communication with the data memory
@@ -668,6 +669,42 @@ impl Component for ControlUnit {
         }
     }
 
+    // the implemented opcodes, and functions of R-type instructions
+    fn coverage_bins(&self) -> Vec<CoverBin> {
+        let op_codes = [
+            OP_0, OP_1, OP_J, OP_JAL, OP_BEQ, OP_BNE, OP_BLEZ, OP_BGTZ, OP_ADDI, OP_ADDIU, OP_SLTI,
+            OP_SLTIU, OP_ANDI, OP_ORI, OP_XORI, OP_LUI, OP_LB, OP_LH, OP_LW, OP_LBU, OP_LHU, OP_SB,
+            OP_SH, OP_SW,
+        ];
+        let functs = [
+            FUNCT_SLL, FUNCT_SRL, FUNCT_SRA, FUNCT_SLLV, FUNCT_SRLV, FUNCT_SRAV, FUNCT_JR,
+            FUNCT_JALR, SYSCALL, FUNCT_ADD, FUNCT_ADDU, FUNCT_SUB, FUNCT_SUBU, FUNCT_AND, FUNCT_OR,
+            FUNCT_XOR, FUNCT_NOR, FUNCT_SLT, FUNCT_SLTU,
+        ];
+        op_codes
+            .iter()
+            .map(|op_code| ("opcode", format!("{:#04x}", op_code)))
+            .chain(
+                functs
+                    .iter()
+                    .map(|funct| ("funct", format!("{:#04x}", funct))),
+            )
+            .collect()
+    }
+
+    // the opcode decoded, and the function of R-type instructions
+    fn coverage(&self, simulator: &Simulator) -> Vec<CoverBin> {
+        let Ok(instr_in) = u32::try_from(simulator.get_input_value(&self.a_in)) else {
+            return vec![];
+        };
+        let op_code = (instr_in >> 26) & 0x0000_003f;
+        let mut bins = vec![("opcode", format!("{:#04x}", op_code))];
+        if op_code == 0 {
+            bins.push(("funct", format!("{:#04x}", instr_in & 0x0000_003f)));
+        }
+        bins
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
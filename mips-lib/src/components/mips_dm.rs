@@ -105,17 +105,10 @@ impl DataMem {
     /// or phys_mem_id is not of type PhysicalMem
     #[allow(clippy::expect_fun_call)]
     fn get_phys_mem<'a>(&self, sim: &'a Simulator) -> &'a PhysicalMem {
-        let v = &sim.ordered_components;
-        let comp = v
-            .iter()
-            .find(|x| x.get_id_ports().0 == self.phys_mem_id)
-            .expect(&format!("cant find {} in simulator", self.phys_mem_id));
-        // deref to get &dyn EguiComponent
-        let comp_any = (*comp).as_any();
-        let phys_mem: &PhysicalMem = comp_any
-            .downcast_ref()
-            .expect("can't downcast to physical memory");
-        phys_mem
+        sim.get_component(&self.phys_mem_id).expect(&format!(
+            "cant find physical memory {} in simulator",
+            self.phys_mem_id
+        ))
     }
 
     fn get_mut_mem<'a>(&self, sim: &'a Simulator) -> RefMut<'a, MipsMem> {
@@ -126,7 +119,9 @@ impl DataMem {
         self.get_phys_mem(sim)
             .history
             .borrow_mut()
-            .insert(cycle, op);
+            .entry(cycle)
+            .or_default()
+            .push(op);
     }
     fn up_cycle(&self, sim: &Simulator) {
         let cycle = sim.cycle;
@@ -140,6 +135,11 @@ impl Component for DataMem {
         self
     }
 
+    // memory history is kept per cycle
+    fn always_clock(&self) -> bool {
+        true
+    }
+
     fn to_(&self) {
         //println!("InstrMem");
     }
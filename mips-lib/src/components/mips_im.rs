@@ -108,6 +108,11 @@ impl Component for InstrMem {
         self
     }
 
+    // reads the physical memory shared with the data memory, and tracks pipeline pcs each cycle
+    fn always_clock(&self) -> bool {
+        true
+    }
+
     fn to_(&self) {
         //println!("InstrMem");
     }
@@ -154,17 +159,11 @@ impl Component for InstrMem {
         // this is inside a {} to make sure our simulator borrow is returned before its used to set signal
         #[allow(clippy::expect_fun_call)]
         let option_instr = {
-            let v = &simulator.ordered_components;
-            let comp = v
-                .iter()
-                .find(|x| x.get_id_ports().0 == self.phys_mem_id)
-                .expect(&format!("cant find {} in simulator", self.phys_mem_id));
-            // deref to get Rc
-            // deref again to get &dyn EguiComponent
-            let comp_any = (**comp).as_any();
-            let phys_mem: &PhysicalMem = comp_any
-                .downcast_ref()
-                .expect("can't downcast to physical memory");
+            let phys_mem: &PhysicalMem =
+                simulator.get_component(&self.phys_mem_id).expect(&format!(
+                    "cant find physical memory {} in simulator",
+                    self.phys_mem_id
+                ));
             phys_mem
                 .mem
                 .borrow_mut()
@@ -209,4 +208,25 @@ impl Component for InstrMem {
             self.pc_dm_history.borrow_mut().clear();
         }
     }
+
+    fn snapshot(&self, history: bool) -> Option<serde_json::Value> {
+        let pc_dm_history = self.pc_dm_history.borrow();
+        match history {
+            true => serde_json::to_value((&*pc_dm_history, &self.dynamic_symbols)).ok(),
+            // the start pc is kept for reset
+            false => serde_json::to_value((
+                &pc_dm_history[..pc_dm_history.len().min(1)],
+                &self.dynamic_symbols,
+            ))
+            .ok(),
+        }
+    }
+
+    fn restore(&self, state: &serde_json::Value) -> Result<(), String> {
+        let (pc_dm_history, dynamic_symbols) =
+            serde_json::from_value(state.clone()).map_err(|err| err.to_string())?;
+        self.pc_dm_history.replace(pc_dm_history);
+        self.dynamic_symbols.replace(dynamic_symbols);
+        Ok(())
+    }
 }
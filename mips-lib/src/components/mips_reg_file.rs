@@ -179,6 +179,21 @@ impl Component for RegFile {
         *self.history.borrow_mut() = vec![];
     }
 
+    fn snapshot(&self, history: bool) -> Option<serde_json::Value> {
+        match history {
+            true => serde_json::to_value((&self.registers, &self.history)).ok(),
+            false => serde_json::to_value((&self.registers, Vec::<RegOp>::new())).ok(),
+        }
+    }
+
+    fn restore(&self, state: &serde_json::Value) -> Result<(), String> {
+        let (registers, history) =
+            serde_json::from_value(state.clone()).map_err(|err| err.to_string())?;
+        self.registers.replace(registers);
+        self.history.replace(history);
+        Ok(())
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
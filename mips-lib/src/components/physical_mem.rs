@@ -8,6 +8,7 @@ use std::{
 };
 use syncrim::{
     common::{Component, Ports, Simulator},
+    fault::FaultError,
     signal::Id,
 };
 
@@ -20,9 +21,10 @@ pub struct PhysicalMem {
     pub pos: (f32, f32),
     #[serde(skip)]
     pub mem: RefCell<MipsMem>,
+    // the writes (and corruptions by fault injection) of each cycle, in order
     #[serde(skip)]
-    pub history: RefCell<HashMap<usize, MemWriteReturn>>,
-    // used for the un_clock(), this is because the simulator is not passed in un clock and we dont know what cycle we un clock to
+    pub history: RefCell<HashMap<usize, Vec<MemWriteReturn>>>,
+    // the cycle last clocked by the data memory, or undone
     #[serde(skip)]
     pub cycle: RefCell<usize>,
 }
@@ -44,6 +46,20 @@ impl PhysicalMem {
         self.history.borrow_mut().clear();
         Ok(())
     }
+
+    // undo the writes done in `cycle` or later, last first
+    fn revert_from(&self, cycle: usize) {
+        let mut history = self.history.borrow_mut();
+        let mut cycles: Vec<usize> = history.keys().copied().filter(|at| *at >= cycle).collect();
+        // sort vec with largest first
+        cycles.sort_by(|a, b| a.cmp(b).reverse());
+        let mut mem = self.mem.borrow_mut();
+        for at in cycles {
+            for op in history.remove(&at).unwrap_or_default().into_iter().rev() {
+                mem.revert(op);
+            }
+        }
+    }
 }
 
 #[typetag::serde]
@@ -61,25 +77,58 @@ impl Component for PhysicalMem {
         self
     }
 
-    fn un_clock(&self, _: &Simulator) {
-        *self.cycle.borrow_mut() -= 1;
-        if let Some(op) = self.history.borrow_mut().remove(&*self.cycle.borrow()) {
-            self.mem.borrow_mut().revert(op);
-        };
+    // the history is kept per cycle, also of cycles the memory was not written
+    fn always_clock(&self) -> bool {
+        true
+    }
+
+    fn un_clock(&self, simulator: &Simulator) {
+        // the simulator cycle is the one undone
+        self.cycle.replace(simulator.cycle);
+        self.revert_from(simulator.cycle);
     }
 
     fn reset(&self) {
         // dont need to reset cycle, since cycle is updated in clock
+        self.revert_from(0);
+    }
 
-        let mut hist_vec: Vec<(usize, MemWriteReturn)> =
-            self.history.borrow_mut().drain().collect();
-        // sort vec with largest first
-        hist_vec.sort_by(|(a, _), (b, _)| a.cmp(b).reverse());
-        let mut mem = self.mem.borrow_mut();
-        for (_, op) in hist_vec {
-            mem.revert(op);
+    fn corrupt_memory(
+        &self,
+        simulator: &Simulator,
+        address: usize,
+        mask: u8,
+    ) -> Result<(), FaultError> {
+        let address = u32::try_from(address).map_err(|_| FaultError::InvalidAddress(address))?;
+        let op = self.mem.borrow_mut().corrupt_byte(address, mask);
+        self.history
+            .borrow_mut()
+            .entry(simulator.cycle)
+            .or_default()
+            .push(op);
+        Ok(())
+    }
+
+    fn snapshot(&self, history: bool) -> Option<serde_json::Value> {
+        match history {
+            true => serde_json::to_value((&self.mem, &self.history, &self.cycle)).ok(),
+            false => serde_json::to_value((
+                &self.mem,
+                HashMap::<usize, Vec<MemWriteReturn>>::new(),
+                &self.cycle,
+            ))
+            .ok(),
         }
     }
+
+    fn restore(&self, state: &serde_json::Value) -> Result<(), String> {
+        let (mem, history, cycle) =
+            serde_json::from_value(state.clone()).map_err(|err| err.to_string())?;
+        self.mem.replace(mem);
+        self.history.replace(history);
+        self.cycle.replace(cycle);
+        Ok(())
+    }
 }
 
 /// A men contains three fields. One with the memory mapped data in a BTreeMap<u32,u8>,
@@ -90,7 +139,7 @@ pub struct MipsMem {
     sections: HashMap<u32, String>,
     data: BTreeMap<u32, u8>,
 }
-#[derive(Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub enum MemOpSize {
     Byte,
     Half,
@@ -136,7 +185,7 @@ impl From<std::io::Error> for MemLoadError {
 }
 
 /// This struct is not ment to be cloned
-#[derive(Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct MemWriteReturn {
     address: u32,
     op_size: MemOpSize,
@@ -391,6 +440,17 @@ impl MipsMem {
         }
         Ok(())
     }
+    /// xor the byte at address with mask, used for fault injection, returns the byte before
+    pub fn corrupt_byte(&mut self, address: u32, mask: u8) -> MemWriteReturn {
+        let byte = self.data.get(&address).copied().unwrap_or(0);
+        self.data.insert(address, byte ^ mask);
+        MemWriteReturn {
+            address,
+            op_size: MemOpSize::Byte,
+            bytes: [byte, 0, 0, 0],
+        }
+    }
+
     /// consumes undo the passed related mem write operation
     pub fn revert(&mut self, op: MemWriteReturn) {
         match op.op_size {
@@ -31,6 +31,7 @@ impl EguiComponent for SignZeroExtend {
                         }
                         SignalValue::Unknown => "Sign/Zero extend:\nextendUnknown".to_string(),
                         SignalValue::DontCare => "Sign/Zero extend:\nDon't Care".to_string(),
+                        SignalValue::Wide(_) => "Sign/Zero extend:\nInvalid cntr".to_string(),
                         SignalValue::Data(v) => match v {
                             0 => "Sign/Zero extend:\nZero",
                             1 => "Sign/Zero extend:\nSign",
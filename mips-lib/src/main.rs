@@ -2,10 +2,13 @@ use clap::Parser;
 // The trait impls from here are used dynamically when json is loaded, so this is actually used
 #[allow(unused_imports)]
 use mips_lib::*;
-use std::path::PathBuf;
+use std::{path::PathBuf, process::ExitCode};
 #[cfg(feature = "gui-egui")]
 use syncrim::gui_egui::editor::Library;
-use syncrim::{common::ComponentStore, fern::fern_setup};
+use syncrim::{
+    fern::fern_setup,
+    headless::{run_model, RunArgs},
+};
 // this is actually loaded dynamically, so it is used.
 #[allow(unused_imports)]
 use mips_lib::components::*;
@@ -16,21 +19,32 @@ struct Args {
     /// Path to the model to load on startup
     #[arg(short, long, default_value = "mips_pipe.json")]
     model: String,
+
+    #[command(flatten)]
+    run: RunArgs,
 }
 
-fn main() {
+fn main() -> ExitCode {
     fern_setup();
     let args = Args::parse();
     let path = PathBuf::from(args.model);
 
-    let cs = ComponentStore::load_file(&path).unwrap_or(ComponentStore { store: vec![] });
+    // without a GUI feature the model is always run headless
+    #[cfg(any(feature = "gui-vizia", feature = "gui-egui"))]
+    if !args.run.is_headless() {
+        let cs = args
+            .run
+            .load_model(&path)
+            .unwrap_or(syncrim::common::ComponentStore { store: vec![] });
+
+        #[cfg(feature = "gui-egui")]
+        syncrim::gui_egui::gui(cs, &path, Library::default()).ok();
 
-    #[cfg(feature = "gui-egui")]
-    syncrim::gui_egui::gui(cs, &path, Library::default()).ok();
+        #[cfg(feature = "gui-vizia")]
+        syncrim::gui_vizia::gui(cs, &path);
 
-    #[cfg(feature = "gui-vizia")]
-    syncrim::gui_vizia::gui(cs, &path);
+        return ExitCode::SUCCESS;
+    }
 
-    #[cfg(not(any(feature = "gui-vizia", feature = "gui-egui")))]
-    syncrim::common::Simulator::new(cs).unwrap();
+    run_model(&path, &args.run)
 }
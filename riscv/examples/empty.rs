@@ -40,11 +40,11 @@ fn main() {
                     id: "dummy_instr_mem".to_string(),
                     pos: (0.0, 0.0),
                     pc: dummy.clone(),
-                    bytes: BTreeMap::new(),
+                    bytes: RefCell::new(BTreeMap::new()),
                     breakpoints: Rc::new(RefCell::new(HashSet::new())),
                     le: true,
                     range: Range { start: 0, end: 0 },
-                    symbols: HashMap::new(),
+                    symbols: RefCell::new(HashMap::new()),
                 }),
                 Rc::new(ALU {
                     id: "dummy_alu".to_string(),
@@ -95,7 +95,7 @@ fn main() {
             // replace the memory contents with ELF contents
             data_mem_comp.memory = Memory::new(data_mem.clone());
             // also, set the initial state for reset
-            data_mem_comp.init_state = data_mem;
+            data_mem_comp.init_state = RefCell::new(data_mem);
             // repush the mutated RVMem to the Component vector
             store.push(Rc::new(data_mem_comp));
             //satisfy borrow checker
@@ -116,9 +116,9 @@ fn main() {
                 .expect(&format!("Downcast failed for {:?}", comp.to_()))
                 .clone();
             // replace the memory contents with ELF contents
-            instr_mem_comp.bytes = instr_mem;
+            instr_mem_comp.bytes = RefCell::new(instr_mem);
             // replace the symbols with ELF symbols
-            instr_mem_comp.symbols = memory.symbols;
+            instr_mem_comp.symbols = RefCell::new(memory.symbols);
             // repush the mutated InstrMem to the Component vector
             store.push(Rc::new(instr_mem_comp));
             //satisfy borrow checker
@@ -139,13 +139,13 @@ fn main() {
                     id: "dummy_instr_mem".to_string(),
                     pos: (0.0, 0.0),
                     pc: dummy.clone(),
-                    bytes: BTreeMap::new(),
+                    bytes: RefCell::new(BTreeMap::new()),
                     range: Range {
                         start: 0,
                         end: 0x1000,
                     },
                     breakpoints: Rc::new(RefCell::new(HashSet::new())),
-                    symbols: HashMap::new(),
+                    symbols: RefCell::new(HashMap::new()),
                     le: true,
                 }),
                 Rc::new(ALU {
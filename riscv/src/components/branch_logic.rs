@@ -108,9 +108,10 @@ impl Component for BranchLogic {
         let rs2: SignalValue = simulator.get_input_value(&self.rs2);
         if enable != 0 {
             match simulator.get_input_value(&self.ctrl) {
-                SignalValue::Unknown | SignalValue::DontCare | SignalValue::Uninitialized => {
-                    out = SignalValue::Unknown
-                }
+                SignalValue::Unknown
+                | SignalValue::DontCare
+                | SignalValue::Uninitialized
+                | SignalValue::Wide(_) => out = SignalValue::Unknown,
                 SignalValue::Data(ctrl) => {
                     match ctrl {
                         0b000 => {
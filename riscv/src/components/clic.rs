@@ -37,7 +37,7 @@ pub const CLIC_STACK_DEPTH_OUT_ID: &str = "stack_depth_out";
 pub const TIMER_WIDTH: u32 = 16;
 pub const TIMER_PRES_WIDTH: u32 = 4;
 pub const TIMER_ADDR: u32 = 0x400;
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 struct CLICOp {
     pub mmio_op: Option<([u32; 2], u32)>,
     pub csr_op: Option<Vec<(usize, u32)>>,
@@ -45,6 +45,19 @@ struct CLICOp {
     pub queue_op: Vec<(u32, u8, bool)>,
 }
 
+// internal state saved in simulator snapshots
+#[derive(Serialize, Deserialize)]
+struct CLICState {
+    csrstore: HashMap<usize, usize>,
+    mmio: HashMap<usize, u8>,
+    queue: PriorityQueue<u32, u8>,
+    clic_stack: Vec<(u32, u32)>,
+    mtime: u64,
+    monotonic: u64,
+    mtimecomp: u64,
+    history: Vec<CLICOp>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct CLIC {
     pub id: Id,
@@ -270,6 +283,37 @@ impl Component for CLIC {
         self.monotonic.swap(&RefCell::new(0));
     }
 
+    fn snapshot(&self, history: bool) -> Option<serde_json::Value> {
+        serde_json::to_value(CLICState {
+            csrstore: self.csrstore.borrow().clone(),
+            mmio: self.mmio.borrow().clone(),
+            queue: self.queue.borrow().clone(),
+            clic_stack: self.clic_stack.borrow().clone(),
+            mtime: *self.mtime.borrow(),
+            monotonic: *self.monotonic.borrow(),
+            mtimecomp: *self.mtimecomp.borrow(),
+            history: match history {
+                true => self.history.borrow().clone(),
+                false => vec![],
+            },
+        })
+        .ok()
+    }
+
+    fn restore(&self, state: &serde_json::Value) -> Result<(), String> {
+        let state: CLICState =
+            serde_json::from_value(state.clone()).map_err(|err| err.to_string())?;
+        self.csrstore.replace(state.csrstore);
+        self.mmio.replace(state.mmio);
+        self.queue.replace(state.queue);
+        self.clic_stack.replace(state.clic_stack);
+        self.mtime.replace(state.mtime);
+        self.monotonic.replace(state.monotonic);
+        self.mtimecomp.replace(state.mtimecomp);
+        self.history.replace(state.history);
+        Ok(())
+    }
+
     fn to_(&self) {
         println!("CLIC");
     }
@@ -722,6 +766,11 @@ impl Component for CLIC {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    // timers and interrupts advance each cycle
+    fn always_clock(&self) -> bool {
+        true
+    }
 }
 
 impl CLIC {
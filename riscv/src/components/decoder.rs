@@ -8,6 +8,7 @@ use syncrim::common::{
     Component, Condition, Id, Input, InputPort, OutputType, Ports, SignalValue, Simulator,
 };
 use syncrim::components::MemCtrl;
+use syncrim::coverage::CoverBin;
 
 pub const DECODER_INSTRUCTION_ID: &str = "instruction";
 
@@ -52,6 +53,20 @@ pub const DECODER_CSR_CTL_ID: &str = "csr_ctl";
 pub const DECODER_CSR_DATA_MUX_ID: &str = "csr_data_mux";
 pub const DECODER_CSR_ADDR_ID: &str = "csr_addr";
 
+// opcodes decoded, with the funct3 values decoded for each (empty if not used)
+const DECODER_OPCODES: [(u32, &[u32]); 10] = [
+    (0b0110011, &[0, 1, 2, 3, 4, 5, 6, 7]), // OP
+    (0b0010011, &[0, 1, 2, 3, 4, 5, 6, 7]), // OP-IMM
+    (0b0110111, &[]),                       // LUI
+    (0b0010111, &[]),                       // AUIPC
+    (0b1101111, &[]),                       // JAL
+    (0b1100111, &[0]),                      // JALR
+    (0b1100011, &[0, 1, 4, 5, 6, 7]),       // BRANCH
+    (0b0000011, &[0, 1, 2, 4, 5]),          // LOAD
+    (0b0100011, &[0, 1, 2]),                // STORE
+    (0b1110011, &[0, 1, 2, 3, 5, 6, 7]),    // SYSTEM
+];
+
 pub const DECODER_HEIGHT: f32 = 600.0;
 pub const DECODER_WIDTH: f32 = 30.0;
 
@@ -164,6 +179,33 @@ impl Component for Decoder {
             ),
         )
     }
+    // the opcodes decoded, and their funct3 (opcode.funct3) where used
+    fn coverage_bins(&self) -> Vec<CoverBin> {
+        let mut bins = vec![];
+        for (opcode, functs) in DECODER_OPCODES {
+            bins.push(("opcode", format!("{:#04x}", opcode)));
+            for funct3 in functs.iter() {
+                bins.push(("funct3", format!("{:#04x}.{}", opcode, funct3)));
+            }
+        }
+        bins
+    }
+    // the opcode decoded, and its funct3 (opcode.funct3) where used
+    fn coverage(&self, simulator: &Simulator) -> Vec<CoverBin> {
+        let Ok(instruction) = u32::try_from(simulator.get_input_value(&self.instruction)) else {
+            return vec![];
+        };
+        let opcode = instruction & 0b1111111;
+        let funct3 = (instruction & (0b111 << 12)) >> 12;
+        let mut bins = vec![("opcode", format!("{:#04x}", opcode))];
+        if DECODER_OPCODES
+            .iter()
+            .any(|(op, functs)| *op == opcode && !functs.is_empty())
+        {
+            bins.push(("funct3", format!("{:#04x}.{}", opcode, funct3)));
+        }
+        bins
+    }
     #[allow(non_snake_case)]
     fn clock(&self, simulator: &mut Simulator) -> Result<(), Condition> {
         let instruction: u32 = simulator
@@ -10,7 +10,7 @@ use syncrim::{
     signal::{SignalUnsigned, SignalValue},
 };
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 pub const GPIO_CSR_BASE: u32 = 0x0;
 pub const GPIO_MMIO_BASE: u32 = 0x6000_0000;
 pub const GPIO_DATA_I_ID: &str = "data_i";
@@ -105,6 +105,11 @@ impl Component for GPIO {
         self
     }
 
+    // pins are shared with the gui
+    fn always_clock(&self) -> bool {
+        true
+    }
+
     fn to_(&self) {
         println!("GPIO");
     }
@@ -255,6 +260,20 @@ impl Component for GPIO {
         }
         Ok(())
     }
+
+    fn snapshot(&self, _history: bool) -> Option<serde_json::Value> {
+        serde_json::to_value((&self.memory, &self.pins.0, &*self.csrstore.0)).ok()
+    }
+
+    fn restore(&self, state: &serde_json::Value) -> Result<(), String> {
+        let (memory, pins, csrstore): (BTreeMap<usize, u8>, Vec<Pin>, HashMap<usize, usize>) =
+            serde_json::from_value(state.clone()).map_err(|err| err.to_string())?;
+        self.memory.0.replace(memory);
+        // keep sharing the pins with the gui
+        self.pins.0.replace(pins);
+        self.csrstore.0.replace(csrstore);
+        Ok(())
+    }
 }
 
 impl GPIO {
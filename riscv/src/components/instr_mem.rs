@@ -26,13 +26,13 @@ pub struct InstrMem {
     pub id: String,
     pub pos: (f32, f32),
     #[serde(skip)]
-    pub bytes: BTreeMap<usize, u8>,
+    pub bytes: RefCell<BTreeMap<usize, u8>>,
     pub pc: Input,
     pub range: Range<usize>,
     #[serde(skip)]
     pub breakpoints: Rc<RefCell<HashSet<usize>>>,
     #[serde(skip)]
-    pub symbols: HashMap<usize, String>,
+    pub symbols: RefCell<HashMap<usize, String>>,
     pub le: bool,
 }
 
@@ -42,6 +42,11 @@ impl Component for InstrMem {
         self
     }
 
+    // breakpoints are shared with the gui
+    fn always_clock(&self) -> bool {
+        true
+    }
+
     fn to_(&self) {
         //println!("InstrMem");
     }
@@ -53,14 +58,14 @@ impl Component for InstrMem {
             height: INSTR_MEM_HEIGHT,
             id: id.to_string(),
             pos: (pos.0, pos.1),
-            bytes: BTreeMap::new(),
+            bytes: RefCell::new(BTreeMap::new()),
             pc: dummy_input,
             range: Range {
                 start: 0,
                 end: 0x1000,
             },
             breakpoints: Rc::new(RefCell::new(HashSet::new())),
-            symbols: HashMap::new(),
+            symbols: RefCell::new(HashMap::new()),
             le: true,
         }))
     }
@@ -86,16 +91,17 @@ impl Component for InstrMem {
     fn clock(&self, simulator: &mut Simulator) -> Result<(), Condition> {
         // get instr at pc/4
         let pc: u32 = simulator.get_input_value(&self.pc).try_into().unwrap();
+        let bytes = self.bytes.borrow();
         let instr = if !self.le {
-            (*self.bytes.get(&((pc) as usize)).unwrap() as u32) << 24
-                | (*self.bytes.get(&((pc + 1) as usize)).unwrap() as u32) << 16
-                | (*self.bytes.get(&((pc + 2) as usize)).unwrap() as u32) << 8
-                | (*self.bytes.get(&((pc + 3) as usize)).unwrap() as u32)
+            (*bytes.get(&((pc) as usize)).unwrap() as u32) << 24
+                | (*bytes.get(&((pc + 1) as usize)).unwrap() as u32) << 16
+                | (*bytes.get(&((pc + 2) as usize)).unwrap() as u32) << 8
+                | (*bytes.get(&((pc + 3) as usize)).unwrap() as u32)
         } else {
-            (*self.bytes.get(&((pc) as usize)).unwrap() as u32)
-                | (*self.bytes.get(&((pc + 1) as usize)).unwrap() as u32) << 8
-                | (*self.bytes.get(&((pc + 2) as usize)).unwrap() as u32) << 16
-                | (*self.bytes.get(&((pc + 3) as usize)).unwrap() as u32) << 24
+            (*bytes.get(&((pc) as usize)).unwrap() as u32)
+                | (*bytes.get(&((pc + 1) as usize)).unwrap() as u32) << 8
+                | (*bytes.get(&((pc + 2) as usize)).unwrap() as u32) << 16
+                | (*bytes.get(&((pc + 3) as usize)).unwrap() as u32) << 24
         };
         //the asm_riscv crate incorrectly panics when trying from instead of
         //returning Err, catch it and handle instead
@@ -118,6 +124,31 @@ impl Component for InstrMem {
             Err(Condition::Halt(format!("Breakpoint at {}", pc)))
         }
     }
+
+    // the program is not part of the model
+    fn snapshot(&self, _history: bool) -> Option<serde_json::Value> {
+        serde_json::to_value((&self.bytes, &self.symbols)).ok()
+    }
+
+    fn restore(&self, state: &serde_json::Value) -> Result<(), String> {
+        let (bytes, symbols) =
+            serde_json::from_value(state.clone()).map_err(|err| err.to_string())?;
+        self.bytes.replace(bytes);
+        self.symbols.replace(symbols);
+        Ok(())
+    }
+
+    // breakpoints are toggled in the gui
+    fn gui_state(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(&self.breakpoints).ok()
+    }
+
+    fn restore_gui_state(&self, state: &serde_json::Value) -> Result<(), String> {
+        let breakpoints = serde_json::from_value(state.clone()).map_err(|err| err.to_string())?;
+        // keep sharing the breakpoints with the gui
+        self.breakpoints.replace(breakpoints);
+        Ok(())
+    }
 }
 mod test {
     #![allow(unused_imports)]
@@ -125,8 +156,10 @@ mod test {
 
     use std::rc::Rc;
     use syncrim::{
-        common::{ComponentStore, Input, Simulator},
-        components::ProbeOut,
+        common::{ComponentStore, Input, RunningState, Simulator},
+        components::{
+            Add, Constant, ProbeOut, Register, ADD_OUT_ID, CONSTANT_OUT_ID, REGISTER_OUT_ID,
+        },
     };
     #[test]
     fn test_inst_mem() {
@@ -147,13 +180,13 @@ mod test {
                     id: "imem".to_string(),
                     pos: (0.0, 0.0),
                     pc: Input::new("pc", "out"),
-                    bytes: instr_mem,
+                    bytes: RefCell::new(instr_mem),
                     range: Range {
                         start: 0,
                         end: 0x1000,
                     },
                     breakpoints: Rc::new(RefCell::new(HashSet::new())),
-                    symbols: HashMap::new(),
+                    symbols: RefCell::new(HashMap::new()),
                     le: false,
                 }),
             ],
@@ -170,4 +203,67 @@ mod test {
             assert_eq!(simulator.get_input_value(imem_out), i.into());
         }
     }
+
+    #[test]
+    fn test_inst_mem_run_threaded() {
+        // pc counting by 4 from 0, with a breakpoint at 0x20
+        let model = || {
+            let mut bytes = BTreeMap::new();
+            for i in 0u32..16u32 {
+                for (j, byte) in (i * 0x0101_0101).to_be_bytes().into_iter().enumerate() {
+                    bytes.insert((i * 4) as usize + j, byte);
+                }
+            }
+            ComponentStore {
+                store: vec![
+                    Constant::rc_new("four", (0.0, 0.0), 4),
+                    Register::rc_new("pc", (0.0, 0.0), Input::new("add", ADD_OUT_ID)),
+                    Add::rc_new(
+                        "add",
+                        (0.0, 0.0),
+                        Input::new("pc", REGISTER_OUT_ID),
+                        Input::new("four", CONSTANT_OUT_ID),
+                    ),
+                    Rc::new(InstrMem {
+                        width: 0.0,
+                        height: 0.0,
+                        id: "imem".to_string(),
+                        pos: (0.0, 0.0),
+                        pc: Input::new("pc", REGISTER_OUT_ID),
+                        bytes: RefCell::new(bytes),
+                        range: Range {
+                            start: 0,
+                            end: 0x40,
+                        },
+                        breakpoints: Rc::new(RefCell::new(HashSet::from([0x20]))),
+                        symbols: RefCell::new(HashMap::new()),
+                        le: false,
+                    }),
+                ],
+            }
+        };
+        let mut simulator = Simulator::new(model()).unwrap();
+        let mut expected = Simulator::new(model()).unwrap();
+
+        // the worker gets the program and breakpoints, not part of the model
+        simulator.set_running().unwrap();
+        let thread = simulator.run_threaded().unwrap();
+        // breakpoints toggled while running are kept when the worker stops
+        let imem = simulator.get_component::<InstrMem>("imem").unwrap();
+        imem.breakpoints.borrow_mut().insert(0x30);
+        thread.finish(&mut simulator).unwrap();
+        expected.set_running().unwrap();
+        while expected.is_running() {
+            expected.clock();
+        }
+        assert_eq!(simulator.running_state, RunningState::Halt);
+        assert_eq!(simulator.cycle, expected.cycle);
+        assert_eq!(simulator.sim_state, expected.sim_state);
+        assert_eq!(
+            simulator.get_input_value(&Input::new("imem", INSTR_MEM_INSTRUCTION_ID)),
+            0x0808_0808.into()
+        );
+        let imem = simulator.get_component::<InstrMem>("imem").unwrap();
+        assert_eq!(*imem.breakpoints.borrow(), HashSet::from([0x20, 0x30]));
+    }
 }
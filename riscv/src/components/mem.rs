@@ -45,7 +45,7 @@ pub struct RVMem {
     //
     history: RefCell<Vec<MemOp>>,
     #[serde(skip)]
-    pub init_state: BTreeMap<usize, u8>,
+    pub init_state: RefCell<BTreeMap<usize, u8>>,
 }
 #[derive(Serialize, Deserialize, Clone)]
 struct MemOp {
@@ -86,7 +86,7 @@ impl RVMem {
             memory: Memory::new(memory.clone()),
             range,
             history: RefCell::new(vec![]),
-            init_state: memory.clone(),
+            init_state: RefCell::new(memory.clone()),
         }
     }
 
@@ -476,11 +476,30 @@ impl Component for RVMem {
     fn reset(&self) {
         //let mut mem = self.memory.0.borrow_mut();
         //let b = self.init_state.0.clone();
-        self.memory.0.replace(self.init_state.clone());
+        self.memory.0.replace(self.init_state.borrow().clone());
         //mem = self.init_state.0.borrow_mut();
         //self.memory.0.swap(&*self.init_state.0.clone());
         self.history.swap(&RefCell::new(vec![]));
     }
+
+    // the initial state (for reset) is not part of the model
+    fn snapshot(&self, history: bool) -> Option<serde_json::Value> {
+        match history {
+            true => serde_json::to_value((&self.memory, &self.history, &self.init_state)).ok(),
+            false => {
+                serde_json::to_value((&self.memory, Vec::<MemOp>::new(), &self.init_state)).ok()
+            }
+        }
+    }
+
+    fn restore(&self, state: &serde_json::Value) -> Result<(), String> {
+        let (memory, history, init_state): (BTreeMap<usize, u8>, Vec<MemOp>, BTreeMap<usize, u8>) =
+            serde_json::from_value(state.clone()).map_err(|err| err.to_string())?;
+        self.memory.0.replace(memory);
+        self.history.replace(history);
+        self.init_state.replace(init_state);
+        Ok(())
+    }
 }
 
 impl Deref for Memory {
@@ -533,7 +552,7 @@ mod test {
                         end: 1u32,
                     },
                     history: RefCell::new(vec![]),
-                    init_state: BTreeMap::new(),
+                    init_state: RefCell::new(BTreeMap::new()),
                 }),
             ],
         };
@@ -721,7 +740,7 @@ mod test {
                         end: 1u32,
                     },
                     history: RefCell::new(vec![]),
-                    init_state: BTreeMap::new(),
+                    init_state: RefCell::new(BTreeMap::new()),
                 }),
             ],
         };
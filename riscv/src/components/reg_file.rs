@@ -320,6 +320,25 @@ impl Component for RegFile {
         self.history.0.swap(&RefCell::new(vec![]));
     }
 
+    fn snapshot(&self, history: bool) -> Option<serde_json::Value> {
+        match history {
+            true => serde_json::to_value((&self.registers, &self.history, &self.stack_depth_state)),
+            false => {
+                serde_json::to_value((&self.registers, RegHistory::new(), &self.stack_depth_state))
+            }
+        }
+        .ok()
+    }
+
+    fn restore(&self, state: &serde_json::Value) -> Result<(), String> {
+        let (registers, history, stack_depth_state): (RegStack, RegHistory, u32) =
+            serde_json::from_value(state.clone()).map_err(|err| err.to_string())?;
+        *self.registers.borrow_mut() = registers;
+        self.history.0.swap(&history.0);
+        self.stack_depth_state.replace(stack_depth_state);
+        Ok(())
+    }
+
     fn get_id_ports(&self) -> (String, Ports) {
         (
             self.id.clone(),
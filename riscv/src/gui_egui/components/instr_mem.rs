@@ -72,7 +72,7 @@ impl InstrMem {
                                     Color32::TRANSPARENT
                                 }
                             };
-                            row.col(|ui| match &self.symbols.get(&address) {
+                            row.col(|ui| match self.symbols.borrow().get(&address) {
                                 Some(s) => {
                                     ui.add(Label::new(format!("{}:", s)).truncate());
                                 }
@@ -86,17 +86,18 @@ impl InstrMem {
                             row.col(|ui| {
                                 ui.add(Label::new(format!("0x{:08x}", address)).truncate());
                             });
+                            let mem = self.bytes.borrow();
                             let mut bytes = [0u8; 4];
                             if !self.le {
-                                bytes[3] = *self.bytes.get(&address).unwrap();
-                                bytes[2] = *self.bytes.get(&(address + 1)).unwrap();
-                                bytes[1] = *self.bytes.get(&(address + 2)).unwrap();
-                                bytes[0] = *self.bytes.get(&(address + 3)).unwrap();
+                                bytes[3] = *mem.get(&address).unwrap();
+                                bytes[2] = *mem.get(&(address + 1)).unwrap();
+                                bytes[1] = *mem.get(&(address + 2)).unwrap();
+                                bytes[0] = *mem.get(&(address + 3)).unwrap();
                             } else {
-                                bytes[0] = *self.bytes.get(&address).unwrap();
-                                bytes[1] = *self.bytes.get(&(address + 1)).unwrap();
-                                bytes[2] = *self.bytes.get(&(address + 2)).unwrap();
-                                bytes[3] = *self.bytes.get(&(address + 3)).unwrap();
+                                bytes[0] = *mem.get(&address).unwrap();
+                                bytes[1] = *mem.get(&(address + 1)).unwrap();
+                                bytes[2] = *mem.get(&(address + 2)).unwrap();
+                                bytes[3] = *mem.get(&(address + 3)).unwrap();
                             }
                             let instr = ((bytes[3] as u32) << 24)
                                 | ((bytes[2] as u32) << 16)
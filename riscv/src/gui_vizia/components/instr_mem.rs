@@ -36,19 +36,20 @@ impl ViziaComponent for InstrMem {
         trace!("---- Create Left Instr View View");
         let data_slice = {
             let mut data_slice = vec![];
+            let bytes = self.bytes.borrow();
             trace!("range {:x?}", self.range);
             for idx in (self.range.start..self.range.end).step_by(4) {
                 trace!("idx {:x?}", idx);
                 let instr = if self.le {
-                    (*self.bytes.get(&idx).unwrap() as u32)
-                        | (*self.bytes.get(&(idx + 1)).unwrap() as u32) << 8
-                        | (*self.bytes.get(&(idx + 2)).unwrap() as u32) << 16
-                        | (*self.bytes.get(&(idx + 3)).unwrap() as u32) << 24
+                    (*bytes.get(&idx).unwrap() as u32)
+                        | (*bytes.get(&(idx + 1)).unwrap() as u32) << 8
+                        | (*bytes.get(&(idx + 2)).unwrap() as u32) << 16
+                        | (*bytes.get(&(idx + 3)).unwrap() as u32) << 24
                 } else {
-                    (*self.bytes.get(&idx).unwrap() as u32) << 24
-                        | (*self.bytes.get(&(idx + 1)).unwrap() as u32) << 16
-                        | (*self.bytes.get(&(idx + 2)).unwrap() as u32) << 8
-                        | (*self.bytes.get(&(idx + 3)).unwrap() as u32)
+                    (*bytes.get(&idx).unwrap() as u32) << 24
+                        | (*bytes.get(&(idx + 1)).unwrap() as u32) << 16
+                        | (*bytes.get(&(idx + 2)).unwrap() as u32) << 8
+                        | (*bytes.get(&(idx + 3)).unwrap() as u32)
                 };
                 data_slice.push(
                     format!(
@@ -63,7 +64,7 @@ impl ViziaComponent for InstrMem {
         println!("{:?}", self.symbols);
         let view = View::build(
             InstrMemView {
-                data: self.bytes.clone(),
+                data: self.bytes.borrow().clone(),
                 start: self.range.start,
                 data_slice,
                 //we may init to 0 range, once view opens this will be updated.
@@ -71,7 +72,7 @@ impl ViziaComponent for InstrMem {
                 breakpoints: self.breakpoints.clone(),
                 pc_input: self.pc.clone(),
                 pc: 0,
-                symbols: self.symbols.clone(),
+                symbols: self.symbols.borrow().clone(),
             },
             cx,
             |cx| {
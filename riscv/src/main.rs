@@ -1,12 +1,33 @@
+use clap::Parser;
 #[allow(unused_imports)]
 use riscv::components::*;
-use std::path::PathBuf;
-use syncrim::common::ComponentStore;
+use std::{path::PathBuf, process::ExitCode};
+use syncrim::headless::{run_model, RunArgs};
 
-fn main() {
-    let path = PathBuf::from("mips.json");
-    let _cs = ComponentStore::load_file(&path);
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Path to the model to load on startup
+    #[arg(short, long, default_value = "mips.json")]
+    model: String,
+
+    #[command(flatten)]
+    run: RunArgs,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let path = PathBuf::from(args.model);
 
     #[cfg(feature = "gui-vizia")]
-    syncrim::gui_vizia::gui(_cs, &path);
+    if !args.run.is_headless() {
+        let cs = args
+            .run
+            .load_model(&path)
+            .unwrap_or(syncrim::common::ComponentStore { store: vec![] });
+        syncrim::gui_vizia::gui(cs, &path);
+        return ExitCode::SUCCESS;
+    }
+
+    run_model(&path, &args.run)
 }
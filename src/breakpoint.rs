@@ -0,0 +1,223 @@
+// Breakpoints on signals, evaluated after each clock
+//
+// A breakpoint watches an output and is hit when its condition holds after a clock.
+// Once hit `hit_count` times, each further hit halts the simulator, reported as a
+// `Condition::Halt` of the component owning the output.
+use crate::common::{Condition, Input, SignalValue, Simulator};
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BreakCondition {
+    /// value equals after the clock
+    Equals(SignalValue),
+    /// value changed by the clock
+    Changes,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub input: Input,
+    pub condition: BreakCondition,
+    pub enabled: bool,
+    /// number of hits before halting, 1 halts on the first hit
+    pub hit_count: usize,
+    /// number of hits since reset
+    pub hits: usize,
+    // sim_state index of the input
+    index: usize,
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.input.id, self.input.field)?;
+        match self.condition {
+            BreakCondition::Equals(SignalValue::Data(value)) => write!(f, " == {:#x}", value)?,
+            BreakCondition::Equals(SignalValue::Wide(value)) => write!(f, " == {:#x}", value)?,
+            BreakCondition::Equals(value) => write!(f, " == {:?}", value)?,
+            BreakCondition::Changes => write!(f, " changes")?,
+        }
+        if self.hit_count > 1 {
+            write!(f, " (hit {})", self.hit_count)?;
+        }
+        Ok(())
+    }
+}
+
+/// The breakpoint input does not refer to an output of the model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownSignal(pub Input);
+
+impl fmt::Display for UnknownSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Signal {}.{} not found", self.0.id, self.0.field)
+    }
+}
+
+impl std::error::Error for UnknownSignal {}
+
+impl Simulator {
+    /// add a breakpoint on an output, returns its index
+    pub fn add_breakpoint(
+        &mut self,
+        input: Input,
+        condition: BreakCondition,
+        hit_count: usize,
+    ) -> Result<usize, UnknownSignal> {
+        let Some(index) = self.get_input_index(&input) else {
+            return Err(UnknownSignal(input));
+        };
+        self.breakpoints.push(Breakpoint {
+            input,
+            condition,
+            enabled: true,
+            hit_count: hit_count.max(1),
+            hits: 0,
+            index,
+        });
+        Ok(self.breakpoints.len() - 1)
+    }
+
+    /// remove breakpoint by index
+    pub fn remove_breakpoint(&mut self, index: usize) -> Option<Breakpoint> {
+        (index < self.breakpoints.len()).then(|| self.breakpoints.remove(index))
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// breakpoints, to enable/disable or change conditions and hit counts
+    pub fn breakpoints_mut(&mut self) -> &mut [Breakpoint] {
+        &mut self.breakpoints
+    }
+
+    // values watched before the clock
+    pub(crate) fn breakpoint_values(&self) -> Vec<SignalValue> {
+        self.breakpoints
+            .iter()
+            .map(|b| self.sim_state[b.index].get_value())
+            .collect()
+    }
+
+    // evaluate breakpoints after the clock, given the values before
+    pub(crate) fn check_breakpoints(&mut self, before: &[SignalValue]) {
+        for (breakpoint, before) in self.breakpoints.iter_mut().zip(before) {
+            if !breakpoint.enabled {
+                continue;
+            }
+            let value = self.sim_state[breakpoint.index].get_value();
+            let hit = match breakpoint.condition {
+                BreakCondition::Equals(expected) => value == expected,
+                BreakCondition::Changes => value != *before,
+            };
+            if !hit {
+                continue;
+            }
+            breakpoint.hits += 1;
+            if breakpoint.hits >= breakpoint.hit_count {
+                self.component_condition.push((
+                    breakpoint.input.id.clone(),
+                    Condition::Halt(format!("breakpoint {}", breakpoint)),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{ComponentStore, RunningState};
+    use crate::components::*;
+
+    // counter, incremented by one each cycle
+    fn simulator() -> Simulator {
+        Simulator::new(ComponentStore {
+            store: vec![
+                Constant::rc_new("one", (0.0, 0.0), 1),
+                Register::rc_new("reg", (0.0, 0.0), Input::new("add", ADD_OUT_ID)),
+                Add::rc_new(
+                    "add",
+                    (0.0, 0.0),
+                    Input::new("reg", REGISTER_OUT_ID),
+                    Input::new("one", CONSTANT_OUT_ID),
+                ),
+                And::rc_new(
+                    "bit1",
+                    (0.0, 0.0),
+                    Input::new("reg", REGISTER_OUT_ID),
+                    Input::new("two", CONSTANT_OUT_ID),
+                ),
+                Constant::rc_new("two", (0.0, 0.0), 2),
+            ],
+        })
+        .unwrap()
+    }
+
+    fn run(simulator: &mut Simulator) {
+        simulator.set_running().ok();
+        while simulator.is_running() && simulator.cycle < 100 {
+            simulator.clock();
+        }
+    }
+
+    #[test]
+    fn test_breakpoint_equals() {
+        let mut simulator = simulator();
+        simulator
+            .add_breakpoint(
+                Input::new("add", ADD_OUT_ID),
+                BreakCondition::Equals(0x10.into()),
+                1,
+            )
+            .unwrap();
+        run(&mut simulator);
+        assert_eq!(simulator.running_state, RunningState::Halt);
+        assert_eq!(
+            simulator.get_input_value(&Input::new("add", ADD_OUT_ID)),
+            0x10.into()
+        );
+        assert_eq!(
+            simulator.component_condition,
+            vec![(
+                "add".to_string(),
+                Condition::Halt("breakpoint add.add_out == 0x10".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn test_breakpoint_changes_hit_count() {
+        let mut simulator = simulator();
+        // bit 1 of the counter changes every second cycle
+        simulator
+            .add_breakpoint(Input::new("bit1", AND_OUT_ID), BreakCondition::Changes, 3)
+            .unwrap();
+        run(&mut simulator);
+        assert_eq!(simulator.running_state, RunningState::Halt);
+        assert_eq!(simulator.breakpoints()[0].hits, 3);
+        let cycle = simulator.cycle;
+
+        // disabled breakpoints are not evaluated
+        simulator.breakpoints_mut()[0].enabled = false;
+        simulator.clock();
+        simulator.clock();
+        assert_eq!(simulator.breakpoints()[0].hits, 3);
+        assert_eq!(simulator.cycle, cycle + 2);
+
+        simulator.reset();
+        assert_eq!(simulator.breakpoints()[0].hits, 0);
+        assert!(simulator.remove_breakpoint(0).is_some());
+        assert!(simulator.breakpoints().is_empty());
+    }
+
+    #[test]
+    fn test_breakpoint_unknown_signal() {
+        let mut simulator = simulator();
+        let input = Input::new("add", "missing");
+        assert_eq!(
+            simulator.add_breakpoint(input.clone(), BreakCondition::Changes, 1),
+            Err(UnknownSignal(input))
+        );
+    }
+}
@@ -0,0 +1,251 @@
+// Clock domains, clocking components only every `divisor` cycles
+//
+// Components assigned to a domain are clocked in cycles where `cycle % divisor == phase`
+// (and when reset), otherwise they are skipped and keep their outputs and internal state,
+// e.g., a peripheral bus clocked at a quarter of the core clock. Components not assigned to
+// a domain are clocked every cycle. Domains are added by the API, or declared in models by
+// `ClockDivider` components.
+use crate::common::{Id, Simulator};
+#[cfg(feature = "components")]
+use crate::{components::ClockDivider, elaboration::ElaborationProblem};
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClockDomain {
+    pub name: Id,
+    pub divisor: usize,
+    pub phase: usize,
+}
+
+impl ClockDomain {
+    /// components of the domain are clocked in `cycle`
+    pub fn ticks(&self, cycle: usize) -> bool {
+        cycle == 0 || cycle % self.divisor == self.phase
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClockDomainError {
+    // divisor is zero, or phase not less than the divisor
+    InvalidDivisor {
+        name: Id,
+        divisor: usize,
+        phase: usize,
+    },
+    DuplicateDomain {
+        name: Id,
+    },
+    UnknownDomain {
+        name: Id,
+    },
+    UnknownComponent {
+        id: Id,
+    },
+}
+
+impl fmt::Display for ClockDomainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClockDomainError::InvalidDivisor {
+                name,
+                divisor,
+                phase,
+            } => write!(
+                f,
+                "Clock domain {} divisor {} phase {}, the phase must be less than the divisor",
+                name, divisor, phase
+            ),
+            ClockDomainError::DuplicateDomain { name } => {
+                write!(f, "Clock domain {} is defined twice", name)
+            }
+            ClockDomainError::UnknownDomain { name } => {
+                write!(f, "Clock domain {} not found", name)
+            }
+            ClockDomainError::UnknownComponent { id } => {
+                write!(f, "Component {} not found", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClockDomainError {}
+
+impl Simulator {
+    /// add a clock domain, clocking its components when `cycle % divisor == phase`
+    pub fn add_clock_domain(
+        &mut self,
+        name: &str,
+        divisor: usize,
+        phase: usize,
+    ) -> Result<(), ClockDomainError> {
+        if phase >= divisor {
+            return Err(ClockDomainError::InvalidDivisor {
+                name: name.into(),
+                divisor,
+                phase,
+            });
+        }
+        if self.clock_domains.iter().any(|d| d.name == name) {
+            return Err(ClockDomainError::DuplicateDomain { name: name.into() });
+        }
+        self.clock_domains.push(ClockDomain {
+            name: name.into(),
+            divisor,
+            phase,
+        });
+        Ok(())
+    }
+
+    /// assign a component to a clock domain, or to no domain (clocked every cycle)
+    pub fn set_clock_domain(
+        &mut self,
+        id: &str,
+        domain: Option<&str>,
+    ) -> Result<(), ClockDomainError> {
+        let Some(index) = self.component_index.get(id).copied() else {
+            return Err(ClockDomainError::UnknownComponent { id: id.into() });
+        };
+        self.component_domain[index] = match domain {
+            Some(name) => Some(
+                self.clock_domains
+                    .iter()
+                    .position(|d| d.name == name)
+                    .ok_or_else(|| ClockDomainError::UnknownDomain { name: name.into() })?,
+            ),
+            None => None,
+        };
+        Ok(())
+    }
+
+    pub fn clock_domains(&self) -> &[ClockDomain] {
+        &self.clock_domains
+    }
+
+    /// clock domain of component, if any
+    pub fn clock_domain(&self, id: &str) -> Option<&ClockDomain> {
+        let index = *self.component_index.get(id)?;
+        self.component_domain[index].map(|domain| &self.clock_domains[domain])
+    }
+
+    // add the clock domains declared by clock dividers in the model
+    #[cfg(feature = "components")]
+    pub(crate) fn add_clock_dividers(&mut self) -> Vec<ElaborationProblem> {
+        let dividers: Vec<ClockDivider> = self
+            .ordered_components
+            .iter()
+            .filter_map(|c| c.as_any().downcast_ref::<ClockDivider>().cloned())
+            .collect();
+        let mut problems = vec![];
+        for divider in dividers {
+            let result = self
+                .add_clock_domain(&divider.id, divider.divisor, divider.phase)
+                .and_then(|_| {
+                    divider
+                        .component_ids()
+                        .try_for_each(|id| self.set_clock_domain(&id, Some(&divider.id)))
+                });
+            if let Err(error) = result {
+                problems.push(ElaborationProblem::ClockDomain {
+                    id: divider.id.clone(),
+                    error,
+                });
+            }
+        }
+        problems
+    }
+
+    // ordered component is clocked in the current cycle
+    pub(crate) fn ticks(&self, index: usize) -> bool {
+        self.component_domain[index]
+            .is_none_or(|domain| self.clock_domains[domain].ticks(self.cycle))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{ComponentStore, Input, SignalValue};
+    use crate::components::*;
+    use crate::fixtures::push_counter;
+
+    // counters, one in the core clock domain, one in a domain clocked every third cycle
+    fn simulator() -> Simulator {
+        let mut cs = ComponentStore {
+            store: vec![Constant::rc_new("one", (0.0, 0.0), 1)],
+        };
+        push_counter(&mut cs, "core", "core_add", "one");
+        push_counter(&mut cs, "bus", "bus_add", "one");
+        Simulator::new(cs).unwrap()
+    }
+
+    fn counters(simulator: &mut Simulator, cycles: usize) -> Vec<(SignalValue, SignalValue)> {
+        (0..cycles)
+            .map(|_| {
+                simulator.clock();
+                (
+                    simulator.get_input_value(&Input::new("core", REGISTER_OUT_ID)),
+                    simulator.get_input_value(&Input::new("bus", REGISTER_OUT_ID)),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_clock_domain() {
+        let mut simulator = simulator();
+        simulator.add_clock_domain("slow", 3, 1).unwrap();
+        simulator.set_clock_domain("bus", Some("slow")).unwrap();
+        assert_eq!(simulator.clock_domain("bus").unwrap().divisor, 3);
+        assert!(simulator.clock_domain("core").is_none());
+
+        // bus counter clocked in cycles 1, 4, 7
+        let values = counters(&mut simulator, 7);
+        let bus: Vec<_> = values.iter().map(|(_, bus)| *bus).collect();
+        let core: Vec<_> = values.iter().map(|(core, _)| *core).collect();
+        assert_eq!(bus, [1, 1, 1, 2, 2, 2, 3].map(SignalValue::from));
+        assert_eq!(core, [1, 2, 3, 4, 5, 6, 7].map(SignalValue::from));
+
+        // identical when event-driven, and stepping back
+        let mut event_driven = simulator.clone();
+        event_driven.reset();
+        event_driven.set_event_driven(true);
+        assert_eq!(counters(&mut event_driven, 7), values);
+        simulator.un_clock();
+        simulator.un_clock();
+        assert_eq!(counters(&mut simulator, 2), values[5..]);
+
+        // back in the core domain
+        simulator.set_clock_domain("bus", None).unwrap();
+        assert_eq!(counters(&mut simulator, 1)[0].1, SignalValue::from(4));
+    }
+
+    #[test]
+    fn test_clock_domain_errors() {
+        let mut simulator = simulator();
+        assert_eq!(
+            simulator.add_clock_domain("slow", 2, 2),
+            Err(ClockDomainError::InvalidDivisor {
+                name: "slow".into(),
+                divisor: 2,
+                phase: 2
+            })
+        );
+        simulator.add_clock_domain("slow", 2, 0).unwrap();
+        assert_eq!(
+            simulator.add_clock_domain("slow", 4, 0),
+            Err(ClockDomainError::DuplicateDomain {
+                name: "slow".into()
+            })
+        );
+        assert_eq!(
+            simulator.set_clock_domain("bus", Some("fast")),
+            Err(ClockDomainError::UnknownDomain {
+                name: "fast".into()
+            })
+        );
+        assert_eq!(
+            simulator.set_clock_domain("uart", Some("slow")),
+            Err(ClockDomainError::UnknownComponent { id: "uart".into() })
+        );
+    }
+}
@@ -3,7 +3,11 @@ use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::{
     collections::{HashMap, HashSet},
+    fs::File,
+    io::BufWriter,
+    ops::Range,
     rc::Rc,
+    sync::{Arc, Mutex},
 };
 
 #[cfg(feature = "gui-egui")]
@@ -15,7 +19,14 @@ use vizia::prelude::*;
 #[cfg(feature = "gui-vizia")]
 use crate::gui_vizia::ViziaComponent;
 
+use crate::breakpoint::Breakpoint;
+use crate::clock_domain::ClockDomain;
+use crate::coverage::{CoverBin, Coverage};
+use crate::fault::{FaultError, InjectedFault};
+use crate::history::History;
+use crate::profiler::ComponentTiming;
 pub use crate::signal::*;
+use crate::vcd::VcdWriter;
 
 #[cfg(not(any(feature = "gui-vizia", feature = "gui-egui")))]
 type Components = Vec<Rc<dyn Component>>;
@@ -29,7 +40,7 @@ pub type Components = Vec<Rc<dyn EguiComponent>>;
 pub enum SimulatorError {
     RunningStateIsErr(),
 }
-#[derive(PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub enum RunningState {
     Running,
     StepTo(usize),
@@ -49,7 +60,7 @@ pub struct Simulator {
     pub sim_state: Vec<Signal>,
     pub id_nr_outputs: IdNrOutputs,
     pub id_field_index: IdFieldIndex,
-    pub history: Vec<(Vec<Signal>, HashSet<Id>)>,
+    pub history: History,
     pub component_ids: Vec<Id>,
     pub graph: Graph<Id, ()>,
 
@@ -58,16 +69,57 @@ pub struct Simulator {
     pub(crate) halt_on_warning: bool,
     // says if simulation is running, halted, stopped or stepping to a specific cycle
     pub running_state: RunningState,
-    pub running_state_history: Vec<RunningState>,
     // stores if components return a condition
-    // TODO add component condition history
     pub component_condition: Vec<(Id, Condition)>,
-    pub component_condition_history: Vec<Vec<(Id, Condition)>>,
 
     // Used to determine active components
     pub sinks: Vec<Id>,
-    pub inputs_read: HashMap<Id, HashSet<Id>>,
+    // per ordered component, its input ports read in the last clock (in port order)
+    pub(crate) inputs_read: Vec<Vec<bool>>,
     pub active: HashSet<Id>,
+
+    // event-driven evaluation, only components downstream of changed outputs are clocked
+    pub(crate) event_driven: bool,
+    // set when sim_state was not produced by clocking components, forcing a full evaluation
+    pub(crate) full_sweep: bool,
+    // per ordered component, clocked each cycle in event-driven evaluation
+    pub(crate) always_clock: Vec<bool>,
+    // per ordered component, range of its outputs in sim_state
+    pub(crate) output_range: Vec<Range<usize>>,
+    // per ordered component, the combinational components reading its outputs
+    pub(crate) fanout: Vec<Vec<usize>>,
+
+    // per ordered component, its inputs (in port order) resolved at elaboration,
+    // and its output fields, used to avoid map lookups while clocking the component
+    pub(crate) inputs: Vec<Vec<Input>>,
+    pub(crate) input_handles: Vec<Vec<InputHandle>>,
+    pub(crate) output_fields: Vec<Vec<Id>>,
+    // ordered component index by id
+    pub(crate) component_index: HashMap<Id, usize>,
+    // ordered component being clocked (or un-clocked), if any
+    pub(crate) current: Option<usize>,
+    // per sequential component (ordered first), its outputs are read by sequential components,
+    // and committed only after all sequential components are clocked (two-phase update)
+    pub(crate) deferred: Vec<bool>,
+
+    // clock domains, and the domain of each ordered component, if any
+    pub(crate) clock_domains: Vec<ClockDomain>,
+    pub(crate) component_domain: Vec<Option<usize>>,
+
+    // breakpoints evaluated after each clock, and the number of conditions
+    // they appended to component_condition in the last clock
+    pub(crate) breakpoints: Vec<Breakpoint>,
+    pub(crate) breakpoint_conditions: usize,
+
+    // per ordered component, clock calls and time spent, if profiling
+    pub(crate) profile: Option<Vec<ComponentTiming>>,
+    // output toggles and component bins hit, if collecting coverage
+    pub(crate) coverage: Option<Coverage>,
+    // faults applied while clocking
+    pub(crate) faults: Vec<InjectedFault>,
+
+    // VCD file the state is streamed to on each clock, if any
+    pub(crate) vcd: Option<Arc<Mutex<VcdWriter<BufWriter<File>>>>>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -111,6 +163,26 @@ pub trait Component {
     fn un_clock(&self, _simulator: &Simulator) {}
     /// reset component internal state to initial value
     fn reset(&self) {}
+    /// internal state not kept in the simulator state (e.g., memory contents),
+    /// saved in simulator snapshots, including what is needed to `un_clock` if `history` is set,
+    /// must include all state not serialized with the model (`#[serde(skip)]`), as the worker
+    /// of `Simulator::run_threaded` is built from the model and a snapshot
+    fn snapshot(&self, _history: bool) -> Option<serde_json::Value> {
+        None
+    }
+    /// restore internal state from a `snapshot` of the same component
+    fn restore(&self, _state: &serde_json::Value) -> Result<(), String> {
+        Ok(())
+    }
+    /// state edited from the GUI (e.g., breakpoints), left out of `snapshot`, sent to the worker
+    /// of `Simulator::run_threaded` when started and when changed while running
+    fn gui_state(&self) -> Option<serde_json::Value> {
+        None
+    }
+    /// restore a `gui_state` of the same component
+    fn restore_gui_state(&self, _state: &serde_json::Value) -> Result<(), String> {
+        Ok(())
+    }
 
     /// consider component to be a sink
     /// either output to environment (e.g., for visualization)
@@ -118,11 +190,35 @@ pub trait Component {
     fn is_sink(&self) -> bool {
         false
     }
+    /// clock component each cycle in event-driven evaluation, also when no input changed
+    /// needed if the outputs or internal state depend on the cycle, or on state shared
+    /// with other components (e.g., memory written by another component)
+    fn always_clock(&self) -> bool {
+        false
+    }
+    /// xor the byte at `address` with `mask`, for fault injection into memory components,
+    /// kept in the undo history as done in `simulator.cycle` (the cycle about to be clocked)
+    fn corrupt_memory(
+        &self,
+        _simulator: &Simulator,
+        _address: usize,
+        _mask: u8,
+    ) -> Result<(), FaultError> {
+        Err(FaultError::NotMemory(self.get_id_ports().0))
+    }
+    /// coverage bins of the component, reported also when not hit (e.g., the inputs of a mux)
+    fn coverage_bins(&self) -> Vec<CoverBin> {
+        vec![]
+    }
+    /// coverage bins hit when clocked, read from the inputs (e.g., the mux input selected)
+    fn coverage(&self, _simulator: &Simulator) -> Vec<CoverBin> {
+        vec![]
+    }
     /// any
     fn as_any(&self) -> &dyn Any;
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 pub enum Condition {
     Warning(String),
     Halt(String),
@@ -232,6 +328,10 @@ impl Input {
     }
 }
 
+/// Input resolved to its index in the simulator state, see `Simulator::resolve_input`
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub struct InputHandle(pub(crate) usize);
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct InputPort {
     pub port_id: Id,
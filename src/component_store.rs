@@ -1,8 +1,19 @@
+// Models are loaded from json, `{"store": [...]}`, optionally declaring parameters,
+// `{"parameters": {"mem_size": 4096}, "store": [...]}`, referred to by components as
+// `"$mem_size"` (a string starting with `$$` is a string starting with `$`). References are
+// replaced by the parameter values, defaults from the model or overrides given when loading.
+// Models are elaborated with the values, the editor saves models by their `ModelSource`,
+// keeping the declarations and the references of values not edited.
 use crate::common::ComponentStore;
 
-use std::{fmt::Display, fs::File, io::prelude::*, path::PathBuf};
+use std::{collections::HashMap, fmt::Display, fs::File, io::prelude::*, path::PathBuf};
 
 use log::*;
+use serde::de::Error;
+use serde_json::Value;
+
+/// parameter values by name
+pub type Parameters = serde_json::Map<String, Value>;
 
 #[derive(Debug)]
 pub enum ComponentStoreLoadError {
@@ -39,17 +50,34 @@ impl From<serde_json::Error> for ComponentStoreLoadError {
 
 impl ComponentStore {
     pub fn load(json: &str) -> Result<Self, serde_json::Error> {
-        serde_json::from_str(json)
+        ComponentStore::load_with_parameters(json, &Parameters::new())
+    }
+
+    /// load with `parameters` overriding the defaults declared by the model
+    pub fn load_with_parameters(
+        json: &str,
+        parameters: &Parameters,
+    ) -> Result<Self, serde_json::Error> {
+        let mut model: Value = serde_json::from_str(json)?;
+        resolve_parameters(&mut model, parameters).map_err(serde_json::Error::custom)?;
+        serde_json::from_value(model)
     }
 
     pub fn load_file(path: &PathBuf) -> Result<Self, ComponentStoreLoadError> {
-        let mut file =
-            File::open(path).map_err(|err| ComponentStoreLoadError::Io(err, path.clone()))?;
-        let mut json = String::new();
-        file.read_to_string(&mut json)
-            .map_err(|err| ComponentStoreLoadError::Io(err, path.clone()))?;
+        ComponentStore::load_file_with_parameters(path, &Parameters::new())
+    }
 
-        let cs = ComponentStore::load(&json)?;
+    /// load file with `parameters` overriding the defaults declared by the model
+    pub fn load_file_with_parameters(
+        path: &PathBuf,
+        parameters: &Parameters,
+    ) -> Result<Self, ComponentStoreLoadError> {
+        let json = read_file(path)?;
+        let cs = ComponentStore::load_with_parameters(&json, parameters)?;
+        #[cfg(feature = "components")]
+        if let Some(dir) = path.parent() {
+            crate::components::set_sub_circuit_dir(&cs, dir);
+        }
         Ok(cs)
     }
 
@@ -67,3 +95,236 @@ impl ComponentStore {
         })
     }
 }
+
+/// A model as read, with its parameter declarations and references, to save the components
+/// loaded from it (after editing) keeping the references to values not edited
+#[derive(Clone, Debug, Default)]
+pub struct ModelSource {
+    parameters: Option<Value>,
+    // components by id, as read (unresolved) and as loaded
+    components: HashMap<String, (Value, Value)>,
+}
+
+impl ModelSource {
+    /// source of the components `loaded` from `json`
+    pub fn new(json: &str, loaded: &ComponentStore) -> Result<Self, serde_json::Error> {
+        let mut model: Value = serde_json::from_str(json)?;
+        let parameters = model
+            .as_object_mut()
+            .and_then(|model| model.remove("parameters"));
+        let store = match model.get_mut("store").map(Value::take) {
+            Some(Value::Array(store)) if store.len() == loaded.store.len() => store,
+            _ => return Err(serde_json::Error::custom("not the model of the components")),
+        };
+        let mut components = HashMap::new();
+        for (unresolved, c) in store.into_iter().zip(&loaded.store) {
+            components.insert(c.get_id_ports().0, (unresolved, serde_json::to_value(c)?));
+        }
+        Ok(ModelSource {
+            parameters,
+            components,
+        })
+    }
+
+    /// source of the components `loaded` from the model file `path`
+    pub fn load_file(
+        path: &PathBuf,
+        loaded: &ComponentStore,
+    ) -> Result<Self, ComponentStoreLoadError> {
+        Ok(ModelSource::new(&read_file(path)?, loaded)?)
+    }
+
+    /// the model of `store`, values equal to the values loaded are replaced by the references
+    /// read, and strings starting with `$` escaped
+    pub fn model(&self, store: &ComponentStore) -> Result<Value, serde_json::Error> {
+        let mut components = vec![];
+        for c in &store.store {
+            let mut value = serde_json::to_value(c)?;
+            let source = self.components.get(&c.get_id_ports().0);
+            restore_references(
+                &mut value,
+                source.map(|(unresolved, _)| unresolved),
+                source.map(|(_, loaded)| loaded),
+            );
+            components.push(value);
+        }
+        let mut model = serde_json::Map::new();
+        if let Some(parameters) = &self.parameters {
+            model.insert("parameters".to_string(), parameters.clone());
+        }
+        model.insert("store".to_string(), Value::Array(components));
+        Ok(Value::Object(model))
+    }
+
+    /// save `store` with the declarations and references of the source
+    pub fn save_file(&self, store: &ComponentStore, path: &PathBuf) {
+        let json = serde_json::to_string_pretty(&self.model(store).unwrap()).unwrap();
+        trace!("path {:?}", path);
+        let mut file = File::create(path).unwrap();
+        file.write_all(json.as_bytes()).unwrap();
+    }
+}
+
+// put back the `unresolved` reference of values equal to the value `loaded` from it
+fn restore_references(value: &mut Value, unresolved: Option<&Value>, loaded: Option<&Value>) {
+    if let Some(Value::String(reference)) = unresolved {
+        if reference.starts_with('$') && !reference.starts_with("$$") && loaded == Some(&*value) {
+            *value = Value::String(reference.clone());
+            return;
+        }
+    }
+    match value {
+        Value::String(string) if string.starts_with('$') => string.insert(0, '$'),
+        Value::Array(values) => {
+            for (index, value) in values.iter_mut().enumerate() {
+                restore_references(
+                    value,
+                    unresolved.and_then(|u| u.get(index)),
+                    loaded.and_then(|l| l.get(index)),
+                );
+            }
+        }
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                restore_references(
+                    value,
+                    unresolved.and_then(|u| u.get(key)),
+                    loaded.and_then(|l| l.get(key)),
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+fn read_file(path: &PathBuf) -> Result<String, ComponentStoreLoadError> {
+    let mut file =
+        File::open(path).map_err(|err| ComponentStoreLoadError::Io(err, path.clone()))?;
+    let mut json = String::new();
+    file.read_to_string(&mut json)
+        .map_err(|err| ComponentStoreLoadError::Io(err, path.clone()))?;
+    Ok(json)
+}
+
+/// load parameters from a json file, e.g., `{"mem_size": 4096}`
+pub fn load_parameters(path: &PathBuf) -> Result<Parameters, ComponentStoreLoadError> {
+    Ok(serde_json::from_str(&read_file(path)?)?)
+}
+
+/// parse a parameter given as `name=value`, the value is json or else a string
+pub fn parse_parameter(parameter: &str) -> Result<(String, Value), String> {
+    let (name, value) = parameter
+        .split_once('=')
+        .ok_or_else(|| format!("expected name=value, found {:?}", parameter))?;
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::from(value));
+    Ok((name.trim().to_string(), value))
+}
+
+// replace parameter references in the model (json) by the declared parameter values,
+// overridden by `parameters`, the declaration is removed
+pub(crate) fn resolve_parameters(model: &mut Value, parameters: &Parameters) -> Result<(), String> {
+    let declared = model
+        .as_object_mut()
+        .and_then(|model| model.remove("parameters"));
+    let mut values = match declared {
+        None => Parameters::new(),
+        Some(Value::Object(values)) => values,
+        Some(_) => return Err("parameters is not an object".to_string()),
+    };
+    for (name, value) in parameters {
+        match values.get_mut(name) {
+            Some(declared) => *declared = value.clone(),
+            None => return Err(format!("parameter {:?} is not declared by the model", name)),
+        }
+    }
+    if let Some(store) = model.get_mut("store") {
+        substitute(store, &values)?;
+    }
+    Ok(())
+}
+
+fn substitute(value: &mut Value, parameters: &Parameters) -> Result<(), String> {
+    match value {
+        Value::String(string) => {
+            if let Some(name) = string.strip_prefix('$') {
+                if name.starts_with('$') {
+                    *string = name.to_string();
+                } else {
+                    *value = parameters.get(name).cloned().ok_or_else(|| {
+                        format!("parameter {:?} is not declared by the model", name)
+                    })?;
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                substitute(value, parameters)?;
+            }
+        }
+        Value::Object(map) => {
+            for value in map.values_mut() {
+                substitute(value, parameters)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{Input, Simulator};
+
+    const MODEL: &str = r#"{
+        "parameters": {"value": 3},
+        "store": [
+            {"type": "Constant", "id": "c", "pos": [0.0, 0.0], "value": {"data": {"Data": "$value"}, "fmt": {"Unsigned": "_32"}}},
+            {"type": "Probe", "id": "$$p", "pos": [0.0, 0.0], "input": {"id": "c", "field": "constant_out"}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_parameters() {
+        let simulator = Simulator::new(ComponentStore::load(MODEL).unwrap()).unwrap();
+        assert_eq!(
+            simulator.get_input_value(&Input::new("c", "constant_out")),
+            3.into()
+        );
+        assert!(simulator.component_ids.contains(&"$p".to_string()));
+
+        let (name, value) = parse_parameter("value=0x10").unwrap();
+        assert_eq!(value, Value::from("0x10"));
+        let (_, value) = parse_parameter("value = 16").unwrap();
+        let parameters = Parameters::from_iter([(name, value)]);
+        let cs = ComponentStore::load_with_parameters(MODEL, &parameters).unwrap();
+        let simulator = Simulator::new(cs).unwrap();
+        assert_eq!(
+            simulator.get_input_value(&Input::new("c", "constant_out")),
+            16.into()
+        );
+
+        let parameters = Parameters::from_iter([("size".to_string(), Value::from(1))]);
+        assert!(ComponentStore::load_with_parameters(MODEL, &parameters).is_err());
+        assert!(ComponentStore::load(&MODEL.replace("$value", "$undeclared")).is_err());
+        assert!(parse_parameter("value").is_err());
+    }
+
+    #[test]
+    fn test_model_source() {
+        let cs = ComponentStore::load(MODEL).unwrap();
+        let source = ModelSource::new(MODEL, &cs).unwrap();
+        let model = source.model(&cs).unwrap();
+        assert_eq!(model["parameters"], serde_json::json!({"value": 3}));
+        assert_eq!(model["store"][0]["value"]["data"]["Data"], "$value");
+        assert_eq!(model["store"][1]["id"], "$$p");
+        let cs = ComponentStore::load(&model.to_string()).unwrap();
+        assert_eq!(cs.store.len(), 2);
+
+        // edited values are kept
+        let edited = ComponentStore::load(&MODEL.replace("\"$value\"", "5")).unwrap();
+        let model = source.model(&edited).unwrap();
+        assert_eq!(model["store"][0]["value"]["data"]["Data"], 5);
+        assert_eq!(model["store"][1]["id"], "$$p");
+    }
+}
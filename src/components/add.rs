@@ -2,7 +2,7 @@
 use crate::common::EguiComponent;
 use crate::common::{
     Component, Condition, Id, Input, InputPort, OutputType, Ports, SignalSigned, SignalUnsigned,
-    SignalValue, Simulator,
+    SignalValue, SignalWide, SignalWideSigned, Simulator,
 };
 use log::*;
 use serde::{Deserialize, Serialize};
@@ -14,6 +14,12 @@ pub const ADD_B_IN_ID: &str = "b_in";
 pub const ADD_OUT_ID: &str = "add_out";
 pub const ADD_OVERFLOW_ID: &str = "overflow";
 
+// port and output indices, in `get_id_ports` order
+const A_IN: usize = 0;
+const B_IN: usize = 1;
+const OUT: usize = 0;
+const OVERFLOW: usize = 1;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Add {
     pub(crate) id: Id,
@@ -62,13 +68,25 @@ impl Component for Add {
     // propagate addition to output
     fn clock(&self, simulator: &mut Simulator) -> Result<(), Condition> {
         // get input values
-        let a_in = u32::try_from(simulator.get_input_value_mut(self.id.clone(), &self.a_in));
-        let b_in = u32::try_from(simulator.get_input_value_mut(self.id.clone(), &self.b_in));
+        let a_in = simulator.get_port_value_mut(A_IN);
+        let b_in = simulator.get_port_value_mut(B_IN);
+        // 64 bit addition if any of the inputs is wide
+        let wide = matches!(a_in, SignalValue::Wide(_)) || matches!(b_in, SignalValue::Wide(_));
 
-        let (value, overflow, res) = match (&a_in, &b_in) {
+        let (value, overflow, res) = match (SignalWide::try_from(a_in), SignalWide::try_from(b_in))
+        {
+            (Ok(a), Ok(b)) if wide => {
+                let (res, overflow) =
+                    SignalWideSigned::overflowing_add(a as SignalWideSigned, b as SignalWideSigned);
+                (
+                    SignalValue::Wide(res as SignalWide),
+                    (overflow as SignalUnsigned).into(),
+                    Ok(()),
+                )
+            }
             (Ok(a), Ok(b)) => {
                 let (res, overflow) =
-                    SignalSigned::overflowing_add(*a as SignalSigned, *b as SignalSigned);
+                    SignalSigned::overflowing_add(a as SignalSigned, b as SignalSigned);
                 (
                     (res as SignalUnsigned).into(),
                     (overflow as SignalUnsigned).into(),
@@ -91,8 +109,8 @@ impl Component for Add {
         );
 
         // set output
-        simulator.set_out_value(&self.id, ADD_OUT_ID, value);
-        simulator.set_out_value(&self.id, ADD_OVERFLOW_ID, overflow);
+        simulator.set_output_value(OUT, value);
+        simulator.set_output_value(OVERFLOW, overflow);
         res
     }
 
@@ -214,4 +232,35 @@ mod test {
             (true as SignalUnsigned).into()
         );
     }
+
+    #[test]
+    fn test_add_wide() {
+        let cs = ComponentStore {
+            store: vec![
+                Rc::new(ProbeOut::new("po1")),
+                Rc::new(ProbeOut::new("po2")),
+                Add::rc_new(
+                    "add",
+                    (0.0, 0.0),
+                    Input::new("po1", "out"),
+                    Input::new("po2", "out"),
+                ),
+            ],
+        };
+        let mut simulator = Simulator::new(cs).unwrap();
+        let add_val = &Input::new("add", ADD_OUT_ID);
+
+        // a wide input gives a wide sum
+        simulator.set_out_value("po1", "out", SignalValue::Wide(0xFFFF_FFFF));
+        simulator.set_out_value("po2", "out", 1);
+        simulator.clock();
+        assert_eq!(
+            simulator.get_input_value(add_val),
+            SignalValue::Wide(0x1_0000_0000)
+        );
+        assert_eq!(
+            simulator.get_input_value(&Input::new("add", ADD_OVERFLOW_ID)),
+            (false as SignalUnsigned).into()
+        );
+    }
 }
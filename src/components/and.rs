@@ -1,8 +1,6 @@
 #[cfg(feature = "gui-egui")]
 use crate::common::EguiComponent;
-use crate::common::{
-    Component, Condition, Id, Input, InputPort, OutputType, Ports, SignalValue, Simulator,
-};
+use crate::common::{Component, Condition, Id, Input, InputPort, OutputType, Ports, Simulator};
 use log::*;
 use serde::{Deserialize, Serialize};
 use std::any::Any;
@@ -12,6 +10,11 @@ pub const AND_B_IN_ID: &str = "b_in";
 
 pub const AND_OUT_ID: &str = "and_out";
 
+// port and output indices, in `get_id_ports` order
+const A_IN: usize = 0;
+const B_IN: usize = 1;
+const OUT: usize = 0;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct And {
     pub(crate) id: Id,
@@ -58,12 +61,12 @@ impl Component for And {
     // propagate addition to output
     fn clock(&self, simulator: &mut Simulator) -> Result<(), Condition> {
         // get input values
-        let a_in: u32 = simulator.get_input_value(&self.a_in).try_into().unwrap();
-        let b_in: u32 = simulator.get_input_value(&self.b_in).try_into().unwrap();
+        let a_in = simulator.get_port_value(A_IN);
+        let b_in = simulator.get_port_value(B_IN);
 
-        let result: u32 = a_in & b_in;
+        let result = a_in & b_in;
 
-        simulator.set_out_value(&self.id, AND_OUT_ID, SignalValue::Data(result));
+        simulator.set_output_value(OUT, result);
         Ok(())
     }
 
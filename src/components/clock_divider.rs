@@ -0,0 +1,126 @@
+#[cfg(feature = "gui-egui")]
+use crate::common::EguiComponent;
+use crate::common::{Component, Condition, Id, OutputType, Ports, Simulator};
+use crate::components::SUB_CIRCUIT_SEPARATOR;
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::rc::Rc;
+
+// Clock divider, declaring the clock domain named by its id, clocking `components` in cycles
+// where `cycle % divisor == phase`. Component ids are relative to the (sub-circuit) model of
+// the divider. The domain is added when elaborating, the divider has no ports.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ClockDivider {
+    pub(crate) id: Id,
+    pub(crate) pos: (f32, f32),
+    pub(crate) divisor: usize,
+    #[serde(default)]
+    pub(crate) phase: usize,
+    pub(crate) components: Vec<Id>,
+}
+
+#[typetag::serde]
+impl Component for ClockDivider {
+    fn to_(&self) {
+        trace!("clock_divider");
+    }
+
+    #[cfg(feature = "gui-egui")]
+    fn dummy(&self, id: &str, pos: (f32, f32)) -> Box<Rc<dyn EguiComponent>> {
+        Box::new(Rc::new(ClockDivider::new(id, pos, 2, 0, vec![])))
+    }
+
+    fn get_id_ports(&self) -> (Id, Ports) {
+        (
+            self.id.clone(),
+            Ports::new(vec![], OutputType::Combinatorial, vec![]),
+        )
+    }
+
+    fn clock(&self, _simulator: &mut Simulator) -> Result<(), Condition> {
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl ClockDivider {
+    pub fn new(
+        id: &str,
+        pos: (f32, f32),
+        divisor: usize,
+        phase: usize,
+        components: Vec<&str>,
+    ) -> Self {
+        ClockDivider {
+            id: id.to_string(),
+            pos,
+            divisor,
+            phase,
+            components: components.into_iter().map(|id| id.to_string()).collect(),
+        }
+    }
+
+    pub fn rc_new(
+        id: &str,
+        pos: (f32, f32),
+        divisor: usize,
+        phase: usize,
+        components: Vec<&str>,
+    ) -> Rc<Self> {
+        Rc::new(ClockDivider::new(id, pos, divisor, phase, components))
+    }
+
+    // ids of the components in the domain, within the model of the divider
+    pub(crate) fn component_ids(&self) -> impl Iterator<Item = Id> + '_ {
+        let prefix = match self.id.rfind(SUB_CIRCUIT_SEPARATOR) {
+            Some(end) => &self.id[..end + SUB_CIRCUIT_SEPARATOR.len()],
+            None => "",
+        };
+        self.components
+            .iter()
+            .map(move |id| format!("{}{}", prefix, id))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{ComponentStore, Input, SignalValue};
+    use crate::components::ProbeStim;
+    use crate::components::{Register, REGISTER_OUT_ID};
+    use crate::elaboration::ElaborationProblem;
+
+    #[test]
+    fn test_clock_divider() {
+        let cs = ComponentStore {
+            store: vec![
+                ProbeStim::rc_new("in", (0.0, 0.0), vec![1, 2, 3, 4, 5]),
+                Register::rc_new("reg", (0.0, 0.0), Input::new("in", "out")),
+                ClockDivider::rc_new("half", (0.0, 0.0), 2, 1, vec!["reg"]),
+            ],
+        };
+        let json = serde_json::to_string(&cs).unwrap();
+        let mut simulator = Simulator::new(ComponentStore::load(&json).unwrap()).unwrap();
+        assert_eq!(simulator.clock_domain("reg").unwrap().name, "half");
+        let mut values = vec![];
+        for _ in 0..4 {
+            simulator.clock();
+            values.push(simulator.get_input_value(&Input::new("reg", REGISTER_OUT_ID)));
+        }
+        // loaded in cycles 1 and 3
+        assert_eq!(values, [1, 1, 3, 3].map(SignalValue::from));
+
+        let cs = ComponentStore {
+            store: vec![ClockDivider::rc_new("half", (0.0, 0.0), 0, 0, vec!["reg"])],
+        };
+        let problems = Simulator::new(cs).err().unwrap().problems;
+        assert!(matches!(
+            &problems[..],
+            [ElaborationProblem::ClockDomain { id, .. }] if id == "half"
+        ));
+    }
+}
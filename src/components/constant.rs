@@ -5,6 +5,9 @@ use log::*;
 use serde::{Deserialize, Serialize};
 use std::{convert::Into, rc::Rc};
 pub const CONSTANT_OUT_ID: &str = "constant_out";
+
+// output index, in `get_id_ports` order
+const OUT: usize = 0;
 use std::any::Any;
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Constant {
@@ -39,7 +42,7 @@ impl Component for Constant {
     }
 
     fn clock(&self, simulator: &mut Simulator) -> Result<(), Condition> {
-        simulator.set_out_value(&self.id, CONSTANT_OUT_ID, self.value.get_value());
+        simulator.set_output_value(OUT, self.value.get_value());
         Ok(())
     }
 
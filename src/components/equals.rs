@@ -1,6 +1,7 @@
 //TODO: add so it can take undefined number of inputs
 use crate::common::{
-    Component, Condition, Id, Input, InputPort, OutputType, Ports, SignalValue, Simulator,
+    Component, Condition, Id, Input, InputPort, OutputType, Ports, SignalUnsigned, SignalValue,
+    Simulator,
 };
 use log::*;
 use serde::{Deserialize, Serialize};
@@ -11,6 +12,11 @@ pub const EQUAL_B_IN_ID: &str = "b_in";
 
 pub const EQUAL_OUT_ID: &str = "equals_out";
 
+// port and output indices, in `get_id_ports` order
+const A_IN: usize = 0;
+const B_IN: usize = 1;
+const OUT: usize = 0;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Equal {
     pub(crate) id: Id,
@@ -48,12 +54,15 @@ impl Component for Equal {
     // propagate addition to output
     fn clock(&self, simulator: &mut Simulator) -> Result<(), Condition> {
         // get input values
-        let a_in: u32 = simulator.get_input_value(&self.a_in).try_into().unwrap();
-        let b_in: u32 = simulator.get_input_value(&self.b_in).try_into().unwrap();
+        let a_in = simulator.get_port_value(A_IN);
+        let b_in = simulator.get_port_value(B_IN);
 
-        let result: u32 = (a_in == b_in) as u32;
+        let result = match (a_in.as_wide(), b_in.as_wide()) {
+            (Some(a), Some(b)) => SignalValue::Data((a == b) as SignalUnsigned),
+            _ => SignalValue::Unknown,
+        };
 
-        simulator.set_out_value(&self.id, EQUAL_OUT_ID, SignalValue::Data(result));
+        simulator.set_output_value(OUT, result);
         Ok(())
     }
 
@@ -4,6 +4,8 @@ use crate::common::{
     Component, Condition, Id, Input, InputPort, OutputType, Ports, SignalSigned, SignalUnsigned,
     SignalValue, Simulator,
 };
+use crate::coverage::CoverBin;
+use crate::fault::FaultError;
 use log::*;
 use num_enum::IntoPrimitive;
 use num_enum::TryFromPrimitive;
@@ -22,6 +24,15 @@ pub const MEM_SIZE_ID: &str = "size";
 pub const MEM_DATA_OUT_ID: &str = "data_o";
 pub const MEM_ERR_OUT_ID: &str = "err";
 
+// port and output indices, in `get_id_ports` order
+const DATA: usize = 0;
+const ADDR: usize = 1;
+const CTRL: usize = 2;
+const SEXT: usize = 3;
+const SIZE: usize = 4;
+const DATA_OUT: usize = 0;
+const ERR_OUT: usize = 1;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Mem {
     pub(crate) id: Id,
@@ -42,7 +53,9 @@ pub struct Mem {
     // memory
     pub(crate) memory: Memory,
     pub(crate) range: Range<u32>,
-    // later history... tbd
+    // bytes corrupted by fault injection as (cycle, address, byte before), writes are not undone
+    #[serde(skip)]
+    pub(crate) history: RefCell<Vec<(usize, usize, Option<u8>)>>,
 }
 
 impl Mem {
@@ -74,6 +87,20 @@ impl Mem {
             size,
             memory: Memory::new(memory),
             range,
+            history: RefCell::default(),
+        }
+    }
+
+    // undo the corruptions done in `cycle` or later, last first
+    fn revert_corruptions(&self, cycle: usize) {
+        let mut history = self.history.borrow_mut();
+        let mut memory = self.memory.0.borrow_mut();
+        while let Some(&(_, address, byte)) = history.last().filter(|(at, ..)| *at >= cycle) {
+            match byte {
+                Some(byte) => memory.insert(address, byte),
+                None => memory.remove(&address),
+            };
+            history.pop();
         }
     }
 
@@ -120,6 +147,12 @@ impl Mem {
             id, pos, width, height, big_endian, data, addr, ctrl, sext, size, memory, range,
         ))
     }
+
+    // outputs of an operation on unknown inputs
+    fn set_unknown(&self, simulator: &mut Simulator) {
+        simulator.set_output_value(DATA_OUT, SignalValue::Unknown);
+        simulator.set_output_value(ERR_OUT, SignalValue::Unknown);
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -335,6 +368,7 @@ impl Component for Mem {
                 end: 0x20,
             },
             memory: Memory::new(BTreeMap::new()),
+            history: RefCell::default(),
         }))
     }
     fn get_id_ports(&self) -> (Id, Ports) {
@@ -370,51 +404,88 @@ impl Component for Mem {
     }
 
     fn clock(&self, simulator: &mut Simulator) -> Result<(), Condition> {
-        let data = simulator.get_input_value(&self.data);
-        let addr = simulator.get_input_value(&self.addr);
-        let size = simulator.get_input_value(&self.size);
-        let sign = simulator.get_input_value(&self.sext);
-
-        match simulator.get_input_value(&self.ctrl) {
-            SignalValue::Data(ctrl) => {
-                let ctrl = MemCtrl::try_from(ctrl as u8).unwrap();
-                match ctrl {
-                    MemCtrl::Read => {
-                        let addr: u32 = addr.try_into().unwrap();
-                        let size: u32 = size.try_into().unwrap();
-                        let sign: u32 = sign.try_into().unwrap();
-                        trace!("read addr {:?} size {:?}", addr, size);
-                        let value = self.memory.read(
-                            addr as usize,
-                            size as usize,
-                            sign != 0,
-                            self.big_endian,
-                        );
-                        simulator.set_out_value(&self.id, "data_o", value);
-                        let value = self.memory.align(addr as usize, size as usize);
-                        trace!("align {:?}", value);
-                        simulator.set_out_value(&self.id, "err", value); // align
+        let data = simulator.get_port_value(DATA);
+        let addr = simulator.get_port_value(ADDR);
+        let size = simulator.get_port_value(SIZE);
+        let sign = simulator.get_port_value(SEXT);
+
+        let ctrl = simulator.get_port_value(CTRL);
+        let res = match SignalUnsigned::try_from(ctrl) {
+            Ok(ctrl) => match u8::try_from(ctrl)
+                .ok()
+                .and_then(|ctrl| MemCtrl::try_from(ctrl).ok())
+            {
+                Some(MemCtrl::Read) => {
+                    match (
+                        usize::try_from(addr),
+                        usize::try_from(size),
+                        SignalUnsigned::try_from(sign),
+                    ) {
+                        (Ok(addr), Ok(size @ (1 | 2 | 4)), Ok(sign)) => {
+                            trace!("read addr {:?} size {:?}", addr, size);
+                            let value = self.memory.read(addr, size, sign != 0, self.big_endian);
+                            simulator.set_output_value(DATA_OUT, value);
+                            let value = self.memory.align(addr, size);
+                            trace!("align {:?}", value);
+                            simulator.set_output_value(ERR_OUT, value); // align
+                            Ok(())
+                        }
+                        (Ok(_), Ok(size), Ok(_)) => {
+                            self.set_unknown(simulator);
+                            Err(Condition::Error(format!("illegal memory size {}", size)))
+                        }
+                        _ => {
+                            // unknown address, size or sign
+                            self.set_unknown(simulator);
+                            Ok(())
+                        }
                     }
-                    MemCtrl::Write => {
-                        let addr: u32 = addr.try_into().unwrap();
-                        let size: u32 = size.try_into().unwrap();
-                        trace!("write addr {:?} size {:?}", addr, size);
-                        self.memory
-                            .write(addr as usize, size as usize, self.big_endian, data);
-                        let value = self.memory.align(addr as usize, size as usize);
-                        trace!("align {:?}", value);
-                        simulator.set_out_value(&self.id, "err", value); // align
-                    }
-                    MemCtrl::None => {
-                        trace!("no read/write");
+                }
+                Some(MemCtrl::Write) => {
+                    match (usize::try_from(addr), usize::try_from(size)) {
+                        (Ok(addr), Ok(size @ (1 | 2 | 4))) => {
+                            let value = self.memory.align(addr, size);
+                            trace!("align {:?}", value);
+                            simulator.set_output_value(ERR_OUT, value); // align
+                            match SignalUnsigned::try_from(data) {
+                                Ok(data) => {
+                                    trace!("write addr {:?} size {:?}", addr, size);
+                                    self.memory.write(addr, size, self.big_endian, data.into());
+                                    Ok(())
+                                }
+                                // memory holds known bytes only
+                                Err(_) => Err(Condition::Warning(format!(
+                                    "write of unknown data to {:#x} ignored",
+                                    addr
+                                ))),
+                            }
+                        }
+                        (Ok(_), Ok(size)) => {
+                            simulator.set_output_value(ERR_OUT, SignalValue::Unknown);
+                            Err(Condition::Error(format!("illegal memory size {}", size)))
+                        }
+                        _ => {
+                            simulator.set_output_value(ERR_OUT, SignalValue::Unknown);
+                            Err(Condition::Warning(
+                                "write to unknown address ignored".to_string(),
+                            ))
+                        }
                     }
                 }
+                Some(MemCtrl::None) => {
+                    trace!("no read/write");
+                    Ok(())
+                }
+                None => {
+                    self.set_unknown(simulator);
+                    Err(Condition::Error(format!("illegal memory control {}", ctrl)))
+                }
+            },
+            Err(_) => {
+                self.set_unknown(simulator);
+                Ok(())
             }
-            _ => {
-                simulator.set_out_value(&self.id, "data_o", SignalValue::Unknown);
-                simulator.set_out_value(&self.id, "err", SignalValue::Unknown); // align
-            }
-        }
+        };
 
         for (idx, i) in self.memory.0.borrow().iter().enumerate() {
             if i.0 % 4 == 0 && idx < 40 {
@@ -430,7 +501,7 @@ impl Component for Mem {
             }
         }
 
-        Ok(())
+        res
     }
 
     fn set_id_port(&mut self, target_port_id: Id, new_input: Input) {
@@ -444,6 +515,70 @@ impl Component for Mem {
         }
     }
 
+    fn un_clock(&self, simulator: &Simulator) {
+        self.revert_corruptions(simulator.cycle);
+    }
+
+    fn reset(&self) {
+        self.revert_corruptions(0);
+    }
+
+    fn snapshot(&self, history: bool) -> Option<serde_json::Value> {
+        match history {
+            true => serde_json::to_value((&self.memory, &self.history)).ok(),
+            false => serde_json::to_value((&self.memory, Vec::<()>::new())).ok(),
+        }
+    }
+
+    fn restore(&self, state: &serde_json::Value) -> Result<(), String> {
+        let (memory, history): (BTreeMap<usize, u8>, _) =
+            serde_json::from_value(state.clone()).map_err(|err| err.to_string())?;
+        *self.memory.0.borrow_mut() = memory;
+        self.history.replace(history);
+        Ok(())
+    }
+
+    fn corrupt_memory(
+        &self,
+        simulator: &Simulator,
+        address: usize,
+        mask: u8,
+    ) -> Result<(), FaultError> {
+        let mut memory = self.memory.0.borrow_mut();
+        let byte = memory.get(&address).copied();
+        memory.insert(address, byte.unwrap_or_default() ^ mask);
+        self.history
+            .borrow_mut()
+            .push((simulator.cycle, address, byte));
+        Ok(())
+    }
+
+    // the operation and size of each access
+    fn coverage_bins(&self) -> Vec<CoverBin> {
+        let mut bins = vec![(MEM_CTRL_ID, format!("{:?}", MemCtrl::None))];
+        for ctrl in [MemCtrl::Read, MemCtrl::Write] {
+            for size in [1, 2, 4] {
+                bins.push((MEM_CTRL_ID, format!("{:?} {}", ctrl, size)));
+            }
+        }
+        bins
+    }
+
+    fn coverage(&self, simulator: &Simulator) -> Vec<CoverBin> {
+        let ctrl = SignalUnsigned::try_from(simulator.get_input_value(&self.ctrl))
+            .ok()
+            .and_then(|ctrl| u8::try_from(ctrl).ok())
+            .and_then(|ctrl| MemCtrl::try_from(ctrl).ok());
+        let size = SignalUnsigned::try_from(simulator.get_input_value(&self.size));
+        match (ctrl, size) {
+            (Some(MemCtrl::None), _) => vec![(MEM_CTRL_ID, format!("{:?}", MemCtrl::None))],
+            (Some(ctrl), Ok(size @ (1 | 2 | 4))) => {
+                vec![(MEM_CTRL_ID, format!("{:?} {}", ctrl, size))]
+            }
+            _ => vec![],
+        }
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -495,6 +630,7 @@ mod test {
                         start: 0u32,
                         end: 1u32,
                     },
+                    history: RefCell::default(),
                 }),
             ],
         };
@@ -678,6 +814,7 @@ mod test {
                         start: 0u32,
                         end: 1u32,
                     },
+                    history: RefCell::default(),
                 }),
             ],
         };
@@ -771,4 +908,62 @@ mod test {
         assert_eq!(simulator.cycle, 9);
         assert_eq!(simulator.get_input_value(out), 0x12.into());
     }
+
+    #[test]
+    fn test_mem_unknown() {
+        let cs = ComponentStore {
+            store: vec![
+                Rc::new(ProbeOut::new("data")),
+                Rc::new(ProbeOut::new("addr")),
+                Rc::new(ProbeOut::new("ctrl")),
+                Rc::new(ProbeOut::new("size")),
+                Rc::new(ProbeOut::new("sign")),
+                Mem::rc_new(
+                    "mem",
+                    (0.0, 0.0),
+                    0.0,
+                    0.0,
+                    false,
+                    Input::new("data", "out"),
+                    Input::new("addr", "out"),
+                    Input::new("ctrl", "out"),
+                    Input::new("sign", "out"),
+                    Input::new("size", "out"),
+                    0..16,
+                ),
+            ],
+        };
+        let mut simulator = Simulator::new(cs).unwrap();
+        let out = &Input::new("mem", MEM_DATA_OUT_ID);
+        let err = &Input::new("mem", MEM_ERR_OUT_ID);
+
+        // read from unknown address gives unknown outputs
+        simulator.set_out_value("ctrl", "out", MemCtrl::Read as SignalUnsigned);
+        simulator.set_out_value("size", "out", 4);
+        simulator.set_out_value("addr", "out", SignalValue::Unknown);
+        simulator.clock();
+        assert_eq!(simulator.get_input_value(out), SignalValue::Unknown);
+        assert_eq!(simulator.get_input_value(err), SignalValue::Unknown);
+        assert!(simulator.component_condition.is_empty());
+
+        // write of unknown data is ignored
+        simulator.set_out_value("ctrl", "out", MemCtrl::Write as SignalUnsigned);
+        simulator.set_out_value("addr", "out", 4);
+        simulator.set_out_value("data", "out", SignalValue::DontCare);
+        simulator.clock();
+        assert_eq!(simulator.get_input_value(err), false.into());
+        assert!(matches!(
+            simulator.component_condition[..],
+            [(_, Condition::Warning(_))]
+        ));
+
+        // illegal size and control are errors
+        simulator.set_out_value("size", "out", 3);
+        simulator.clock();
+        assert_eq!(simulator.get_input_value(err), SignalValue::Unknown);
+        assert!(matches!(
+            simulator.component_condition[..],
+            [(_, Condition::Error(_))]
+        ));
+    }
 }
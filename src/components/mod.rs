@@ -1,5 +1,6 @@
 mod add;
 mod and;
+mod clock_divider;
 mod constant;
 mod cross;
 mod equals;
@@ -14,12 +15,15 @@ mod probe_stim;
 mod register;
 mod sext;
 mod shift_left_const;
+mod sub_circuit;
+mod test_vectors;
 //mod sz_extend;
 mod wire;
 mod zero_extend;
 
 pub use add::*;
 pub use and::*;
+pub use clock_divider::*;
 pub use constant::*;
 pub use cross::*;
 pub use equals::*;
@@ -34,6 +38,8 @@ pub use probe_stim::*;
 pub use register::*;
 pub use sext::*;
 pub use shift_left_const::*;
+pub use sub_circuit::*;
+pub use test_vectors::*;
 //pub use sz_extend::*;
 pub use wire::*;
 pub use zero_extend::*;
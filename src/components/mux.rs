@@ -4,6 +4,7 @@ use crate::common::{
     Component, Condition, Id, Input, InputPort, OutputType, Ports, SignalUnsigned, SignalValue,
     Simulator,
 };
+use crate::coverage::CoverBin;
 use log::*;
 use serde::{Deserialize, Serialize};
 use std::any::Any;
@@ -13,6 +14,11 @@ pub const MUX_SELECT_ID: &str = "select";
 pub const MUX_TEMPLATE_ID: &str = "in";
 pub const MUX_OUT_ID: &str = "mux_out";
 
+// port and output indices, in `get_id_ports` order, the inputs follow the select
+const SELECT: usize = 0;
+const M_IN: usize = 1;
+const OUT: usize = 0;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Mux {
     pub(crate) id: Id,
@@ -64,16 +70,13 @@ impl Component for Mux {
     // propagate selected input value to output
     fn clock(&self, simulator: &mut Simulator) -> Result<(), Condition> {
         // get input value
-        let select: SignalValue = simulator.get_input_value_mut(self.id.clone(), &self.select);
+        let select: SignalValue = simulator.get_port_value_mut(SELECT);
         trace!("-----------{}------------", self.id);
         let (value, res) = if let Ok(select) = TryInto::<SignalUnsigned>::try_into(select) {
             let select = select as usize;
             trace!("select {}", select);
             if select < self.m_in.len() {
-                (
-                    simulator.get_input_value_mut(self.id.clone(), &self.m_in[select]),
-                    Ok(()),
-                )
+                (simulator.get_port_value_mut(M_IN + select), Ok(()))
             } else {
                 (
                     SignalValue::Unknown,
@@ -81,17 +84,37 @@ impl Component for Mux {
                 )
             }
         } else {
+            // all inputs may be selected
+            let inputs: Vec<SignalValue> = (0..self.m_in.len())
+                .map(|i| simulator.get_port_value_mut(M_IN + i))
+                .collect();
             (
-                SignalValue::Unknown,
+                select.select(&inputs),
                 Err(Condition::Warning("select unknown".to_string())),
             )
         };
         trace!("-----------------value:{:?}, end---------------", value);
         // set output
-        simulator.set_out_value(&self.id, MUX_OUT_ID, value);
+        simulator.set_output_value(OUT, value);
         res
     }
 
+    // the input selected
+    fn coverage_bins(&self) -> Vec<CoverBin> {
+        (0..self.m_in.len())
+            .map(|i| (MUX_SELECT_ID, format!("{}{}", MUX_TEMPLATE_ID, i)))
+            .collect()
+    }
+
+    fn coverage(&self, simulator: &Simulator) -> Vec<CoverBin> {
+        match SignalUnsigned::try_from(simulator.get_input_value(&self.select)) {
+            Ok(select) if (select as usize) < self.m_in.len() => {
+                vec![(MUX_SELECT_ID, format!("{}{}", MUX_TEMPLATE_ID, select))]
+            }
+            _ => vec![],
+        }
+    }
+
     fn set_id_port(&mut self, target_port_id: Id, new_input: Input) {
         let target_port_id = target_port_id.as_str();
         if target_port_id == MUX_SELECT_ID {
@@ -10,6 +10,10 @@ pub const PASS_THROUGH_IN_ID: &str = "pass_through_in";
 
 pub const PASS_THROUGH_OUT_ID: &str = "pass_through_out";
 
+// port and output indices, in `get_id_ports` order
+const IN: usize = 0;
+const OUT: usize = 0;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct PassThrough {
     pub(crate) id: Id,
@@ -49,9 +53,9 @@ impl Component for PassThrough {
     // propagate input value to output
     fn clock(&self, simulator: &mut Simulator) -> Result<(), Condition> {
         // get input value
-        let value = simulator.get_input_value_mut(self.id.clone(), &self.input);
+        let value = simulator.get_port_value_mut(IN);
         // set output
-        simulator.set_out_value(&self.id, PASS_THROUGH_OUT_ID, value);
+        simulator.set_output_value(OUT, value);
         Ok(())
     }
 
@@ -8,6 +8,9 @@ use std::rc::Rc;
 
 pub const PROBE_IN_ID: &str = "in";
 
+// port index, in `get_id_ports` order
+const IN: usize = 0;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Probe {
     pub(crate) id: Id,
@@ -49,7 +52,7 @@ impl Component for Probe {
 
     fn clock(&self, simulator: &mut Simulator) -> Result<(), Condition> {
         // get input value
-        let value = simulator.get_input_value_mut(self.id.clone(), &self.input);
+        let value = simulator.get_port_value_mut(IN);
         trace!("probe: register id {} in {:?}", self.id, value);
         Ok(())
     }
@@ -1,5 +1,6 @@
 use crate::{
     common::{Component, Condition, Id, Input, InputPort, OutputType, Ports, Signal, Simulator},
+    components::VectorFile,
     signal::SignalValue,
 };
 use log::*;
@@ -9,12 +10,18 @@ use std::rc::Rc;
 
 pub const PROBE_ASSERT_IN_ID: &str = "in";
 
+// Assertion, the input equals the value of the current cycle (any value if don't care),
+// given by values or read from a vector file
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ProbeAssert {
     pub(crate) id: Id,
     pub(crate) pos: (f32, f32),
     pub(crate) input: Input,
+    #[serde(default)]
     pub(crate) values: Vec<Signal>,
+    // values read from a file instead, when elaborated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) vectors: Option<VectorFile>,
 }
 
 #[typetag::serde]
@@ -40,15 +47,16 @@ impl Component for ProbeAssert {
     fn clock(&self, simulator: &mut Simulator) -> Result<(), Condition> {
         trace!("-- cycle {} --", simulator.cycle);
         let lhs = simulator.get_input_value(&self.input);
-        let rhs = match self.values.get(simulator.cycle) {
+        let rhs = match self.values().get(simulator.cycle) {
             Some(rhs) => rhs.get_value(),
             _ => SignalValue::Unknown,
         };
+        let holds = rhs == SignalValue::DontCare || lhs == rhs;
 
         // the assertion is checked only in test mode
         #[cfg(test)]
-        assert_eq!(lhs, rhs);
-        if lhs == rhs {
+        assert!(holds, "assertion failed {:?} != {:?}", lhs, rhs);
+        if holds {
             Ok(())
         } else {
             Err(Condition::Assert(format!(
@@ -63,6 +71,11 @@ impl Component for ProbeAssert {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    // asserts the value of each cycle
+    fn always_clock(&self) -> bool {
+        true
+    }
 }
 
 impl ProbeAssert {
@@ -72,6 +85,26 @@ impl ProbeAssert {
             pos,
             input,
             values: values.into_iter().map(|v| v.into()).collect(),
+            vectors: None,
+        }
+    }
+
+    /// assertion of values read from a vector file, when elaborated
+    pub fn new_with_vectors(id: &str, pos: (f32, f32), input: Input, vectors: VectorFile) -> Self {
+        ProbeAssert {
+            id: id.to_string(),
+            pos,
+            input,
+            values: vec![],
+            vectors: Some(vectors),
+        }
+    }
+
+    /// values per cycle
+    pub fn values(&self) -> &[Signal] {
+        match &self.vectors {
+            Some(vectors) => &vectors.values,
+            None => &self.values,
         }
     }
 
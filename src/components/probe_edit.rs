@@ -18,7 +18,7 @@ pub struct ProbeEdit {
     pub(crate) edit_history: Arc<RwLock<Vec<TextSignal>>>, // will contain the next editable value
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct TextSignal {
     pub text: String,
     pub signal: Signal,
@@ -72,6 +72,27 @@ impl Component for ProbeEdit {
         edit_history.push(prev); // push as next (to be edited)
     }
 
+    fn snapshot(&self, history: bool) -> Option<serde_json::Value> {
+        let edit_history = self.edit_history.read().unwrap();
+        match history {
+            true => serde_json::to_value(&*edit_history).ok(),
+            // the current and next value, needed to un_clock back to the snapshot
+            false => {
+                serde_json::to_value(&edit_history[edit_history.len().saturating_sub(2)..]).ok()
+            }
+        }
+    }
+
+    fn restore(&self, state: &serde_json::Value) -> Result<(), String> {
+        let edit_history: Vec<TextSignal> =
+            serde_json::from_value(state.clone()).map_err(|err| err.to_string())?;
+        if edit_history.is_empty() {
+            return Err("empty edit history".to_string());
+        }
+        *self.edit_history.write().unwrap() = edit_history;
+        Ok(())
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -93,4 +114,25 @@ impl ProbeEdit {
     pub fn rc_new(id: &str, pos: (f32, f32)) -> Rc<Self> {
         Rc::new(ProbeEdit::new(id, pos))
     }
+
+    /// the value output in the next cycle
+    pub fn next_value(&self) -> TextSignal {
+        self.edit_history.read().unwrap().last().unwrap().clone()
+    }
+
+    /// set the value output in the next cycle
+    pub fn set_next_value(&self, value: TextSignal) {
+        *self.edit_history.write().unwrap().last_mut().unwrap() = value;
+    }
+
+    /// the values output in the last `cycles` cycles, oldest first
+    pub fn output_history(&self, cycles: usize) -> Vec<Signal> {
+        let history = self.edit_history.read().unwrap();
+        // the last entry is the next value, not yet output
+        let outputs = &history[..history.len() - 1];
+        outputs[outputs.len().saturating_sub(cycles)..]
+            .iter()
+            .map(|value| value.signal)
+            .collect()
+    }
 }
@@ -1,14 +1,20 @@
 use crate::common::{Component, Condition, Id, OutputType, Ports, Signal, SignalValue, Simulator};
+use crate::components::VectorFile;
 use log::*;
 use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::rc::Rc;
 
+// Stimuli, outputs the value of the current cycle, given by values or read from a vector file
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ProbeStim {
     pub(crate) id: Id,
     pub(crate) pos: (f32, f32),
+    #[serde(default)]
     pub(crate) values: Vec<Signal>,
+    // values read from a file instead, when elaborated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) vectors: Option<VectorFile>,
 }
 
 #[typetag::serde]
@@ -31,7 +37,7 @@ impl Component for ProbeStim {
 
     fn clock(&self, simulator: &mut Simulator) -> Result<(), Condition> {
         trace!("-- cycle {} --", simulator.cycle);
-        let (out, res) = if let Some(signal) = self.values.get(simulator.cycle) {
+        let (out, res) = if let Some(signal) = self.values().get(simulator.cycle) {
             (signal.get_value(), Ok(()))
         } else {
             (
@@ -58,6 +64,25 @@ impl ProbeStim {
             id: id.to_string(),
             pos,
             values: values.into_iter().map(|v| v.into()).collect(),
+            vectors: None,
+        }
+    }
+
+    /// stimuli read from a vector file, when elaborated
+    pub fn new_with_vectors(id: &str, pos: (f32, f32), vectors: VectorFile) -> Self {
+        ProbeStim {
+            id: id.to_string(),
+            pos,
+            values: vec![],
+            vectors: Some(vectors),
+        }
+    }
+
+    /// values per cycle
+    pub fn values(&self) -> &[Signal] {
+        match &self.vectors {
+            Some(vectors) => &vectors.values,
+            None => &self.values,
         }
     }
 
@@ -1,6 +1,9 @@
 #[cfg(feature = "gui-egui")]
 use crate::common::EguiComponent;
-use crate::common::{Component, Condition, Id, Input, InputPort, OutputType, Ports, Simulator};
+use crate::common::{
+    Component, Condition, Id, Input, InputPort, OutputType, Ports, SignalUnsigned, SignalValue,
+    SignalWide, Simulator,
+};
 use log::*;
 use serde::{Deserialize, Serialize};
 use std::any::Any;
@@ -8,13 +11,35 @@ use std::rc::Rc;
 
 pub const REGISTER_R_IN_ID: &str = "r_in";
 
+pub const REGISTER_ENABLE_ID: &str = "enable";
+
+pub const REGISTER_RESET_ID: &str = "reset";
+
 pub const REGISTER_OUT_ID: &str = "register_out";
 
+// port and output indices, in `get_id_ports` order,
+// the optional enable and reset ports follow the input
+const R_IN: usize = 0;
+const OUT: usize = 0;
+
+// Register, with optional clock enable and synchronous reset (taking precedence over enable)
+// the output holds its value while enable is low, and is set to the reset value while reset is high,
+// an unknown (X) enable or reset gives an unknown output
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Register {
     pub(crate) id: Id,
     pub(crate) pos: (f32, f32),
     pub(crate) r_in: Input,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) enable: Option<Input>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) reset: Option<Input>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub(crate) reset_value: SignalWide,
+}
+
+fn is_zero(value: &SignalWide) -> bool {
+    *value == 0
 }
 
 #[typetag::serde]
@@ -29,36 +54,85 @@ impl Component for Register {
             id: id.to_string(),
             pos: (pos.0, pos.1),
             r_in: dummy_input.clone(),
+            enable: None,
+            reset: None,
+            reset_value: 0,
         }))
     }
     fn get_id_ports(&self) -> (Id, Ports) {
+        let mut inputs = vec![InputPort {
+            port_id: REGISTER_R_IN_ID.to_string(),
+            input: self.r_in.clone(),
+        }];
+        if let Some(enable) = &self.enable {
+            inputs.push(InputPort {
+                port_id: REGISTER_ENABLE_ID.to_string(),
+                input: enable.clone(),
+            });
+        }
+        if let Some(reset) = &self.reset {
+            inputs.push(InputPort {
+                port_id: REGISTER_RESET_ID.to_string(),
+                input: reset.clone(),
+            });
+        }
         (
             self.id.clone(),
-            Ports::new(
-                // Vector of inputs
-                vec![&InputPort {
-                    port_id: REGISTER_R_IN_ID.to_string(),
-                    input: self.r_in.clone(),
-                }],
-                OutputType::Sequential,
-                vec![REGISTER_OUT_ID],
-            ),
+            Ports {
+                inputs,
+                out_type: OutputType::Sequential,
+                outputs: vec![REGISTER_OUT_ID.to_string()],
+            },
         )
     }
 
     // propagate input value to output
     fn clock(&self, simulator: &mut Simulator) -> Result<(), Condition> {
-        // get input value
-        let value = simulator.get_input_value_mut(self.id.clone(), &self.r_in);
+        let enable = self.enable.as_ref().map(|_| R_IN + 1);
+        let reset = self
+            .reset
+            .as_ref()
+            .map(|_| R_IN + 1 + self.enable.is_some() as usize);
+        // control input high, None if X, `default` if not connected
+        let mut active = |port: Option<usize>, default: bool| match port {
+            Some(port) => simulator
+                .get_port_value_mut(port)
+                .as_wide()
+                .map(|data| data != 0),
+            None => Some(default),
+        };
+        let reset = active(reset, false);
+        let enable = active(enable, true);
+        let (value, res) = match (reset, enable) {
+            (Some(true), _) => (self.reset_signal(), Ok(())),
+            // get input value
+            (Some(false), Some(true)) => (simulator.get_port_value_mut(R_IN), Ok(())),
+            (Some(false), Some(false)) => {
+                // hold
+                trace!("eval: register id {} disabled", self.id);
+                return Ok(());
+            }
+            (None, _) => (
+                SignalValue::Unknown,
+                Err(Condition::Warning("reset unknown".to_string())),
+            ),
+            (Some(false), None) => (
+                SignalValue::Unknown,
+                Err(Condition::Warning("enable unknown".to_string())),
+            ),
+        };
         // set output
-        simulator.set_out_value(&self.id, REGISTER_OUT_ID, value);
+        simulator.set_output_value(OUT, value);
         trace!("eval: register id {} in {:?}", self.id, value);
-        Ok(())
+        res
     }
 
     fn set_id_port(&mut self, target_port_id: Id, new_input: Input) {
-        if target_port_id == REGISTER_R_IN_ID {
-            self.r_in = new_input;
+        match target_port_id.as_str() {
+            REGISTER_R_IN_ID => self.r_in = new_input,
+            REGISTER_ENABLE_ID => self.enable = Some(new_input),
+            REGISTER_RESET_ID => self.reset = Some(new_input),
+            _ => {}
         }
     }
 
@@ -77,10 +151,122 @@ impl Register {
             id: id.to_string(),
             pos,
             r_in,
+            enable: None,
+            reset: None,
+            reset_value: 0,
         }
     }
 
     pub fn rc_new(id: &str, pos: (f32, f32), r_in: Input) -> Rc<Self> {
         Rc::new(Register::new(id, pos, r_in))
     }
+
+    // the reset value, `Data` if it fits
+    fn reset_signal(&self) -> SignalValue {
+        match SignalUnsigned::try_from(self.reset_value) {
+            Ok(data) => SignalValue::Data(data),
+            Err(_) => SignalValue::Wide(self.reset_value),
+        }
+    }
+
+    /// register with clock enable and synchronous reset inputs, if given
+    pub fn rc_new_with_enable_reset(
+        id: &str,
+        pos: (f32, f32),
+        r_in: Input,
+        enable: Option<Input>,
+        reset: Option<Input>,
+        reset_value: SignalWide,
+    ) -> Rc<Self> {
+        Rc::new(Register {
+            id: id.to_string(),
+            pos,
+            r_in,
+            enable,
+            reset,
+            reset_value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::ComponentStore;
+    use crate::components::ProbeStim;
+
+    #[test]
+    fn test_register_enable_reset() {
+        let cs = ComponentStore {
+            store: vec![
+                ProbeStim::rc_new("in", (0.0, 0.0), vec![1, 2, 3, 4, 5, 6]),
+                ProbeStim::rc_new("en", (0.0, 0.0), vec![1, 0, 0, 1, 1, 0]),
+                ProbeStim::rc_new("rst", (0.0, 0.0), vec![0, 0, 1, 0, 0, 0]),
+                Register::rc_new_with_enable_reset(
+                    "reg",
+                    (0.0, 0.0),
+                    Input::new("in", "out"),
+                    Some(Input::new("en", "out")),
+                    Some(Input::new("rst", "out")),
+                    0x10,
+                ),
+            ],
+        };
+        let mut simulator = Simulator::new(cs).unwrap();
+        let out = Input::new("reg", REGISTER_OUT_ID);
+        let mut values = vec![];
+        for _ in 0..6 {
+            simulator.clock();
+            values.push(simulator.get_input_value(&out));
+        }
+        // inputs of the previous cycle: load, hold, reset (while disabled), load, load, hold
+        assert_eq!(
+            values,
+            [1, 1, 0x10, 4, 5, 5].map(SignalValue::from).to_vec()
+        );
+    }
+
+    #[test]
+    fn test_register_unknown_enable_reset() {
+        let x = SignalValue::Unknown;
+        let cs = ComponentStore {
+            store: vec![
+                ProbeStim::rc_new("in", (0.0, 0.0), vec![1, 2, 3, 4]),
+                ProbeStim::rc_new("en", (0.0, 0.0), vec![1.into(), x, 1.into(), 1.into()]),
+                ProbeStim::rc_new("rst", (0.0, 0.0), vec![0.into(), 0.into(), x, 1.into()]),
+                Register::rc_new_with_enable_reset(
+                    "reg",
+                    (0.0, 0.0),
+                    Input::new("in", "out"),
+                    Some(Input::new("en", "out")),
+                    Some(Input::new("rst", "out")),
+                    0x1_0000_0000,
+                ),
+            ],
+        };
+        let mut simulator = Simulator::new(cs).unwrap();
+        let out = Input::new("reg", REGISTER_OUT_ID);
+        let mut values = vec![];
+        for _ in 0..4 {
+            simulator.clock();
+            values.push(simulator.get_input_value(&out));
+        }
+        // load, X enable, X reset, reset to a wide value
+        assert_eq!(values, [1.into(), x, x, SignalValue::Wide(0x1_0000_0000)]);
+    }
+
+    #[test]
+    fn test_register_json() {
+        // models without enable and reset load, and are saved as before
+        let json =
+            r#"{"type":"Register","id":"reg","pos":[0.0,0.0],"r_in":{"id":"in","field":"out"}}"#;
+        let register: Register = serde_json::from_str(json).unwrap();
+        assert!(register.enable.is_none() && register.reset.is_none());
+        assert_eq!(register.reset_value, 0);
+        let saved = serde_json::to_string(&register).unwrap();
+        assert_eq!(
+            saved,
+            r#"{"id":"reg","pos":[0.0,0.0],"r_in":{"id":"in","field":"out"}}"#
+        );
+    }
 }
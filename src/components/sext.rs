@@ -14,6 +14,10 @@ pub const SEXT_IN_ID: &str = "sext_in";
 
 pub const SEXT_OUT_ID: &str = "sext_out";
 
+// port and output indices, in `get_id_ports` order
+const IN: usize = 0;
+const OUT: usize = 0;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Sext {
     pub(crate) id: Id,
@@ -62,33 +66,39 @@ impl Component for Sext {
     // propagate sign extension to output
     // TODO: always extend to Signal size? (it should not matter and should be slightly cheaper)
     fn clock(&self, simulator: &mut Simulator) -> Result<(), Condition> {
-        assert!(
-            self.out_size <= SignalUnsigned::BITS,
-            "{}: Output size {} is larger than maximum size {}",
-            self.id,
-            self.out_size,
-            SignalUnsigned::BITS
-        );
+        if !(0 < self.in_size
+            && self.in_size <= self.out_size
+            && self.out_size <= SignalUnsigned::BITS)
+        {
+            simulator.set_output_value(OUT, SignalValue::Unknown);
+            return Err(Condition::Error(format!(
+                "{}: Sizes must satisfy 0 < input size {} <= output size {} <= {}",
+                self.id,
+                self.in_size,
+                self.out_size,
+                SignalUnsigned::BITS
+            )));
+        }
 
         // get input values
-        match simulator.get_input_value(&self.sext_in) {
-            SignalValue::Data(mut value) => {
+        let value = match SignalUnsigned::try_from(simulator.get_port_value(IN)) {
+            Ok(mut value) => {
                 let to_sext = self.out_size - self.in_size; // Amount to be arithmetically shifted
                 let to_shl = SignalUnsigned::BITS - self.in_size; // To move input to MSB
                 let to_shr = to_shl - to_sext; // To shift the result back to LSB
 
                 value <<= to_shl;
                 value = ((value as SignalSigned) >> to_sext) as SignalUnsigned;
-                value >>= to_shr;
-
-                // set output
-                simulator.set_out_value(&self.id, SEXT_OUT_ID, SignalValue::Data(value));
-            }
-            _ => {
-                simulator.set_out_value(&self.id, SEXT_OUT_ID, SignalValue::Unknown);
-                trace!("{} unknown input", self.id);
+                SignalValue::Data(value >> to_shr)
             }
+            Err(_) => SignalValue::Unknown,
+        };
+        if !value.is_known() {
+            trace!("{} unknown input", self.id);
         }
+
+        // set output
+        simulator.set_output_value(OUT, value);
         Ok(())
     }
 
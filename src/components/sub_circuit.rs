@@ -0,0 +1,621 @@
+// Sub-circuit, a model (component store file) embedded as a component
+//
+// Components of the sub-circuit model refer to its input ports by port id, e.g.,
+// `{"id": "a", "field": "in"}` reads input port `a` (the field is not used). When the model
+// is elaborated (`Simulator::new`) sub-circuits are flattened: their components are added to
+// the model with ids prefixed by the sub-circuit id (e.g., `alu/add`), positions relative to
+// the sub-circuit, and inputs reading input ports replaced by the inputs connected to the
+// ports. The sub-circuit remains, copying the internal outputs it exposes to its own outputs.
+// Parameters set by the sub-circuit override the defaults declared by its model, so one model
+// can be used as a template for sub-circuits of different sizes or values.
+//
+// Components referring to other components by id other than through inputs are not renamed,
+// and can not be used in sub-circuits.
+use crate::common::{
+    Component, ComponentStore, Components, Condition, Id, Input, InputPort, OutputType, Ports,
+    Simulator,
+};
+use crate::component_store::{resolve_parameters, Parameters};
+use crate::elaboration::ElaborationProblem;
+use log::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// separates the sub-circuit id from the ids of its components
+pub const SUB_CIRCUIT_SEPARATOR: &str = "/";
+
+/// output `field` of the sub-circuit, driven by `input` inside the sub-circuit
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SubCircuitOutput {
+    pub field: Id,
+    pub input: Input,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SubCircuit {
+    pub(crate) id: Id,
+    pub(crate) pos: (f32, f32),
+    /// model of the sub-circuit, relative to the model including it
+    pub(crate) file: PathBuf,
+    pub(crate) inputs: Vec<InputPort>,
+    pub(crate) outputs: Vec<SubCircuitOutput>,
+    /// parameters of the sub-circuit model, overriding its defaults
+    #[serde(default, skip_serializing_if = "Parameters::is_empty")]
+    pub(crate) parameters: Parameters,
+    /// components of the sub-circuit shown in the gui
+    #[serde(skip)]
+    pub(crate) expanded: Cell<bool>,
+    /// set when the components have been added to the model,
+    /// the outputs then refer to the (prefixed) components
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) flattened: bool,
+    /// directory of the model including the sub-circuit, set when loaded from a file,
+    /// else `file` is relative to the working directory
+    #[serde(skip)]
+    pub(crate) dir: RefCell<PathBuf>,
+}
+
+#[typetag::serde]
+impl Component for SubCircuit {
+    fn to_(&self) {
+        trace!("sub_circuit {:?}", self.file);
+    }
+
+    fn get_id_ports(&self) -> (Id, Ports) {
+        (
+            self.id.clone(),
+            Ports {
+                inputs: self
+                    .outputs
+                    .iter()
+                    .map(|output| InputPort {
+                        port_id: output.field.clone(),
+                        input: output.input.clone(),
+                    })
+                    .collect(),
+                out_type: OutputType::Combinatorial,
+                outputs: self.outputs.iter().map(|o| o.field.clone()).collect(),
+            },
+        )
+    }
+
+    // copy internal outputs to the sub-circuit outputs, read as the ports of the same order
+    fn clock(&self, simulator: &mut Simulator) -> Result<(), Condition> {
+        for output in 0..self.outputs.len() {
+            let signal = simulator.get_handle_signal(simulator.input_handles()[output]);
+            simulator.set_output_value(output, signal.get_value());
+            simulator.set_output_fmt(output, signal.get_fmt());
+        }
+        Ok(())
+    }
+
+    fn set_id_port(&mut self, target_port_id: Id, new_input: Input) {
+        for port in &mut self.inputs {
+            if port.port_id == target_port_id {
+                port.input = new_input.clone();
+            }
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl SubCircuit {
+    pub fn new(
+        id: &str,
+        pos: (f32, f32),
+        file: impl Into<PathBuf>,
+        inputs: Vec<(&str, Input)>,
+        outputs: Vec<(&str, Input)>,
+    ) -> Self {
+        SubCircuit {
+            id: id.to_string(),
+            pos,
+            file: file.into(),
+            inputs: inputs
+                .into_iter()
+                .map(|(port_id, input)| InputPort {
+                    port_id: port_id.to_string(),
+                    input,
+                })
+                .collect(),
+            outputs: outputs
+                .into_iter()
+                .map(|(field, input)| SubCircuitOutput {
+                    field: field.to_string(),
+                    input,
+                })
+                .collect(),
+            parameters: Parameters::new(),
+            expanded: Cell::new(false),
+            flattened: false,
+            dir: RefCell::new(PathBuf::new()),
+        }
+    }
+
+    pub fn rc_new(
+        id: &str,
+        pos: (f32, f32),
+        file: impl Into<PathBuf>,
+        inputs: Vec<(&str, Input)>,
+        outputs: Vec<(&str, Input)>,
+    ) -> Rc<Self> {
+        Rc::new(SubCircuit::new(id, pos, file, inputs, outputs))
+    }
+
+    /// set a parameter of the sub-circuit model
+    pub fn set_parameter(&mut self, name: &str, value: impl Into<Value>) {
+        self.parameters.insert(name.to_string(), value.into());
+    }
+
+    /// prefix of the ids of components in the sub-circuit
+    pub fn prefix(&self) -> String {
+        format!("{}{}", self.id, SUB_CIRCUIT_SEPARATOR)
+    }
+
+    pub fn is_expanded(&self) -> bool {
+        self.expanded.get()
+    }
+
+    pub fn set_expanded(&self, expanded: bool) {
+        self.expanded.set(expanded);
+    }
+}
+
+// sub-circuits of a model loaded from a file in `dir` refer to models relative to `dir`
+pub(crate) fn set_sub_circuit_dir(store: &ComponentStore, dir: &Path) {
+    for c in &store.store {
+        if let Some(sub_circuit) = c.as_any().downcast_ref::<SubCircuit>() {
+            sub_circuit.dir.replace(dir.to_path_buf());
+        }
+    }
+}
+
+// add the components of sub-circuits to the model, recursively
+// components not in sub-circuits are kept as is
+pub(crate) fn flatten_sub_circuits(
+    store: ComponentStore,
+) -> (ComponentStore, Vec<ElaborationProblem>) {
+    let mut problems = vec![];
+    let is_sub_circuit = |c: &dyn Any| c.downcast_ref::<SubCircuit>().is_some_and(|s| !s.flattened);
+    if !store.store.iter().any(|c| is_sub_circuit(c.as_any())) {
+        return (store, problems);
+    }
+
+    let mut components: Components = vec![];
+    for c in store.store {
+        if !is_sub_circuit(c.as_any()) {
+            components.push(c);
+            continue;
+        }
+        let id = c.get_id_ports().0;
+        let dir = match c.as_any().downcast_ref::<SubCircuit>() {
+            Some(sub_circuit) => sub_circuit.dir.borrow().clone(),
+            None => PathBuf::new(),
+        };
+        let mut values = vec![];
+        let value = serde_json::to_value(&c).expect("ICE: sub-circuit not serializable");
+        flatten(
+            value,
+            &dir,
+            "",
+            (0.0, 0.0),
+            &HashMap::new(),
+            &mut vec![],
+            &mut values,
+            &mut problems,
+        );
+        match serde_json::from_value::<ComponentStore>(serde_json::json!({ "store": values })) {
+            Ok(store) => components.extend(store.store),
+            Err(err) => problems.push(ElaborationProblem::SubCircuit {
+                id,
+                error: err.to_string(),
+            }),
+        }
+    }
+    (ComponentStore { store: components }, problems)
+}
+
+// add a component (value) of a sub-circuit with `prefix` (the sub-circuit path) to `out`,
+// offset by the sub-circuit position, with `ports` mapping input port ids to inputs,
+// `dir` is the directory of the model including the component,
+// `files` are the models of the enclosing sub-circuits
+#[allow(clippy::too_many_arguments)]
+fn flatten(
+    mut value: Value,
+    dir: &Path,
+    prefix: &str,
+    offset: (f32, f32),
+    ports: &HashMap<Id, Value>,
+    files: &mut Vec<PathBuf>,
+    out: &mut Vec<Value>,
+    problems: &mut Vec<ElaborationProblem>,
+) {
+    if value["type"] != "SubCircuit" || value["flattened"] == true {
+        rename(&mut value, prefix, offset, ports);
+        out.push(value);
+        return;
+    }
+
+    // outputs refer to components inside the sub-circuit
+    let outputs = value.get_mut("outputs").map(Value::take);
+    rename(&mut value, prefix, offset, ports);
+    if let Some(outputs) = outputs {
+        value["outputs"] = outputs;
+    }
+    let sub_circuit: SubCircuit = match serde_json::from_value(value.clone()) {
+        Ok(sub_circuit) => sub_circuit,
+        Err(err) => {
+            problems.push(ElaborationProblem::SubCircuit {
+                id: format!("{}{}", prefix, value["id"].as_str().unwrap_or_default()),
+                error: err.to_string(),
+            });
+            return;
+        }
+    };
+    let id = sub_circuit.id.clone();
+    let child_prefix = sub_circuit.prefix();
+    for output in value["outputs"].as_array_mut().into_iter().flatten() {
+        rename_inputs(&mut output["input"], &child_prefix, &HashMap::new());
+    }
+    value["flattened"] = true.into();
+    out.push(value);
+
+    let file = dir.join(&sub_circuit.file);
+    if files.contains(&file) {
+        problems.push(ElaborationProblem::SubCircuit {
+            id,
+            error: format!("{:?} includes itself", file),
+        });
+        return;
+    }
+    let store = match fs::read_to_string(&file)
+        .map_err(|err| err.to_string())
+        .and_then(|json| serde_json::from_str::<Value>(&json).map_err(|err| err.to_string()))
+        .and_then(|mut model| {
+            resolve_parameters(&mut model, &sub_circuit.parameters)?;
+            Ok(model)
+        }) {
+        Ok(mut model) => model["store"].take(),
+        Err(err) => {
+            problems.push(ElaborationProblem::SubCircuit {
+                id,
+                error: format!("{:?}: {}", file, err),
+            });
+            return;
+        }
+    };
+    let Value::Array(store) = store else {
+        problems.push(ElaborationProblem::SubCircuit {
+            id,
+            error: format!("{:?}: not a model", file),
+        });
+        return;
+    };
+
+    let ports = sub_circuit
+        .inputs
+        .iter()
+        .map(|port| {
+            let input = serde_json::to_value(&port.input).expect("ICE: input not serializable");
+            (port.port_id.clone(), input)
+        })
+        .collect();
+    let child_dir = file.parent().unwrap_or(Path::new("")).to_path_buf();
+    files.push(file);
+    for value in store {
+        flatten(
+            value,
+            &child_dir,
+            &child_prefix,
+            sub_circuit.pos,
+            &ports,
+            files,
+            out,
+            problems,
+        );
+    }
+    files.pop();
+}
+
+// prefix the component id, offset its position and rename its inputs
+fn rename(value: &mut Value, prefix: &str, offset: (f32, f32), ports: &HashMap<Id, Value>) {
+    let Value::Object(map) = value else {
+        return;
+    };
+    for (key, field) in map.iter_mut() {
+        match (key.as_str(), field) {
+            ("id", Value::String(id)) => *id = format!("{}{}", prefix, id),
+            ("pos", pos) => offset_pos(pos, offset),
+            (_, field) => rename_inputs(field, prefix, ports),
+        }
+    }
+}
+
+// inputs reading a port are replaced by the input connected to the port, others prefixed
+fn rename_inputs(value: &mut Value, prefix: &str, ports: &HashMap<Id, Value>) {
+    match value {
+        Value::Object(map) if map.len() == 2 && map.get("field").is_some_and(Value::is_string) => {
+            let Some(Value::String(id)) = map.get_mut("id") else {
+                return;
+            };
+            match ports.get(id.as_str()) {
+                Some(input) => *value = input.clone(),
+                None => *id = format!("{}{}", prefix, id),
+            }
+        }
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|value| rename_inputs(value, prefix, ports)),
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| rename_inputs(value, prefix, ports)),
+        _ => {}
+    }
+}
+
+// positions are (x, y) pairs, or lists of pairs (e.g., wires)
+fn offset_pos(value: &mut Value, offset: (f32, f32)) {
+    let Value::Array(values) = value else {
+        return;
+    };
+    match values.as_mut_slice() {
+        [Value::Number(x), Value::Number(y)] => {
+            *x = add(x, offset.0);
+            *y = add(y, offset.1);
+        }
+        values => values.iter_mut().for_each(|pos| offset_pos(pos, offset)),
+    }
+}
+
+fn add(number: &serde_json::Number, offset: f32) -> serde_json::Number {
+    serde_json::Number::from_f64(number.as_f64().unwrap_or_default() + offset as f64)
+        .unwrap_or_else(|| number.clone())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::SignalValue;
+    use crate::components::*;
+
+    // directory of the models written by the tests of this process
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("syncrim_sub_circuit_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // adder, output `sum` = `a` + `b`
+    fn adder(name: &str) -> PathBuf {
+        let path = temp_dir().join(name);
+        ComponentStore {
+            store: vec![
+                Add::rc_new(
+                    "add",
+                    (10.0, 20.0),
+                    Input::new("a", "in"),
+                    Input::new("b", "in"),
+                ),
+                Wire::rc_new("w", vec![(0.0, 0.0), (10.0, 0.0)], Input::new("a", "in")),
+            ],
+        }
+        .save_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_sub_circuit() {
+        let adder = adder("adder.json");
+        // sum of three, by two adders
+        let add3 = temp_dir().join("add3.json");
+        ComponentStore {
+            store: vec![
+                SubCircuit::rc_new(
+                    "ab",
+                    (0.0, 0.0),
+                    &adder,
+                    vec![("a", Input::new("a", "in")), ("b", Input::new("b", "in"))],
+                    vec![("sum", Input::new("add", ADD_OUT_ID))],
+                ),
+                SubCircuit::rc_new(
+                    "abc",
+                    (100.0, 0.0),
+                    &adder,
+                    vec![("a", Input::new("ab", "sum")), ("b", Input::new("c", "in"))],
+                    vec![("sum", Input::new("add", ADD_OUT_ID))],
+                ),
+            ],
+        }
+        .save_file(&add3);
+
+        let cs = ComponentStore {
+            store: vec![
+                Constant::rc_new("c1", (0.0, 0.0), 1),
+                Constant::rc_new("c2", (0.0, 0.0), 2),
+                Constant::rc_new("c3", (0.0, 0.0), 3),
+                SubCircuit::rc_new(
+                    "add3",
+                    (1000.0, 0.0),
+                    &add3,
+                    vec![
+                        ("a", Input::new("c1", CONSTANT_OUT_ID)),
+                        ("b", Input::new("c2", CONSTANT_OUT_ID)),
+                        ("c", Input::new("c3", CONSTANT_OUT_ID)),
+                    ],
+                    vec![("out", Input::new("abc", "sum"))],
+                ),
+                Probe::rc_new("p", (0.0, 0.0), Input::new("add3", "out")),
+            ],
+        };
+        let simulator = Simulator::new(cs).unwrap();
+        assert_eq!(
+            simulator.get_input_value(&Input::new("add3", "out")),
+            SignalValue::from(6)
+        );
+        assert_eq!(
+            simulator.get_input_value(&Input::new("add3/ab/add", ADD_OUT_ID)),
+            SignalValue::from(3)
+        );
+
+        // components are renamed, moved and read the inputs connected to the ports
+        let add: &Add = simulator.get_component("add3/abc/add").unwrap();
+        assert_eq!(add.pos, (1110.0, 20.0));
+        assert_eq!(add.a_in, Input::new("add3/ab", "sum"));
+        assert_eq!(add.b_in, Input::new("c3", CONSTANT_OUT_ID));
+        let wire: &Wire = simulator.get_component("add3/ab/w").unwrap();
+        assert_eq!(wire.pos, vec![(1000.0, 0.0), (1010.0, 0.0)]);
+        assert_eq!(wire.input, Input::new("c1", CONSTANT_OUT_ID));
+    }
+
+    #[test]
+    fn test_sub_circuit_relative() {
+        // models in a directory, including each other by relative paths
+        let dir = temp_dir().join("relative");
+        fs::create_dir_all(dir.join("lib")).unwrap();
+        fs::rename(adder("relative_adder.json"), dir.join("lib/adder.json")).unwrap();
+        let inputs = || vec![("a", Input::new("a", "in")), ("b", Input::new("b", "in"))];
+        let outputs = || vec![("sum", Input::new("add", ADD_OUT_ID))];
+        ComponentStore {
+            store: vec![SubCircuit::rc_new(
+                "ab",
+                (0.0, 0.0),
+                "adder.json",
+                inputs(),
+                outputs(),
+            )],
+        }
+        .save_file(&dir.join("lib/wrapper.json"));
+        let model = dir.join("model.json");
+        ComponentStore {
+            store: vec![
+                Constant::rc_new("c1", (0.0, 0.0), 1),
+                Constant::rc_new("c2", (0.0, 0.0), 2),
+                SubCircuit::rc_new(
+                    "w",
+                    (0.0, 0.0),
+                    "lib/wrapper.json",
+                    vec![
+                        ("a", Input::new("c1", CONSTANT_OUT_ID)),
+                        ("b", Input::new("c2", CONSTANT_OUT_ID)),
+                    ],
+                    vec![("sum", Input::new("ab", "sum"))],
+                ),
+            ],
+        }
+        .save_file(&model);
+
+        let simulator = Simulator::new(ComponentStore::load_file(&model).unwrap()).unwrap();
+        assert_eq!(
+            simulator.get_input_value(&Input::new("w", "sum")),
+            SignalValue::from(3)
+        );
+    }
+
+    #[test]
+    fn test_sub_circuit_parameters() {
+        // constant, by default 1
+        let constant = temp_dir().join("constant.json");
+        fs::write(
+            &constant,
+            r#"{
+                "parameters": {"value": 1},
+                "store": [{"type": "Constant", "id": "c", "pos": [0.0, 0.0],
+                    "value": {"data": {"Data": "$value"}, "fmt": {"Unsigned": "_32"}}}]
+            }"#,
+        )
+        .unwrap();
+        let outputs = || vec![("out", Input::new("c", CONSTANT_OUT_ID))];
+        let mut seven = SubCircuit::new("seven", (0.0, 0.0), &constant, vec![], outputs());
+        seven.set_parameter("value", 7);
+        let cs = ComponentStore {
+            store: vec![
+                SubCircuit::rc_new("one", (0.0, 0.0), &constant, vec![], outputs()),
+                Rc::new(seven),
+            ],
+        };
+        let simulator = Simulator::new(cs).unwrap();
+        assert_eq!(
+            simulator.get_input_value(&Input::new("one", "out")),
+            SignalValue::from(1)
+        );
+        assert_eq!(
+            simulator.get_input_value(&Input::new("seven", "out")),
+            SignalValue::from(7)
+        );
+
+        let mut size = SubCircuit::new("size", (0.0, 0.0), &constant, vec![], outputs());
+        size.set_parameter("size", 7);
+        let problems = Simulator::new(ComponentStore {
+            store: vec![Rc::new(size)],
+        })
+        .err()
+        .unwrap()
+        .problems;
+        assert!(matches!(
+            &problems[0],
+            ElaborationProblem::SubCircuit { id, error } if id == "size" && error.contains("\"size\" is not declared")
+        ));
+    }
+
+    #[test]
+    fn test_sub_circuit_problems() {
+        let adder = adder("problems_adder.json");
+        let recursive = temp_dir().join("recursive.json");
+        ComponentStore {
+            store: vec![SubCircuit::rc_new(
+                "r",
+                (0.0, 0.0),
+                &recursive,
+                vec![],
+                vec![],
+            )],
+        }
+        .save_file(&recursive);
+        let missing = temp_dir().join("missing.json");
+
+        let cs = ComponentStore {
+            store: vec![
+                Constant::rc_new("c", (0.0, 0.0), 1),
+                SubCircuit::rc_new("r", (0.0, 0.0), &recursive, vec![], vec![]),
+                SubCircuit::rc_new("m", (0.0, 0.0), &missing, vec![], vec![]),
+                // port b not connected
+                SubCircuit::rc_new(
+                    "a",
+                    (0.0, 0.0),
+                    &adder,
+                    vec![("a", Input::new("c", CONSTANT_OUT_ID))],
+                    vec![("sum", Input::new("add", ADD_OUT_ID))],
+                ),
+            ],
+        };
+        let problems = Simulator::new(cs).err().unwrap().problems;
+        assert_eq!(problems.len(), 3, "{:?}", problems);
+        assert!(matches!(
+            &problems[0],
+            ElaborationProblem::SubCircuit { id, error } if id == "r/r" && error.contains("includes itself")
+        ));
+        assert!(matches!(
+            &problems[1],
+            ElaborationProblem::SubCircuit { id, .. } if id == "m"
+        ));
+        assert_eq!(
+            problems[2..],
+            [ElaborationProblem::UnconnectedPort {
+                id: "a/add".into(),
+                port_id: ADD_B_IN_ID.into(),
+                input: Input::new("a/b", "in")
+            }]
+        );
+    }
+}
@@ -0,0 +1,363 @@
+// Test vectors loaded from a file, for ProbeStim and ProbeAssert
+//
+// A file has named columns, one per signal, and one row per cycle, or rows for the cycles given
+// by an optional `cycle` column, e.g., as CSV
+//
+//   cycle, a,    b, sum
+//   1,     1,    2, 3
+//   5,     0x10, -, x
+//
+// or as JSON, `[{"cycle": 1, "a": 1, "b": 2, "sum": 3}, {"cycle": 5, "a": "0x10", ...}]`.
+// Values are parsed as `SignalValue` (e.g., `0x10`, `-` don't care, `x` unknown). Cycles
+// without a value (not in a row, or an empty entry) hold the previous value for stimuli and
+// are don't care for assertions. Files are read when the model is elaborated.
+use crate::common::{ComponentStore, Components, Signal, SignalValue};
+use crate::components::{ProbeAssert, ProbeStim};
+use crate::elaboration::ElaborationProblem;
+use crate::golden::value_text;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::HashMap, fs, path::PathBuf, rc::Rc};
+
+// name of the column giving the cycle of each row
+const CYCLE_COLUMN: &str = "cycle";
+
+/// Values of a component read from a file, column `column` or else the component id
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct VectorFile {
+    /// relative to the working directory, `.json` or else CSV
+    pub file: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    // values per cycle, read when elaborated
+    #[serde(skip)]
+    pub(crate) values: Vec<Signal>,
+}
+
+/// Test vectors, values by column for the cycles of the rows
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TestVectors {
+    pub cycles: Vec<usize>,
+    pub columns: HashMap<String, Vec<Option<SignalValue>>>,
+}
+
+impl TestVectors {
+    /// read from a JSON (`.json`) or CSV file
+    pub fn load(path: &PathBuf) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("{:?}: {}", path, err))?;
+        let vectors = if path.extension().is_some_and(|ext| ext == "json") {
+            TestVectors::from_json(&text)
+        } else {
+            TestVectors::from_csv(&text)
+        };
+        vectors.map_err(|err| format!("{:?}: {}", path, err))
+    }
+
+    /// parse CSV, a header with the column names and a row per line,
+    /// empty lines and lines starting with `#` are ignored
+    pub fn from_csv(text: &str) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'));
+        let Some((_, header)) = lines.next() else {
+            return Err("no header".to_string());
+        };
+        let names: Vec<&str> = header.split(',').map(str::trim).collect();
+        let mut rows = vec![];
+        for (line, text) in lines {
+            let entries: Vec<&str> = text.split(',').map(str::trim).collect();
+            if entries.len() != names.len() {
+                return Err(format!(
+                    "line {}: {} entries, expected {}",
+                    line + 1,
+                    entries.len(),
+                    names.len()
+                ));
+            }
+            let row = names
+                .iter()
+                .zip(entries)
+                .filter(|(_, entry)| !entry.is_empty())
+                .map(|(name, entry)| (name.to_string(), entry.to_string()))
+                .collect();
+            rows.push((line + 1, row));
+        }
+        TestVectors::from_rows(&names, rows)
+    }
+
+    /// parse JSON, an array of rows, each an object of values (numbers or strings) by column
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let json: Vec<serde_json::Map<String, Value>> =
+            serde_json::from_str(text).map_err(|err| err.to_string())?;
+        let mut names = vec![];
+        let mut rows = vec![];
+        for (index, object) in json.into_iter().enumerate() {
+            let mut row = vec![];
+            for (name, value) in object {
+                let entry = match value {
+                    Value::String(text) => text,
+                    Value::Number(number) => number.to_string(),
+                    Value::Null => continue,
+                    _ => return Err(format!("row {}: {} is not a value", index, name)),
+                };
+                if !names.contains(&name) {
+                    names.push(name.clone());
+                }
+                row.push((name, entry));
+            }
+            rows.push((index, row));
+        }
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        TestVectors::from_rows(&names, rows)
+    }
+
+    // rows of (name, entry), numbered (line or index) for errors
+    fn from_rows(
+        names: &[&str],
+        rows: Vec<(usize, Vec<(String, String)>)>,
+    ) -> Result<Self, String> {
+        let mut vectors = TestVectors {
+            cycles: vec![],
+            columns: names
+                .iter()
+                .filter(|name| **name != CYCLE_COLUMN)
+                .map(|name| (name.to_string(), vec![]))
+                .collect(),
+        };
+        for (index, (number, row)) in rows.into_iter().enumerate() {
+            let mut cycle = index;
+            for (name, entry) in &row {
+                if name == CYCLE_COLUMN {
+                    cycle = entry
+                        .parse()
+                        .map_err(|_| format!("row {}: invalid cycle {:?}", number, entry))?;
+                }
+            }
+            if vectors.cycles.last().is_some_and(|last| *last >= cycle) {
+                return Err(format!("row {}: cycle {} out of order", number, cycle));
+            }
+            vectors.cycles.push(cycle);
+            for values in vectors.columns.values_mut() {
+                values.push(None);
+            }
+            for (name, entry) in row {
+                if name == CYCLE_COLUMN {
+                    continue;
+                }
+                let value = entry
+                    .parse::<SignalValue>()
+                    .map_err(|err| format!("row {}: {}: {}", number, name, err))?;
+                *vectors.columns.get_mut(&name).unwrap().last_mut().unwrap() = Some(value);
+            }
+        }
+        Ok(vectors)
+    }
+
+    /// as CSV, with a `cycle` column and the other columns by name, read back by `from_csv`
+    pub fn to_csv(&self) -> String {
+        let mut names: Vec<&String> = self.columns.keys().collect();
+        names.sort();
+        let mut csv = CYCLE_COLUMN.to_string();
+        for name in &names {
+            csv.push_str(&format!(",{}", name));
+        }
+        csv.push('\n');
+        for (row, cycle) in self.cycles.iter().enumerate() {
+            csv.push_str(&cycle.to_string());
+            for name in &names {
+                csv.push(',');
+                if let Some(value) = self.columns[*name][row] {
+                    csv.push_str(&value_text(value));
+                }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// values of column `name` for each cycle up to the last row,
+    /// cycles without a value `hold` the previous value, or else are don't care
+    pub fn values(&self, name: &str, hold: bool) -> Result<Vec<Signal>, String> {
+        let column = self
+            .columns
+            .get(name)
+            .ok_or_else(|| format!("no column {:?}", name))?;
+        let Some(last) = self.cycles.last() else {
+            return Ok(vec![]);
+        };
+        let mut values = vec![SignalValue::DontCare; last + 1];
+        let mut previous = SignalValue::DontCare;
+        let mut rows = self.cycles.iter().zip(column).peekable();
+        for (cycle, value) in values.iter_mut().enumerate() {
+            match rows.next_if(|(row_cycle, _)| **row_cycle == cycle) {
+                Some((_, Some(row_value))) => {
+                    *value = *row_value;
+                    previous = *row_value;
+                }
+                _ if hold => *value = previous,
+                _ => {}
+            }
+        }
+        Ok(values.into_iter().map(Signal::from).collect())
+    }
+}
+
+impl VectorFile {
+    pub fn new(file: impl Into<PathBuf>, column: Option<&str>) -> Self {
+        VectorFile {
+            file: file.into(),
+            column: column.map(String::from),
+            values: vec![],
+        }
+    }
+
+    // read the values of component `id`, files already read are in `files`
+    fn load(
+        &mut self,
+        id: &str,
+        hold: bool,
+        files: &mut HashMap<PathBuf, Result<TestVectors, String>>,
+    ) -> Result<(), String> {
+        let vectors = files
+            .entry(self.file.clone())
+            .or_insert_with(|| TestVectors::load(&self.file))
+            .as_ref()
+            .map_err(Clone::clone)?;
+        let column = self.column.as_deref().unwrap_or(id);
+        self.values = vectors
+            .values(column, hold)
+            .map_err(|err| format!("{:?}: {}", self.file, err))?;
+        Ok(())
+    }
+}
+
+// read the values of ProbeStim and ProbeAssert components with vector files
+pub(crate) fn load_vectors(store: ComponentStore) -> (ComponentStore, Vec<ElaborationProblem>) {
+    let mut problems = vec![];
+    let mut files = HashMap::new();
+    let mut components: Components = vec![];
+    for c in store.store {
+        let any = c.as_any();
+        let result = if let Some(stim) = any.downcast_ref::<ProbeStim>() {
+            stim.vectors.clone().map(|mut vectors| {
+                let result = vectors.load(&stim.id, true, &mut files);
+                let mut stim = stim.clone();
+                stim.vectors = Some(vectors);
+                components.push(Rc::new(stim));
+                result
+            })
+        } else if let Some(assert) = any.downcast_ref::<ProbeAssert>() {
+            assert.vectors.clone().map(|mut vectors| {
+                let result = vectors.load(&assert.id, false, &mut files);
+                let mut assert = assert.clone();
+                assert.vectors = Some(vectors);
+                components.push(Rc::new(assert));
+                result
+            })
+        } else {
+            None
+        };
+        match result {
+            None => components.push(c),
+            Some(Ok(())) => {}
+            Some(Err(error)) => problems.push(ElaborationProblem::TestVectors {
+                id: c.get_id_ports().0,
+                error,
+            }),
+        }
+    }
+    (ComponentStore { store: components }, problems)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{Input, Simulator};
+
+    const CSV: &str = "
+        # adder vectors
+        cycle, a, sum
+        0,     0, 0
+        2,     0x10, -
+        4,     ,  x
+    ";
+
+    #[test]
+    fn test_vectors() {
+        let vectors = TestVectors::from_csv(CSV).unwrap();
+        assert_eq!(vectors.cycles, [0, 2, 4]);
+        let values = |name, hold| -> Vec<SignalValue> {
+            vectors
+                .values(name, hold)
+                .unwrap()
+                .iter()
+                .map(Signal::get_value)
+                .collect()
+        };
+        let (x, dc) = (SignalValue::Unknown, SignalValue::DontCare);
+        assert_eq!(
+            values("a", true),
+            [0.into(), 0.into(), 0x10.into(), 0x10.into(), 0x10.into()]
+        );
+        assert_eq!(values("sum", false), [0.into(), dc, dc, dc, x]);
+        assert!(vectors.values("b", false).is_err());
+
+        let json = r#"[{"cycle": 0, "a": 0, "sum": "0"}, {"cycle": 2, "a": "0x10", "sum": "-"},
+            {"cycle": 4, "sum": "x"}]"#;
+        assert_eq!(TestVectors::from_json(json).unwrap(), vectors);
+        assert_eq!(TestVectors::from_csv(&vectors.to_csv()).unwrap(), vectors);
+
+        assert!(TestVectors::from_csv("cycle,a\n2,1\n1,2").is_err());
+        assert!(TestVectors::from_csv("a,b\n1").is_err());
+        assert!(TestVectors::from_csv("a\n1z").is_err());
+    }
+
+    #[test]
+    fn test_vector_file() {
+        let dir = std::env::temp_dir().join(format!("syncrim_vectors_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("vectors.csv");
+        fs::write(&file, "cycle,stim,expected\n0,1,1\n2,2,-\n3,,2\n").unwrap();
+        let cs = ComponentStore {
+            store: vec![
+                Rc::new(ProbeStim::new_with_vectors(
+                    "stim",
+                    (0.0, 0.0),
+                    VectorFile::new(&file, None),
+                )),
+                Rc::new(ProbeAssert::new_with_vectors(
+                    "assert",
+                    (0.0, 0.0),
+                    Input::new("stim", "out"),
+                    VectorFile::new(&file, Some("expected")),
+                )),
+            ],
+        };
+        // vectors are read also when the model is loaded from json
+        let json = serde_json::to_string(&cs).unwrap();
+        let mut simulator = Simulator::new(ComponentStore::load(&json).unwrap()).unwrap();
+        let out = Input::new("stim", "out");
+        let mut values = vec![simulator.get_input_value(&out)];
+        for _ in 0..2 {
+            simulator.clock();
+            values.push(simulator.get_input_value(&out));
+        }
+        assert_eq!(values, [1, 1, 2].map(SignalValue::from));
+        assert!(simulator.get_component_condition().is_none());
+
+        let cs = ComponentStore {
+            store: vec![Rc::new(ProbeStim::new_with_vectors(
+                "missing",
+                (0.0, 0.0),
+                VectorFile::new(&file, None),
+            ))],
+        };
+        let problems = Simulator::new(cs).err().unwrap().problems;
+        assert!(matches!(
+            &problems[..],
+            [ElaborationProblem::TestVectors { id, .. }] if id == "missing"
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,9 +1,7 @@
 // use std::fmt::Alignment;
 #[cfg(feature = "gui-egui")]
 use crate::common::EguiComponent;
-use crate::common::{
-    Component, Condition, Id, Input, InputPort, OutputType, Ports, SignalValue, Simulator,
-};
+use crate::common::{Component, Condition, Id, Input, InputPort, OutputType, Ports, Simulator};
 use log::*;
 use serde::{Deserialize, Serialize};
 use std::any::Any;
@@ -13,6 +11,10 @@ pub const ZEROEXTEND_SIGNAL_IN_ID: &str = "signal_in";
 
 pub const ZEROEXTEND_OUT_ID: &str = "zero_extend_out";
 
+// port and output indices, in `get_id_ports` order
+const IN: usize = 0;
+const OUT: usize = 0;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ZeroExtend {
     pub(crate) id: Id,
@@ -58,14 +60,11 @@ impl Component for ZeroExtend {
 
     fn clock(&self, simulator: &mut Simulator) -> Result<(), Condition> {
         // get input values
-        let signal_in: u32 = simulator
-            .get_input_value(&self.signal_in)
-            .try_into()
-            .unwrap();
+        let signal_in = simulator.get_port_value(IN);
 
-        let output: u32 = signal_in & 0x0000_FFFF; // already zero extended
+        let output = signal_in.map(|signal_in| signal_in & 0x0000_FFFF); // already zero extended
 
-        simulator.set_out_value(&self.id, ZEROEXTEND_OUT_ID, SignalValue::Data(output));
+        simulator.set_output_value(OUT, output);
         Ok(())
     }
 
@@ -0,0 +1,320 @@
+// Coverage of a model by the cycles run, e.g., to judge how well test programs exercise it
+//
+// When enabled, each clock records the output bits toggled (rising and falling) and the bins
+// components report hit (`Component::coverage`), e.g., the mux input selected, the memory
+// operation, or the opcode decoded. Clocks done by reset are not counted, and coverage
+// accumulates (also over reset and reverse simulation) until cleared.
+use crate::common::{Id, SignalValue, SignalWide, Simulator};
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt};
+
+/// A coverage bin of a component, as (group, bin), e.g., `("select", "in1".to_string())`
+pub type CoverBin = (&'static str, String);
+
+/// Coverage collected while clocking
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Coverage {
+    cycles: u64,
+    // per sim_state entry, the previous value and the bits seen rising and falling
+    previous: Vec<SignalValue>,
+    rose: Vec<SignalWide>,
+    fell: Vec<SignalWide>,
+    width: Vec<u32>,
+    // per ordered component, hits per bin
+    bins: Vec<BTreeMap<CoverBin, u64>>,
+}
+
+/// Toggle coverage of an output, bits seen both rising and falling
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct ToggleCoverage {
+    pub id: Id,
+    pub field: Id,
+    // bits of the widest value seen
+    pub bits: u32,
+    pub toggled: u32,
+    pub rose: SignalWide,
+    pub fell: SignalWide,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct BinCoverage {
+    pub group: String,
+    pub bin: String,
+    pub hits: u64,
+}
+
+/// Bins of a component, also those not hit
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct ComponentCoverage {
+    pub id: Id,
+    pub bins: Vec<BinCoverage>,
+}
+
+/// Coverage report, in model order, as text (`Display`) or JSON (`Serialize`)
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    pub cycles: u64,
+    pub toggles: Vec<ToggleCoverage>,
+    pub components: Vec<ComponentCoverage>,
+}
+
+impl CoverageReport {
+    /// (toggled, total) output bits
+    pub fn toggled_bits(&self) -> (u32, u32) {
+        self.toggles
+            .iter()
+            .fold((0, 0), |(t, b), c| (t + c.toggled, b + c.bits))
+    }
+
+    /// (hit, total) component bins
+    pub fn hit_bins(&self) -> (usize, usize) {
+        let bins = self.components.iter().flat_map(|c| &c.bins);
+        (bins.clone().filter(|b| b.hits > 0).count(), bins.count())
+    }
+}
+
+fn percent(part: usize, total: usize) -> f64 {
+    match total {
+        0 => 0.0,
+        _ => 100.0 * part as f64 / total as f64,
+    }
+}
+
+impl fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (toggled, bits) = self.toggled_bits();
+        let (hit, bins) = self.hit_bins();
+        writeln!(f, "coverage of {} cycles", self.cycles)?;
+        write!(
+            f,
+            "toggle: {}/{} bits ({:.1}%)",
+            toggled,
+            bits,
+            percent(toggled as usize, bits as usize)
+        )?;
+        for c in &self.toggles {
+            write!(
+                f,
+                "\n  {:<32} {:>3}/{:<3} rose {:#x} fell {:#x}",
+                format!("{}.{}", c.id, c.field),
+                c.toggled,
+                c.bits,
+                c.rose,
+                c.fell
+            )?;
+        }
+        write!(
+            f,
+            "\nbins: {}/{} hit ({:.1}%)",
+            hit,
+            bins,
+            percent(hit, bins)
+        )?;
+        for c in &self.components {
+            for b in &c.bins {
+                let missed = if b.hits == 0 { " (missed)" } else { "" };
+                write!(
+                    f,
+                    "\n  {:<24} {:<8} {:<12} {:>8}{}",
+                    c.id, b.group, b.bin, b.hits, missed
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Coverage {
+    fn new(simulator: &Simulator) -> Self {
+        let signals = simulator.sim_state.len();
+        Coverage {
+            cycles: 0,
+            previous: simulator.sim_state.iter().map(|s| s.get_value()).collect(),
+            rose: vec![0; signals],
+            fell: vec![0; signals],
+            width: vec![0; signals],
+            bins: simulator
+                .ordered_components
+                .iter()
+                .map(|c| c.coverage_bins().into_iter().map(|bin| (bin, 0)).collect())
+                .collect(),
+        }
+    }
+
+    // record the bins hit by ordered component `index`
+    pub(crate) fn hit(&mut self, index: usize, bins: Vec<CoverBin>) {
+        for bin in bins {
+            *self.bins[index].entry(bin).or_default() += 1;
+        }
+    }
+
+    // record the bits toggled since the previous clock
+    pub(crate) fn sample(&mut self, simulator: &Simulator, count: bool) {
+        self.cycles += count as u64;
+        for (i, signal) in simulator.sim_state.iter().enumerate() {
+            let value = signal.get_value();
+            if count {
+                if let (Some(before), Some(after)) = (self.previous[i].as_wide(), value.as_wide()) {
+                    self.rose[i] |= !before & after;
+                    self.fell[i] |= before & !after;
+                }
+            }
+            self.width[i] = self.width[i].max(value.width().unwrap_or(0));
+            self.previous[i] = value;
+        }
+    }
+}
+
+impl Simulator {
+    /// enable or disable coverage collection, enabling clears the coverage
+    pub fn set_coverage(&mut self, coverage: bool) {
+        self.coverage = coverage.then(|| Coverage::new(self));
+    }
+
+    pub fn is_covering(&self) -> bool {
+        self.coverage.is_some()
+    }
+
+    /// clear the coverage, if collecting
+    pub fn clear_coverage(&mut self) {
+        if self.coverage.is_some() {
+            self.set_coverage(true);
+        }
+    }
+
+    /// coverage collected since enabled or cleared, empty if not collecting
+    pub fn coverage(&self) -> CoverageReport {
+        let Some(coverage) = &self.coverage else {
+            return CoverageReport::default();
+        };
+        let mut report = CoverageReport {
+            cycles: coverage.cycles,
+            ..Default::default()
+        };
+        for id in self.graph.node_weights() {
+            let index = self.component_index[id];
+            let start = self.output_range[index].start;
+            for (i, field) in self.output_fields[index].iter().enumerate() {
+                let (rose, fell) = (coverage.rose[start + i], coverage.fell[start + i]);
+                report.toggles.push(ToggleCoverage {
+                    id: id.clone(),
+                    field: field.clone(),
+                    bits: coverage.width[start + i],
+                    toggled: (rose & fell).count_ones(),
+                    rose,
+                    fell,
+                });
+            }
+            if !coverage.bins[index].is_empty() {
+                report.components.push(ComponentCoverage {
+                    id: id.clone(),
+                    bins: coverage.bins[index]
+                        .iter()
+                        .map(|((group, bin), hits)| BinCoverage {
+                            group: group.to_string(),
+                            bin: bin.clone(),
+                            hits: *hits,
+                        })
+                        .collect(),
+                });
+            }
+        }
+        report
+    }
+}
+
+#[cfg(all(test, feature = "components"))]
+mod test {
+    use super::*;
+    use crate::common::{ComponentStore, Input};
+    use crate::components::*;
+    use crate::fixtures::counter;
+
+    #[test]
+    fn test_coverage() {
+        // counter, selecting the count or a constant by its lowest bit
+        let mut cs = counter(1);
+        cs.store.push(Constant::rc_new("mask", (0.0, 0.0), 1));
+        cs.store.push(And::rc_new(
+            "and",
+            (0.0, 0.0),
+            Input::new("reg", REGISTER_OUT_ID),
+            Input::new("mask", CONSTANT_OUT_ID),
+        ));
+        cs.store.push(Mux::rc_new(
+            "mux",
+            (0.0, 0.0),
+            Input::new("and", AND_OUT_ID),
+            vec![
+                Input::new("reg", REGISTER_OUT_ID),
+                Input::new("step", CONSTANT_OUT_ID),
+                Input::new("step", CONSTANT_OUT_ID),
+            ],
+        ));
+        let mut simulator = Simulator::new(cs).unwrap();
+        assert!(!simulator.is_covering());
+        simulator.set_coverage(true);
+        for _ in 0..4 {
+            simulator.clock();
+        }
+        let report = simulator.coverage();
+        assert_eq!(report.cycles, 4);
+        // reg 0 -> 4, bits 0 and 1 toggled, bit 2 only rose
+        let reg = report.toggles.iter().find(|c| c.id == "reg").unwrap();
+        assert_eq!(
+            (reg.bits, reg.toggled, reg.rose, reg.fell),
+            (32, 2, 0b111, 0b11)
+        );
+        let mux = &report.components[0];
+        assert_eq!(mux.id, "mux");
+        let hits: Vec<_> = mux.bins.iter().map(|b| (b.bin.as_str(), b.hits)).collect();
+        assert_eq!(hits, [("in0", 2), ("in1", 2), ("in2", 0)]);
+        assert_eq!(report.hit_bins(), (2, 3));
+        let text = report.to_string();
+        let missed: Vec<_> = text.lines().filter(|l| l.ends_with("(missed)")).collect();
+        assert_eq!(missed.len(), 1);
+        assert!(missed[0].contains("in2"));
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["components"][0]["bins"][1]["hits"], 2);
+
+        // reset is not counted
+        simulator.reset();
+        assert_eq!(simulator.coverage().cycles, 4);
+        simulator.clear_coverage();
+        assert_eq!(simulator.coverage().cycles, 0);
+        simulator.set_coverage(false);
+        assert_eq!(simulator.coverage(), CoverageReport::default());
+    }
+
+    #[test]
+    fn test_coverage_event_driven() {
+        // the mux inputs never change, so it is skipped when event driven
+        let model = || ComponentStore {
+            store: vec![
+                Constant::rc_new("one", (0.0, 0.0), 1),
+                Mux::rc_new(
+                    "mux",
+                    (0.0, 0.0),
+                    Input::new("one", CONSTANT_OUT_ID),
+                    vec![
+                        Input::new("one", CONSTANT_OUT_ID),
+                        Input::new("one", CONSTANT_OUT_ID),
+                    ],
+                ),
+            ],
+        };
+        let mut full = Simulator::new(model()).unwrap();
+        let mut event = Simulator::new(model()).unwrap();
+        event.set_event_driven(true);
+        for simulator in [&mut full, &mut event] {
+            simulator.set_coverage(true);
+            for _ in 0..4 {
+                simulator.clock();
+            }
+        }
+        let report = event.coverage();
+        let hits: Vec<_> = report.components[0].bins.iter().map(|b| b.hits).collect();
+        assert_eq!(hits, [0, 4]);
+        assert_eq!(report, full.coverage());
+    }
+}
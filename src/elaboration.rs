@@ -0,0 +1,154 @@
+// Problems found when elaborating a model into a simulator (`Simulator::new`)
+use crate::clock_domain::ClockDomainError;
+use crate::common::{Id, Input, InputPort};
+use petgraph::{algo::tarjan_scc, graph::NodeIndex, Graph};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// One step along a combinational loop, component `id` reads `input` on port `port_id`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopStep {
+    pub id: Id,
+    pub port_id: Id,
+    pub input: Input,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElaborationProblem {
+    DuplicateId { id: Id },
+    DuplicateField { id: Id, field: Id },
+    // the input refers to a component or field not in the model
+    UnconnectedPort { id: Id, port_id: Id, input: Input },
+    // steps along the loop, each reading from the previous one, the first from the last
+    CombinationalLoop { path: Vec<LoopStep> },
+    // the sub-circuit model could not be added
+    SubCircuit { id: Id, error: String },
+    // the test vectors of a ProbeStim or ProbeAssert could not be read
+    TestVectors { id: Id, error: String },
+    // the clock domain declared by a clock divider could not be added
+    ClockDomain { id: Id, error: ClockDomainError },
+}
+
+impl fmt::Display for ElaborationProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ElaborationProblem::DuplicateId { id } => {
+                write!(f, "Component identifier {:?} is defined twice", id)
+            }
+            ElaborationProblem::DuplicateField { id, field } => {
+                write!(f, "Component {:?} field {:?} is defined twice", id, field)
+            }
+            ElaborationProblem::UnconnectedPort { id, port_id, input } => write!(
+                f,
+                "Component {} port {} is not connected, {}:{} not found",
+                id, port_id, input.id, input.field
+            ),
+            ElaborationProblem::CombinationalLoop { path } => {
+                write!(f, "Combinational loop")?;
+                for (i, step) in path.iter().enumerate() {
+                    let sep = if i == 0 { ":" } else { "," };
+                    write!(
+                        f,
+                        "{} {}:{} -> {}:{}",
+                        sep, step.input.id, step.input.field, step.id, step.port_id
+                    )?;
+                }
+                Ok(())
+            }
+            ElaborationProblem::SubCircuit { id, error } => {
+                write!(f, "Sub-circuit {}: {}", id, error)
+            }
+            ElaborationProblem::TestVectors { id, error } => {
+                write!(f, "Test vectors of {}: {}", id, error)
+            }
+            ElaborationProblem::ClockDomain { id, error } => {
+                write!(f, "Clock divider {}: {}", id, error)
+            }
+        }
+    }
+}
+
+/// All problems found when elaborating a model
+#[derive(Debug, Clone)]
+pub struct ElaborationError {
+    pub problems: Vec<ElaborationProblem>,
+}
+
+impl fmt::Display for ElaborationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Model has {} problem(s):", self.problems.len())?;
+        for problem in &self.problems {
+            write!(f, "\n  {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ElaborationError {}
+
+impl ElaborationError {
+    /// the steps of each combinational loop found
+    pub fn loops(&self) -> impl Iterator<Item = &[LoopStep]> {
+        self.problems.iter().filter_map(|problem| match problem {
+            ElaborationProblem::CombinationalLoop { path } => Some(path.as_slice()),
+            _ => None,
+        })
+    }
+}
+
+// combinational loops of the graph, one per strongly connected component,
+// each starting at the smallest component id in the loop
+pub(crate) fn combinational_loops(
+    graph: &Graph<Id, ()>,
+    inputs: &HashMap<Id, Vec<InputPort>>,
+) -> Vec<Vec<LoopStep>> {
+    let mut loops: Vec<Vec<LoopStep>> = tarjan_scc(graph)
+        .into_iter()
+        .filter(|scc| scc.len() > 1 || graph.contains_edge(scc[0], scc[0]))
+        .map(|scc| {
+            let start = *scc.iter().min_by_key(|node| &graph[**node]).unwrap();
+            let path = loop_path(graph, start, &scc.into_iter().collect());
+            (0..path.len())
+                .map(|i| {
+                    let id = &path[i];
+                    let from = &path[(i + path.len() - 1) % path.len()];
+                    let port = inputs[id]
+                        .iter()
+                        .find(|port| &port.input.id == from)
+                        .expect("ICE: loop edge without input");
+                    LoopStep {
+                        id: id.clone(),
+                        port_id: port.port_id.clone(),
+                        input: port.input.clone(),
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    loops.sort_by(|a, b| a[0].id.cmp(&b[0].id));
+    loops
+}
+
+// path of component ids around a loop through `node` within `nodes`, found by depth first search
+fn loop_path(graph: &Graph<Id, ()>, node: NodeIndex, nodes: &HashSet<NodeIndex>) -> Vec<Id> {
+    let mut visited = vec![false; graph.node_count()];
+    let mut path = vec![node];
+    // stack of successors left to visit per node on the path
+    let mut stack = vec![graph.neighbors(node).collect::<Vec<_>>()];
+    while let Some(successors) = stack.last_mut() {
+        match successors.pop() {
+            Some(next) if next == node => break,
+            Some(next) if !visited[next.index()] && nodes.contains(&next) => {
+                visited[next.index()] = true;
+                path.push(next);
+                stack.push(graph.neighbors(next).collect());
+            }
+            Some(_) => {}
+            None => {
+                path.pop();
+                stack.pop();
+            }
+        }
+    }
+    path.into_iter().map(|n| graph[n].clone()).collect()
+}
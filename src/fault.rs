@@ -0,0 +1,568 @@
+// Fault injection, outputs stuck at a value or bits flipped, and memory bytes corrupted
+//
+// Faults are injected into a simulator and applied while clocking: an output fault right after
+// the component owning the output is clocked (also when skipped by event-driven evaluation),
+// a memory fault before the components of its cycle are clocked, kept in the undo history of the
+// memory. When event driven, a memory is clocked in the cycle it is corrupted, and a component in
+// the cycle after an output flipped, as when not event driven. Faults are kept over reset.
+//
+// A campaign runs a model once without and once per fault, reporting the faults detected,
+// i.e., a component reported a failed assertion (`ProbeAssert`) or an error, e.g., as JSON.
+// A model failing already without faults is an error, as no fault could be told detected
+//
+//   {"cycles": 100, "faults": [
+//     {"kind": "StuckAt", "signal": {"id": "reg", "field": "register_out"}, "bit": 0, "value": 1},
+//     {"kind": "BitFlip", "signal": {"id": "reg", "field": "register_out"}, "bit": 3, "cycle": 10},
+//     {"kind": "MemoryCorrupt", "id": "mem", "address": 4096, "mask": 255, "cycle": 5}]}
+use crate::breakpoint::UnknownSignal;
+use crate::common::{
+    ComponentStore, Condition, Id, Input, RunningState, SignalUnsigned, SignalValue, SignalWide,
+    Simulator,
+};
+use crate::elaboration::ElaborationError;
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, path::Path};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind")]
+pub enum Fault {
+    /// output (or only bit `bit` of it) forced to `value` from cycle `from` on
+    StuckAt {
+        signal: Input,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bit: Option<u32>,
+        value: SignalWide,
+        #[serde(default)]
+        from: usize,
+    },
+    /// bit `bit` of the output flipped in cycle `cycle`
+    BitFlip {
+        signal: Input,
+        bit: u32,
+        cycle: usize,
+    },
+    /// byte at `address` of memory component `id` xor-ed with `mask` in cycle `cycle`
+    MemoryCorrupt {
+        id: Id,
+        address: usize,
+        mask: u8,
+        cycle: usize,
+    },
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fault::StuckAt {
+                signal,
+                bit,
+                value,
+                from,
+            } => {
+                write!(f, "{}.{}", signal.id, signal.field)?;
+                if let Some(bit) = bit {
+                    write!(f, " bit {}", bit)?;
+                }
+                write!(f, " stuck at {:#x} from cycle {}", value, from)
+            }
+            Fault::BitFlip { signal, bit, cycle } => write!(
+                f,
+                "{}.{} bit {} flipped in cycle {}",
+                signal.id, signal.field, bit, cycle
+            ),
+            Fault::MemoryCorrupt {
+                id,
+                address,
+                mask,
+                cycle,
+            } => write!(
+                f,
+                "{} byte {:#x} xor {:#04x} in cycle {}",
+                id, address, mask, cycle
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FaultError {
+    UnknownSignal(UnknownSignal),
+    UnknownComponent(Id),
+    // component corrupted not being a memory
+    NotMemory(Id),
+    // address corrupted outside of the memory
+    InvalidAddress(usize),
+    // bit outside of `SignalWide`
+    InvalidBit(u32),
+    // the run without faults already failed
+    FaultFree(Outcome),
+    Elaboration(Box<ElaborationError>),
+    Json(serde_json::Error),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for FaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FaultError::UnknownSignal(error) => write!(f, "{}", error),
+            FaultError::UnknownComponent(id) => write!(f, "Component {} not found", id),
+            FaultError::NotMemory(id) => write!(f, "Component {} is not a memory", id),
+            FaultError::InvalidAddress(address) => {
+                write!(f, "Address {:#x} out of range", address)
+            }
+            FaultError::InvalidBit(bit) => write!(f, "Bit {} out of range", bit),
+            FaultError::FaultFree(outcome) => write!(f, "Run without faults {}", outcome),
+            FaultError::Elaboration(error) => write!(f, "{}", error),
+            FaultError::Json(error) => write!(f, "Error while decoding campaign: {}", error),
+            FaultError::Io(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for FaultError {}
+
+// a fault injected, with the ordered component and sim_state index of the output faulted
+#[derive(Clone, Debug)]
+pub(crate) struct InjectedFault {
+    fault: Fault,
+    component: usize,
+    index: Option<usize>,
+}
+
+// data as `Data` if it fits, as for parsed values
+fn from_wide(data: SignalWide) -> SignalValue {
+    match SignalUnsigned::try_from(data) {
+        Ok(data) => SignalValue::Data(data),
+        Err(_) => SignalValue::Wide(data),
+    }
+}
+
+impl InjectedFault {
+    // the output value with the fault applied in `cycle`, None if not faulted
+    fn apply(&self, value: SignalValue, cycle: usize) -> Option<SignalValue> {
+        match self.fault {
+            Fault::StuckAt {
+                bit: None,
+                value: stuck,
+                from,
+                ..
+            } if cycle >= from => Some(from_wide(stuck)),
+            Fault::StuckAt {
+                bit: Some(bit),
+                value: stuck,
+                from,
+                ..
+            } if cycle >= from => {
+                let data = value.as_wide()?;
+                Some(from_wide((data & !(1 << bit)) | ((stuck & 1) << bit)))
+            }
+            Fault::BitFlip { bit, cycle: at, .. } if cycle == at => {
+                Some(from_wide(value.as_wide()? ^ (1 << bit)))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Simulator {
+    /// inject a fault, applied from the next clock on, returns its index
+    pub fn inject_fault(&mut self, fault: Fault) -> Result<usize, FaultError> {
+        let bit = match &fault {
+            Fault::StuckAt { bit, .. } => *bit,
+            Fault::BitFlip { bit, .. } => Some(*bit),
+            Fault::MemoryCorrupt { .. } => None,
+        };
+        if let Some(bit) = bit.filter(|bit| *bit >= SignalWide::BITS) {
+            return Err(FaultError::InvalidBit(bit));
+        }
+        let (component, index) = match &fault {
+            Fault::StuckAt { signal, .. } | Fault::BitFlip { signal, .. } => {
+                let index = self
+                    .get_input_index(signal)
+                    .ok_or_else(|| FaultError::UnknownSignal(UnknownSignal(signal.clone())))?;
+                (self.component_index[&signal.id], Some(index))
+            }
+            Fault::MemoryCorrupt { id, .. } => {
+                let component = *self
+                    .component_index
+                    .get(id)
+                    .ok_or_else(|| FaultError::UnknownComponent(id.clone()))?;
+                (component, None)
+            }
+        };
+        self.faults.push(InjectedFault {
+            fault,
+            component,
+            index,
+        });
+        Ok(self.faults.len() - 1)
+    }
+
+    /// remove all faults, outputs forced keep their value until clocked
+    pub fn clear_faults(&mut self) {
+        self.faults.clear();
+    }
+
+    pub fn faults(&self) -> Vec<&Fault> {
+        self.faults.iter().map(|f| &f.fault).collect()
+    }
+
+    /// xor the byte at `address` of memory component `id` with `mask`,
+    /// kept in the undo history of the cycle about to be clocked
+    pub fn corrupt_memory(&mut self, id: &str, address: usize, mask: u8) -> Result<(), FaultError> {
+        let component = self
+            .component_index
+            .get(id)
+            .ok_or_else(|| FaultError::UnknownComponent(id.to_string()))?;
+        self.ordered_components[*component].corrupt_memory(self, address, mask)?;
+        // the memory is read again also if event driven
+        self.full_sweep = true;
+        Ok(())
+    }
+
+    // apply the memory faults of the cycle about to be clocked,
+    // a component not being a memory reports a warning
+    pub(crate) fn apply_memory_faults(&mut self) {
+        for fault in &self.faults {
+            if let Fault::MemoryCorrupt {
+                address,
+                mask,
+                cycle,
+                ..
+            } = fault.fault
+            {
+                if cycle != self.cycle {
+                    continue;
+                }
+                let component = &self.ordered_components[fault.component];
+                if let Err(err) = component.corrupt_memory(self, address, mask) {
+                    self.component_condition.push((
+                        self.component_ids[fault.component].clone(),
+                        Condition::Warning(err.to_string()),
+                    ));
+                }
+            }
+        }
+    }
+
+    // ordered components clocked in the cycle about to be clocked also if their inputs did not
+    // change: memories corrupted in it, and components with an output flipped in the cycle
+    // before (computing the output again, as when not event driven)
+    pub(crate) fn faulted_components(&self) -> Vec<usize> {
+        self.faults
+            .iter()
+            .filter(|fault| match fault.fault {
+                Fault::MemoryCorrupt { cycle, .. } => cycle == self.cycle,
+                Fault::BitFlip { cycle, .. } => cycle + 1 == self.cycle,
+                Fault::StuckAt { .. } => false,
+            })
+            .map(|fault| fault.component)
+            .collect()
+    }
+
+    // apply the faults of the outputs of ordered component `component`,
+    // returns true if an output was changed
+    pub(crate) fn apply_output_faults(&mut self, component: usize) -> bool {
+        let mut changed = false;
+        for fault in &self.faults {
+            if fault.component != component {
+                continue;
+            }
+            let Some(index) = fault.index else {
+                continue;
+            };
+            let value = self.sim_state[index].get_value();
+            if let Some(faulted) = fault.apply(value, self.cycle) {
+                changed |= faulted != value;
+                self.sim_state[index].set_value(faulted);
+            }
+        }
+        changed
+    }
+}
+
+/// Faults run one at a time, each for `cycles` cycles after reset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Campaign {
+    pub cycles: usize,
+    pub faults: Vec<Fault>,
+}
+
+/// Outcome of a run, the first failed assertion or error reported, if any
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Detected {
+        cycle: usize,
+        id: Id,
+        condition: Condition,
+    },
+    Undetected,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Detected {
+                cycle,
+                id,
+                condition,
+            } => write!(f, "detected in cycle {} by {}: {:?}", cycle, id, condition),
+            Outcome::Undetected => write!(f, "undetected"),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct FaultResult {
+    pub fault: Fault,
+    pub outcome: Outcome,
+}
+
+/// Outcome of each fault
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct CampaignReport {
+    pub cycles: usize,
+    pub results: Vec<FaultResult>,
+}
+
+impl CampaignReport {
+    pub fn detected(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|r| r.outcome != Outcome::Undetected)
+            .count()
+    }
+}
+
+impl fmt::Display for CampaignReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in &self.results {
+            writeln!(f, "{}: {}", result.fault, result.outcome)?;
+        }
+        write!(
+            f,
+            "{}/{} faults detected within {} cycles",
+            self.detected(),
+            self.results.len(),
+            self.cycles
+        )
+    }
+}
+
+impl Campaign {
+    pub fn load(path: &Path) -> Result<Self, FaultError> {
+        let json = fs::read_to_string(path).map_err(FaultError::Io)?;
+        serde_json::from_str(&json).map_err(FaultError::Json)
+    }
+
+    /// run the model without faults, and with each fault, each from a new model,
+    /// an error if the run without faults already fails
+    pub fn run(&self, model: impl Fn() -> ComponentStore) -> Result<CampaignReport, FaultError> {
+        let run = |fault: Option<&Fault>| -> Result<Outcome, FaultError> {
+            let mut simulator =
+                Simulator::new(model()).map_err(|err| FaultError::Elaboration(Box::new(err)))?;
+            if let Some(fault) = fault {
+                simulator.inject_fault(fault.clone())?;
+                simulator.reset();
+            }
+            Ok(simulator.run_detecting(self.cycles))
+        };
+        let fault_free = run(None)?;
+        if fault_free != Outcome::Undetected {
+            return Err(FaultError::FaultFree(fault_free));
+        }
+        let results = self
+            .faults
+            .iter()
+            .map(|fault| {
+                Ok(FaultResult {
+                    fault: fault.clone(),
+                    outcome: run(Some(fault))?,
+                })
+            })
+            .collect::<Result<_, FaultError>>()?;
+        Ok(CampaignReport {
+            cycles: self.cycles,
+            results,
+        })
+    }
+}
+
+impl Simulator {
+    // clock up to `cycles` cycles, until a failed assertion or error is reported, or halted,
+    // conditions reported by reset are also detected
+    fn run_detecting(&mut self, cycles: usize) -> Outcome {
+        for clocked in 0..=cycles {
+            if let Some((id, condition)) = self.failed_condition() {
+                return Outcome::Detected {
+                    // the cycle the condition was reported in
+                    cycle: self.cycle - 1,
+                    id: id.clone(),
+                    condition: condition.clone(),
+                };
+            }
+            if clocked == cycles || self.running_state == RunningState::Halt {
+                break;
+            }
+            self.clock();
+        }
+        Outcome::Undetected
+    }
+}
+
+#[cfg(all(test, feature = "components"))]
+mod test {
+    use super::*;
+    use crate::components::*;
+    use crate::fixtures::counter;
+
+    // counter, and a mux selecting by its lowest two bits
+    fn model() -> ComponentStore {
+        let mut cs = counter(1);
+        cs.store.push(Constant::rc_new("mask", (0.0, 0.0), 3));
+        cs.store.push(And::rc_new(
+            "and",
+            (0.0, 0.0),
+            Input::new("reg", REGISTER_OUT_ID),
+            Input::new("mask", CONSTANT_OUT_ID),
+        ));
+        cs.store.push(Mux::rc_new(
+            "mux",
+            (0.0, 0.0),
+            Input::new("and", AND_OUT_ID),
+            vec![Input::new("step", CONSTANT_OUT_ID); 4],
+        ));
+        cs
+    }
+
+    #[test]
+    fn test_inject_fault() {
+        let reg = Input::new("reg", REGISTER_OUT_ID);
+        let mut simulator = Simulator::new(model()).unwrap();
+        simulator
+            .inject_fault(Fault::BitFlip {
+                signal: reg.clone(),
+                bit: 4,
+                cycle: 3,
+            })
+            .unwrap();
+        while simulator.cycle < 5 {
+            simulator.clock();
+        }
+        // 3 flipped to 19 in cycle 3, counting on from there
+        assert_eq!(simulator.get_input_value(&reg), 20.into());
+
+        // faults are kept over reset, and applied from cycle 0 on
+        simulator.clear_faults();
+        simulator
+            .inject_fault(Fault::StuckAt {
+                signal: reg.clone(),
+                bit: Some(0),
+                value: 0,
+                from: 0,
+            })
+            .unwrap();
+        simulator.reset();
+        for _ in 0..4 {
+            simulator.clock();
+            assert_eq!(simulator.get_input_value(&reg), 0.into());
+        }
+        assert_eq!(simulator.faults().len(), 1);
+
+        assert!(matches!(
+            simulator.inject_fault(Fault::BitFlip {
+                signal: reg,
+                bit: 64,
+                cycle: 0
+            }),
+            Err(FaultError::InvalidBit(64))
+        ));
+        assert!(simulator
+            .inject_fault(Fault::MemoryCorrupt {
+                id: "missing".into(),
+                address: 0,
+                mask: 1,
+                cycle: 0
+            })
+            .is_err());
+        assert!(matches!(
+            simulator.corrupt_memory("reg", 0, 1),
+            Err(FaultError::NotMemory(id)) if id == "reg"
+        ));
+    }
+
+    #[test]
+    fn test_bit_flip_event_driven() {
+        // the inputs of the adder never change, the flip is computed again the cycle after
+        let model = || ComponentStore {
+            store: vec![
+                Constant::rc_new("one", (0.0, 0.0), 1),
+                Add::rc_new(
+                    "add",
+                    (0.0, 0.0),
+                    Input::new("one", CONSTANT_OUT_ID),
+                    Input::new("one", CONSTANT_OUT_ID),
+                ),
+            ],
+        };
+        let add = Input::new("add", ADD_OUT_ID);
+        let mut full = Simulator::new(model()).unwrap();
+        let mut event = Simulator::new(model()).unwrap();
+        event.set_event_driven(true);
+        for simulator in [&mut full, &mut event] {
+            simulator
+                .inject_fault(Fault::BitFlip {
+                    signal: add.clone(),
+                    bit: 0,
+                    cycle: 2,
+                })
+                .unwrap();
+            let mut values = vec![];
+            for _ in 0..3 {
+                simulator.clock();
+                values.push(simulator.get_input_value(&add));
+            }
+            assert_eq!(values, [2.into(), 3.into(), 2.into()]);
+        }
+    }
+
+    #[test]
+    fn test_campaign() {
+        let json = r#"{"cycles": 10, "faults": [
+            {"kind": "StuckAt", "signal": {"id": "and", "field": "and_out"}, "bit": 2, "value": 1},
+            {"kind": "BitFlip", "signal": {"id": "reg", "field": "register_out"}, "bit": 8,
+             "cycle": 2}]}"#;
+        let campaign: Campaign = serde_json::from_str(json).unwrap();
+        let report = campaign.run(model).unwrap();
+        // the mux select out of range is detected (already by reset), the flip is masked
+        assert!(matches!(
+            &report.results[0].outcome,
+            Outcome::Detected { cycle: 0, id, condition: Condition::Error(_) } if id == "mux"
+        ));
+        assert_eq!(report.results[1].outcome, Outcome::Undetected);
+        assert_eq!(report.detected(), 1);
+        assert!(report
+            .to_string()
+            .ends_with("1/2 faults detected within 10 cycles"));
+        assert_eq!(
+            report.results[1].fault.to_string(),
+            "reg.register_out bit 8 flipped in cycle 2"
+        );
+
+        // a counter selecting out of the mux inputs fails without faults
+        let failing = || {
+            let mut cs = counter(1);
+            cs.store.push(Mux::rc_new(
+                "mux",
+                (0.0, 0.0),
+                Input::new("reg", REGISTER_OUT_ID),
+                vec![Input::new("step", CONSTANT_OUT_ID); 2],
+            ));
+            cs
+        };
+        assert!(matches!(
+            campaign.run(failing),
+            Err(FaultError::FaultFree(Outcome::Detected { id, .. })) if id == "mux"
+        ));
+    }
+}
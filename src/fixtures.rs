@@ -0,0 +1,43 @@
+// Models shared by the unit tests
+use crate::common::{ComponentStore, Input, Signal};
+use crate::components::*;
+
+// add a counter in register `reg`, incremented by constant `step` through adder `add`
+pub(crate) fn push_counter(cs: &mut ComponentStore, reg: &str, add: &str, step: &str) {
+    cs.store.push(Register::rc_new(
+        reg,
+        (0.0, 0.0),
+        Input::new(add, ADD_OUT_ID),
+    ));
+    cs.store.push(Add::rc_new(
+        add,
+        (0.0, 0.0),
+        Input::new(reg, REGISTER_OUT_ID),
+        Input::new(step, CONSTANT_OUT_ID),
+    ));
+}
+
+// counter in register `reg`, incremented by constant `step` of value `step` through adder `add`
+pub(crate) fn counter(step: impl Into<Signal>) -> ComponentStore {
+    let mut cs = ComponentStore {
+        store: vec![Constant::rc_new("step", (0.0, 0.0), step)],
+    };
+    push_counter(&mut cs, "reg", "add", "step");
+    cs
+}
+
+// accumulator in register `acc`, adding the value of ProbeEdit `in` each cycle through `add`
+pub(crate) fn accumulator() -> ComponentStore {
+    ComponentStore {
+        store: vec![
+            ProbeEdit::rc_new("in", (0.0, 0.0)),
+            Register::rc_new("acc", (0.0, 0.0), Input::new("add", ADD_OUT_ID)),
+            Add::rc_new(
+                "add",
+                (0.0, 0.0),
+                Input::new("acc", REGISTER_OUT_ID),
+                Input::new("in", PROBE_EDIT_OUT_ID),
+            ),
+        ],
+    }
+}
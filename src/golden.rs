@@ -0,0 +1,303 @@
+// Golden files, values of selected outputs recorded per cycle, to check later runs against
+//
+// A golden file is recorded by running a model, and checked by re-running the model from the
+// same cycle (e.g., after reset), reporting the first cycle and signal diverging. Values are
+// saved as text (e.g., `0x10`, `x` unknown), a value edited to `-` (don't care) is not checked.
+use crate::breakpoint::UnknownSignal;
+use crate::common::{Input, RunningState, SignalValue, Simulator};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    fs::File,
+    io::{prelude::*, BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Golden {
+    pub signals: Vec<Input>,
+    // cycle of the first values
+    pub first_cycle: usize,
+    // per cycle, the value of each signal
+    pub values: Vec<Vec<String>>,
+}
+
+/// First value differing from the golden file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    pub cycle: usize,
+    pub input: Input,
+    pub expected: SignalValue,
+    pub actual: SignalValue,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cycle {}: {}.{} expected {}, found {}",
+            self.cycle,
+            self.input.id,
+            self.input.field,
+            value_text(self.expected),
+            value_text(self.actual)
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum GoldenError {
+    Json(serde_json::Error),
+    Io(std::io::Error, PathBuf),
+    UnknownSignal(UnknownSignal),
+    // invalid value in the golden file
+    Value(usize, Input, String),
+    // the simulator is not at the first cycle of the golden file
+    Cycle { cycle: usize, first_cycle: usize },
+    Diverged(Divergence),
+}
+
+impl fmt::Display for GoldenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GoldenError::Json(error) => write!(f, "Error while decoding golden file: {}", error),
+            GoldenError::Io(error, path) => {
+                write!(f, "Error while accessing file {:?} : {}", path, error)
+            }
+            GoldenError::UnknownSignal(error) => write!(f, "{}", error),
+            GoldenError::Value(cycle, input, text) => write!(
+                f,
+                "Invalid value {:?} of {}.{} in cycle {}",
+                text, input.id, input.field, cycle
+            ),
+            GoldenError::Cycle { cycle, first_cycle } => write!(
+                f,
+                "Simulator at cycle {}, golden file starts at cycle {}",
+                cycle, first_cycle
+            ),
+            GoldenError::Diverged(divergence) => write!(f, "Diverged at {}", divergence),
+        }
+    }
+}
+
+impl std::error::Error for GoldenError {}
+
+impl From<serde_json::Error> for GoldenError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+// value as text, parsed back by `SignalValue::from_str`
+pub(crate) fn value_text(value: SignalValue) -> String {
+    match value {
+        SignalValue::Data(data) => format!("{:#x}", data),
+        SignalValue::Wide(data) => format!("{:#x}", data),
+        SignalValue::Unknown => "x".to_string(),
+        SignalValue::DontCare => "-".to_string(),
+        SignalValue::Uninitialized => "Uninitialized".to_string(),
+    }
+}
+
+impl Golden {
+    /// record `signals` in the current cycle and the following `cycles` cycles,
+    /// stops early when the simulator halts
+    pub fn record(
+        simulator: &mut Simulator,
+        signals: &[Input],
+        cycles: usize,
+    ) -> Result<Self, GoldenError> {
+        let indices = signal_indices(simulator, signals)?;
+        let sample = |simulator: &Simulator| {
+            indices
+                .iter()
+                .map(|index| value_text(simulator.get(*index).get_value()))
+                .collect()
+        };
+        let mut golden = Golden {
+            signals: signals.to_vec(),
+            first_cycle: simulator.cycle,
+            values: vec![sample(simulator)],
+        };
+        for _ in 0..cycles {
+            simulator.clock();
+            golden.values.push(sample(simulator));
+            if matches!(
+                simulator.running_state,
+                RunningState::Halt | RunningState::Err
+            ) {
+                break;
+            }
+        }
+        Ok(golden)
+    }
+
+    /// run the simulator (at the first cycle) through the cycles of the golden file,
+    /// stops at the first value diverging
+    pub fn check(&self, simulator: &mut Simulator) -> Result<(), GoldenError> {
+        if simulator.cycle != self.first_cycle {
+            return Err(GoldenError::Cycle {
+                cycle: simulator.cycle,
+                first_cycle: self.first_cycle,
+            });
+        }
+        let indices = signal_indices(simulator, &self.signals)?;
+        for (i, values) in self.values.iter().enumerate() {
+            if i > 0 {
+                simulator.clock();
+            }
+            for ((input, index), text) in self.signals.iter().zip(&indices).zip(values) {
+                let expected = text.parse::<SignalValue>().map_err(|_| {
+                    GoldenError::Value(simulator.cycle, input.clone(), text.clone())
+                })?;
+                let actual = simulator.get(*index).get_value();
+                // known values are compared by value, values of wide outputs that fit
+                // are parsed back as `Data`
+                let diverged = match (expected.as_wide(), actual.as_wide()) {
+                    (Some(expected), Some(actual)) => expected != actual,
+                    _ => expected != SignalValue::DontCare && expected != actual,
+                };
+                if diverged {
+                    return Err(GoldenError::Diverged(Divergence {
+                        cycle: simulator.cycle,
+                        input: input.clone(),
+                        expected,
+                        actual,
+                    }));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), GoldenError> {
+        let file = File::create(path).map_err(|err| GoldenError::Io(err, path.into()))?;
+        let mut w = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut w, self)?;
+        w.flush().map_err(|err| GoldenError::Io(err, path.into()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, GoldenError> {
+        let file = File::open(path).map_err(|err| GoldenError::Io(err, path.into()))?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+}
+
+impl Simulator {
+    /// all outputs of the model, in model order
+    pub fn outputs(&self) -> Vec<Input> {
+        self.graph
+            .node_weights()
+            .flat_map(|id| {
+                self.output_fields[self.component_index[id]]
+                    .iter()
+                    .map(move |field| Input::new(id, field))
+            })
+            .collect()
+    }
+}
+
+/// parse a signal given as `id.field`
+pub fn parse_signal(text: &str) -> Result<Input, String> {
+    let (id, field) = text
+        .rsplit_once('.')
+        .ok_or_else(|| format!("expected id.field, found {:?}", text))?;
+    Ok(Input::new(id, field))
+}
+
+// sim_state index of each signal
+fn signal_indices(simulator: &Simulator, signals: &[Input]) -> Result<Vec<usize>, GoldenError> {
+    signals
+        .iter()
+        .map(|input| {
+            simulator
+                .get_input_index(input)
+                .ok_or_else(|| GoldenError::UnknownSignal(UnknownSignal(input.clone())))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{ComponentStore, SignalWide};
+    use crate::components::*;
+    use crate::fixtures::counter;
+
+    #[test]
+    fn test_golden() {
+        let mut simulator = Simulator::new(counter(1)).unwrap();
+        let signals = simulator.outputs();
+        assert_eq!(signals.len(), 4);
+        let golden = Golden::record(&mut simulator, &signals, 5).unwrap();
+        assert_eq!(golden.values.len(), 6);
+        let json = serde_json::to_string(&golden).unwrap();
+        let golden: Golden = serde_json::from_str(&json).unwrap();
+
+        simulator.reset();
+        golden.check(&mut simulator).unwrap();
+        assert!(matches!(
+            golden.check(&mut simulator),
+            Err(GoldenError::Cycle { first_cycle: 1, .. })
+        ));
+
+        let reg = Input::new("reg", REGISTER_OUT_ID);
+        let mut golden = Golden::record(
+            &mut Simulator::new(counter(1)).unwrap(),
+            std::slice::from_ref(&reg),
+            5,
+        )
+        .unwrap();
+        let Err(GoldenError::Diverged(divergence)) =
+            golden.check(&mut Simulator::new(counter(2)).unwrap())
+        else {
+            panic!("no divergence");
+        };
+        assert_eq!(
+            divergence,
+            Divergence {
+                cycle: 2,
+                input: reg,
+                expected: 1.into(),
+                actual: 2.into(),
+            }
+        );
+        assert_eq!(
+            divergence.to_string(),
+            "cycle 2: reg.register_out expected 0x1, found 0x2"
+        );
+
+        // don't care values are not checked
+        golden.values[1][0] = "-".to_string();
+        let Err(GoldenError::Diverged(divergence)) =
+            golden.check(&mut Simulator::new(counter(2)).unwrap())
+        else {
+            panic!("no divergence");
+        };
+        assert_eq!(divergence.cycle, 3);
+
+        assert_eq!(parse_signal("alu/add.add_out").unwrap().id, "alu/add");
+        assert!(parse_signal("add").is_err());
+    }
+    #[test]
+    fn test_golden_wide() {
+        let wide = |small: SignalWide| {
+            let cs = ComponentStore {
+                store: vec![
+                    Constant::rc_new("small", (0.0, 0.0), SignalValue::Wide(small)),
+                    Constant::rc_new("large", (0.0, 0.0), SignalValue::Wide(0x1_0000_0000)),
+                ],
+            };
+            Simulator::new(cs).unwrap()
+        };
+        let mut simulator = wide(5);
+        let signals = simulator.outputs();
+        let golden = Golden::record(&mut simulator, &signals, 2).unwrap();
+        assert_eq!(golden.values[0], ["0x5", "0x100000000"]);
+        golden.check(&mut wide(5)).unwrap();
+        let Err(GoldenError::Diverged(divergence)) = golden.check(&mut wide(6)) else {
+            panic!("no divergence");
+        };
+        assert_eq!(divergence.input, Input::new("small", CONSTANT_OUT_ID));
+    }
+}
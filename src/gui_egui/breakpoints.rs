@@ -0,0 +1,125 @@
+use crate::breakpoint::BreakCondition;
+use crate::common::{Input, SignalValue, Simulator};
+use crate::gui_egui::helper::signal_menu;
+use egui::{Color32, ComboBox, Context, DragValue, Ui};
+
+// Breakpoint panel, lists the simulator breakpoints and adds new ones
+pub struct BreakpointPanel {
+    pub visible: bool,
+    // breakpoint being added
+    input: Option<Input>,
+    changes: bool,
+    value: String,
+    hit_count: usize,
+    error: Option<String>,
+}
+
+impl Default for BreakpointPanel {
+    fn default() -> Self {
+        BreakpointPanel {
+            visible: false,
+            input: None,
+            changes: false,
+            value: String::new(),
+            hit_count: 1,
+            error: None,
+        }
+    }
+}
+
+impl BreakpointPanel {
+    /// breakpoints can only be changed when `editable`, i.e., not while free-running
+    pub fn render(&mut self, ctx: &Context, simulator: &mut Simulator, editable: bool) {
+        let mut visible = self.visible;
+        egui::Window::new("Breakpoints")
+            .open(&mut visible)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(editable, |ui| {
+                    self.render_list(ui, simulator);
+                    ui.separator();
+                    self.render_add(ui, simulator);
+                });
+            });
+        self.visible = visible;
+    }
+
+    fn render_list(&mut self, ui: &mut Ui, simulator: &mut Simulator) {
+        if simulator.breakpoints().is_empty() {
+            ui.label("No breakpoints");
+            return;
+        }
+        let mut remove = None;
+        egui::Grid::new("breakpoints").striped(true).show(ui, |ui| {
+            for (index, breakpoint) in simulator.breakpoints_mut().iter_mut().enumerate() {
+                let text = breakpoint.to_string();
+                ui.checkbox(&mut breakpoint.enabled, text);
+                ui.label(format!("hits: {}", breakpoint.hits));
+                if ui.button("🗑").on_hover_text("Remove").clicked() {
+                    remove = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(index) = remove {
+            simulator.remove_breakpoint(index);
+        }
+    }
+
+    fn render_add(&mut self, ui: &mut Ui, simulator: &mut Simulator) {
+        ui.horizontal(|ui| {
+            let text = match &self.input {
+                Some(input) => format!("{}.{}", input.id, input.field),
+                None => "Signal".to_string(),
+            };
+            ui.menu_button(text, |ui| {
+                signal_menu(ui, simulator, |ui, input| {
+                    if ui.button(&input.field).clicked() {
+                        self.input = Some(input);
+                        ui.close_menu();
+                    }
+                });
+            });
+            ComboBox::from_id_salt("breakpoint_condition")
+                .selected_text(if self.changes { "changes" } else { "==" })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.changes, false, "==");
+                    ui.selectable_value(&mut self.changes, true, "changes");
+                });
+            if !self.changes {
+                ui.add(egui::TextEdit::singleline(&mut self.value).desired_width(80.0));
+            }
+            ui.label("hit");
+            ui.add(DragValue::new(&mut self.hit_count).range(1..=usize::MAX));
+            if ui
+                .add_enabled(self.input.is_some(), egui::Button::new("Add"))
+                .clicked()
+            {
+                self.add(simulator);
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+    }
+
+    fn add(&mut self, simulator: &mut Simulator) {
+        let Some(input) = self.input.clone() else {
+            return;
+        };
+        let condition = if self.changes {
+            BreakCondition::Changes
+        } else {
+            match self.value.trim().parse::<SignalValue>() {
+                Ok(value) => BreakCondition::Equals(value),
+                Err(err) => {
+                    self.error = Some(err.to_string());
+                    return;
+                }
+            }
+        };
+        self.error = simulator
+            .add_breakpoint(input, condition, self.hit_count)
+            .err()
+            .map(|err| err.to_string());
+    }
+}
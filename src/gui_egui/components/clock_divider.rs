@@ -0,0 +1,72 @@
+use crate::common::{EguiComponent, Id, Ports, Simulator};
+use crate::components::ClockDivider;
+use crate::gui_egui::editor::{EditorMode, EditorRenderReturn, GridOptions};
+use crate::gui_egui::gui::EguiExtra;
+use crate::gui_egui::helper::basic_component_gui;
+use egui::{Pos2, Rect, Response, Ui, Vec2};
+
+#[typetag::serde]
+impl EguiComponent for ClockDivider {
+    fn render(
+        &self,
+        ui: &mut Ui,
+        _context: &mut EguiExtra,
+        simulator: Option<&mut Simulator>,
+        offset: Vec2,
+        scale: f32,
+        clip_rect: Rect,
+        _editor_mode: EditorMode,
+    ) -> Option<Vec<Response>> {
+        basic_component_gui(self, &simulator, ui.ctx(), offset, scale, clip_rect, |ui| {
+            ui.label(format!("⏱ {} ÷{}", self.id, self.divisor))
+                .on_hover_text(format!(
+                    "phase {}, clocking {}",
+                    self.phase,
+                    self.components.join(", ")
+                ));
+        })
+    }
+
+    fn render_editor(
+        &mut self,
+        ui: &mut Ui,
+        context: &mut EguiExtra,
+        simulator: Option<&mut Simulator>,
+        offset: Vec2,
+        scale: f32,
+        clip_rect: Rect,
+        _id_ports: &[(Id, Ports)],
+        _grid: &GridOptions,
+        editor_mode: EditorMode,
+    ) -> EditorRenderReturn {
+        let resp = self.render(
+            ui,
+            context,
+            simulator,
+            offset,
+            scale,
+            clip_rect,
+            editor_mode,
+        );
+        EditorRenderReturn {
+            delete: false,
+            resp,
+        }
+    }
+
+    fn ports_location(&self) -> Vec<(Id, Pos2)> {
+        vec![]
+    }
+
+    fn top_padding(&self) -> f32 {
+        20f32
+    }
+
+    fn set_pos(&mut self, pos: (f32, f32)) {
+        self.pos = pos;
+    }
+
+    fn get_pos(&self) -> (f32, f32) {
+        self.pos
+    }
+}
@@ -1,5 +1,6 @@
 mod add;
 mod and;
+mod clock_divider;
 mod constant;
 mod cross;
 mod equal;
@@ -14,5 +15,6 @@ mod probe_stim;
 mod register;
 mod sext;
 mod shift_left_const;
+mod sub_circuit;
 mod wire;
 mod zero_extend;
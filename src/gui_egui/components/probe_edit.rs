@@ -1,4 +1,6 @@
-use crate::common::{EguiComponent, Ports, SignalSigned, SignalUnsigned, SignalValue, Simulator};
+use crate::common::{
+    EguiComponent, Ports, SignalSigned, SignalUnsigned, SignalValue, SignalWide, Simulator,
+};
 use crate::components::{ProbeEdit, TextSignal};
 use crate::gui_egui::component_ui::{
     drag_logic, input_change_id, pos_drag_value, properties_window, rect_with_hover,
@@ -54,6 +56,20 @@ impl EguiComponent for ProbeEdit {
                         };
                         r
                     }
+                    SignalValue::Wide(d) => {
+                        let mut val = d;
+                        ui.style_mut()
+                            .text_styles
+                            .get_mut(&TextStyle::Button)
+                            .unwrap()
+                            .size = scale * 12f32;
+                        let r = ui.add(DragValue::new(&mut val));
+                        *self.edit_history.write().unwrap().last_mut().unwrap() = TextSignal {
+                            text: format!("{}", val),
+                            signal: SignalValue::Wide(val).into(),
+                        };
+                        r
+                    }
                     SignalValue::Uninitialized => ui.label("Uninitialized"),
                     SignalValue::DontCare => ui.label("DontCare"),
                     SignalValue::Unknown => ui.label("Unknown"),
@@ -180,9 +196,13 @@ fn parse_signal(text: &str) -> SignalValue {
         (signal as SignalUnsigned).into()
     } else if let Ok(signal) = text.parse::<SignalUnsigned>() {
         (signal as SignalUnsigned).into()
+    } else if let Ok(signal) = text.parse::<SignalWide>() {
+        SignalValue::Wide(signal)
     } else if let Some(hex) = text.strip_prefix("0x") {
         if let Ok(signal) = SignalUnsigned::from_str_radix(hex, 16) {
             signal.into()
+        } else if let Ok(signal) = SignalWide::from_str_radix(hex, 16) {
+            SignalValue::Wide(signal)
         } else {
             SignalValue::Unknown
         }
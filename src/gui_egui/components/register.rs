@@ -1,13 +1,19 @@
 use crate::common::{EguiComponent, Input, Ports, SignalUnsigned, Simulator};
-use crate::components::{Register, REGISTER_OUT_ID};
+use crate::components::{
+    Register, REGISTER_ENABLE_ID, REGISTER_OUT_ID, REGISTER_RESET_ID, REGISTER_R_IN_ID,
+};
 use crate::gui_egui::component_ui::{
-    drag_logic, input_change_id, input_selector, pos_drag_value, properties_window,
-    rect_with_hover, visualize_ports,
+    drag_logic, input_change_id, input_selector, input_selector_removeable, pos_drag_value,
+    properties_window, rect_with_hover, visualize_ports,
 };
 use crate::gui_egui::editor::{EditorMode, EditorRenderReturn, GridOptions};
 use crate::gui_egui::gui::EguiExtra;
 use crate::gui_egui::helper::offset_helper;
-use egui::{Color32, Pos2, Rect, Response, Shape, Stroke, Ui, Vec2};
+use egui::{Align2, Color32, DragValue, FontId, Pos2, Rect, Response, Shape, Stroke, Ui, Vec2};
+
+// location of the enable and reset inputs, at the bottom
+const ENABLE_POS: (f32, f32) = (-5f32, 20f32);
+const RESET_POS: (f32, f32) = (5f32, 20f32);
 
 #[typetag::serde]
 impl EguiComponent for Register {
@@ -47,6 +53,21 @@ impl EguiComponent for Register {
                 color: Color32::BLACK,
             },
         ));
+        // enable and reset inputs
+        for (input, pos, text) in [
+            (&self.enable, ENABLE_POS, "e"),
+            (&self.reset, RESET_POS, "r"),
+        ] {
+            if input.is_some() {
+                ui.painter().text(
+                    oh((pos.0, pos.1 - 5f32), s, o),
+                    Align2::CENTER_CENTER,
+                    text,
+                    FontId::monospace(8f32 * scale),
+                    Color32::BLACK,
+                );
+            }
+        }
         let rect = Rect {
             min: oh((-10f32, -20f32), s, o),
             max: oh((10f32, 20f32), s, o),
@@ -62,6 +83,16 @@ impl EguiComponent for Register {
                         _ => format!("In {:?}", r),
                     }
                 });
+                if let Some(enable) = &self.enable {
+                    ui.label(format!("Enable {}", s.get_input_signal(enable)));
+                }
+                if let Some(reset) = &self.reset {
+                    ui.label(format!(
+                        "Reset {} (to {:#x})",
+                        s.get_input_signal(reset),
+                        self.reset_value
+                    ));
+                }
                 ui.label({
                     let r: Result<SignalUnsigned, String> = s
                         .get_input_value(&Input {
@@ -130,10 +161,42 @@ impl EguiComponent for Register {
                 clicked_dropdown |= input_selector(
                     ui,
                     &mut self.r_in,
-                    crate::components::REGISTER_R_IN_ID.to_string(),
+                    REGISTER_R_IN_ID.to_string(),
                     id_ports,
                     self.id.clone(),
                 );
+                for (input, port_id) in [
+                    (&mut self.enable, REGISTER_ENABLE_ID),
+                    (&mut self.reset, REGISTER_RESET_ID),
+                ] {
+                    match input {
+                        Some(i) => {
+                            let (clicked, delete) = input_selector_removeable(
+                                ui,
+                                i,
+                                port_id.to_string(),
+                                id_ports,
+                                self.id.clone(),
+                                true,
+                            );
+                            clicked_dropdown |= clicked;
+                            if delete {
+                                *input = None;
+                            }
+                        }
+                        None => {
+                            if ui.button(format!("+ Add {} input", port_id)).clicked() {
+                                *input = Some(Input::new("id", "field"));
+                            }
+                        }
+                    }
+                }
+                if self.reset.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.label("Reset value");
+                        ui.add(DragValue::new(&mut self.reset_value).hexadecimal(1, false, false));
+                    });
+                }
                 clicked_dropdown
             },
         );
@@ -146,16 +209,29 @@ impl EguiComponent for Register {
 
     fn ports_location(&self) -> Vec<(crate::common::Id, Pos2)> {
         let own_pos = Vec2::new(self.pos.0, self.pos.1);
-        vec![
+        let mut ports = vec![
             (
-                crate::components::REGISTER_R_IN_ID.to_string(),
+                REGISTER_R_IN_ID.to_string(),
                 Pos2::new(-10f32, 0f32) + own_pos,
             ),
             (
-                crate::components::REGISTER_OUT_ID.to_string(),
+                REGISTER_OUT_ID.to_string(),
                 Pos2::new(10f32, 0f32) + own_pos,
             ),
-        ]
+        ];
+        if self.enable.is_some() {
+            ports.push((
+                REGISTER_ENABLE_ID.to_string(),
+                Pos2::from(ENABLE_POS) + own_pos,
+            ));
+        }
+        if self.reset.is_some() {
+            ports.push((
+                REGISTER_RESET_ID.to_string(),
+                Pos2::from(RESET_POS) + own_pos,
+            ));
+        }
+        ports
     }
 
     fn get_input_location(&self, input: Input) -> Option<(f32, f32)> {
@@ -163,21 +239,20 @@ impl EguiComponent for Register {
         let input_pos_change = (-10f32, 0f32);
         let output_pos_change = (10f32, 0f32);
 
-        if input == self.r_in {
+        let change = if input == self.r_in {
             // looks input
-            return Some((
-                own_pos.0 + input_pos_change.0,
-                own_pos.1 + input_pos_change.1,
-            ));
+            input_pos_change
         } else if input == Input::new(&self.id, REGISTER_OUT_ID) {
             // look output
-            return Some((
-                own_pos.0 + output_pos_change.0,
-                own_pos.1 + output_pos_change.1,
-            ));
-        }
-
-        None
+            output_pos_change
+        } else if self.enable.as_ref() == Some(&input) {
+            ENABLE_POS
+        } else if self.reset.as_ref() == Some(&input) {
+            RESET_POS
+        } else {
+            return None;
+        };
+        Some((own_pos.0 + change.0, own_pos.1 + change.1))
     }
 
     fn top_padding(&self) -> f32 {
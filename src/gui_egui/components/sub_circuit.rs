@@ -0,0 +1,118 @@
+use crate::common::{EguiComponent, Id, Input, Ports, Simulator};
+use crate::components::SubCircuit;
+use crate::gui_egui::editor::{EditorMode, EditorRenderReturn, GridOptions};
+use crate::gui_egui::gui::EguiExtra;
+use crate::gui_egui::helper::basic_component_gui;
+use egui::{pos2, Rect, Response, Ui, Vec2};
+
+// spacing of ports
+const PORT_SPACING: f32 = 20.0;
+const WIDTH: f32 = 80.0;
+
+#[typetag::serde]
+impl EguiComponent for SubCircuit {
+    fn render(
+        &self,
+        ui: &mut Ui,
+        _context: &mut EguiExtra,
+        simulator: Option<&mut Simulator>,
+        offset: Vec2,
+        scale: f32,
+        clip_rect: Rect,
+        _editor_mode: EditorMode,
+    ) -> Option<Vec<Response>> {
+        let name = self
+            .file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        basic_component_gui(self, &simulator, ui.ctx(), offset, scale, clip_rect, |ui| {
+            ui.horizontal(|ui| {
+                // components are only shown in the simulator
+                if simulator.is_some() {
+                    let (text, hover) = if self.is_expanded() {
+                        ("⊟", "Collapse")
+                    } else {
+                        ("⊞", "Expand")
+                    };
+                    if ui.button(text).on_hover_text(hover).clicked() {
+                        self.set_expanded(!self.is_expanded());
+                    }
+                }
+                ui.label(name);
+            });
+        })
+    }
+
+    fn render_editor(
+        &mut self,
+        ui: &mut egui::Ui,
+        context: &mut EguiExtra,
+        simulator: Option<&mut Simulator>,
+        offset: egui::Vec2,
+        scale: f32,
+        clip_rect: egui::Rect,
+        _id_ports: &[(Id, Ports)],
+        _grid: &GridOptions,
+        editor_mode: EditorMode,
+    ) -> EditorRenderReturn {
+        self.render(
+            ui,
+            context,
+            simulator,
+            offset,
+            scale,
+            clip_rect,
+            editor_mode,
+        );
+        EditorRenderReturn {
+            delete: false,
+            resp: None,
+        }
+    }
+
+    fn set_pos(&mut self, pos: (f32, f32)) {
+        self.pos = pos;
+    }
+
+    fn get_pos(&self) -> (f32, f32) {
+        self.pos
+    }
+
+    fn top_padding(&self) -> f32 {
+        20f32
+    }
+
+    fn get_input_location(&self, id: Input) -> Option<(f32, f32)> {
+        let location = |index: usize| Some(<(f32, f32)>::from(self.ports_location()[index].1));
+        if let Some(index) = self.inputs.iter().position(|port| port.input == id) {
+            location(index)
+        } else if id.id == self.id {
+            let index = self.outputs.iter().position(|o| o.field == id.field)?;
+            location(self.inputs.len() + index)
+        } else {
+            None
+        }
+    }
+
+    // inputs on the left, outputs on the right
+    fn ports_location(&self) -> Vec<(Id, egui::Pos2)> {
+        let pos: Vec2 = self.pos.into();
+        let column = |ports: Vec<Id>, x: f32| {
+            let top = -(ports.len().saturating_sub(1) as f32) * PORT_SPACING / 2.0;
+            ports
+                .into_iter()
+                .enumerate()
+                .map(move |(i, port)| (port, pos2(x, top + i as f32 * PORT_SPACING) + pos))
+        };
+        column(
+            self.inputs.iter().map(|p| p.port_id.clone()).collect(),
+            -WIDTH / 2.0,
+        )
+        .chain(column(
+            self.outputs.iter().map(|o| o.field.clone()).collect(),
+            WIDTH / 2.0,
+        ))
+        .collect()
+    }
+}
@@ -1,4 +1,5 @@
 use crate::common::{Components, EguiComponent, Id, Input};
+use crate::component_store::ModelSource;
 use crate::components::*;
 use crate::gui_egui::gui::EguiExtra;
 use crate::gui_egui::{
@@ -10,9 +11,13 @@ use crate::gui_egui::{
     menu::Menu,
 };
 use eframe::{egui, Frame};
-use egui::{Color32, Context, LayerId, PointerButton, Pos2, Rect, Shape, Vec2};
+use egui::{
+    Color32, Context, CornerRadius, LayerId, PointerButton, Pos2, Rect, Shape, Stroke, StrokeKind,
+    Vec2,
+};
 use std::{
-    collections::{BTreeMap, HashMap},
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
     ops::Range,
     path::Path,
     rc::Rc,
@@ -35,6 +40,8 @@ pub struct Editor {
     pub wm: WireMode,
     pub im: InputMode,
     pub contexts: HashMap<crate::common::Id, EguiExtra>,
+    // parameter declarations and references of the model file, kept when saving
+    pub source: ModelSource,
 }
 
 #[derive(Clone)]
@@ -122,6 +129,7 @@ impl Default for Library {
                     end: 0x20,
                 },
                 memory: Memory::new(BTreeMap::new()),
+                history: RefCell::default(),
             }),
             Rc::new(Mux {
                 id: "mux".to_string(),
@@ -130,17 +138,18 @@ impl Default for Library {
                 m_in: vec![dummy_input.clone(), dummy_input.clone()],
                 scale: 1.0,
             }),
-            Rc::new(Register {
-                id: "reg".to_string(),
-                pos: (0.0, 0.0),
-                r_in: dummy_input.clone(),
-            }),
+            Rc::new(Register::new("reg", (0.0, 0.0), dummy_input.clone())),
         ];
         Library(library)
     }
 }
 impl Editor {
-    pub fn gui(components: Components, _path: &Path, library: &Library) -> Self {
+    pub fn gui(
+        components: Components,
+        _path: &Path,
+        library: &Library,
+        source: ModelSource,
+    ) -> Self {
         let dummy_input = Input::new("id", "field");
         let library: Components = library.clone().0;
         let library_contexts = crate::gui_egui::gui::create_contexts(&library);
@@ -184,6 +193,7 @@ impl Editor {
                 library_contexts,
             },
             contexts: HashMap::new(),
+            source,
         };
         e.contexts = crate::gui_egui::gui::create_contexts(&e.components);
         e
@@ -273,6 +283,16 @@ impl Editor {
 
     fn draw_area(ctx: &Context, gui: &mut Gui, frame: egui::Frame) {
         let mut layer_id: Option<LayerId> = None;
+        // components and signals of combinational loops, highlighted in red
+        let mut loop_ids = HashSet::new();
+        let mut loop_inputs = HashSet::new();
+        if let Some(err) = &gui.elaboration_error {
+            for step in err.loops().flatten() {
+                loop_ids.insert(step.id.clone());
+                loop_inputs.insert(step.input.clone());
+            }
+        }
+        let mut loop_rects = vec![];
         let central_panel = egui::CentralPanel::default().frame(frame).show(ctx, |ui| {
             ui.set_clip_rect(Editor::gui_to_editor(gui).clip_rect);
 
@@ -339,7 +359,7 @@ impl Editor {
                         //println!("{}, {:?}", old_key, e.contexts);
                         match e.contexts.remove(&old_key) {
                             Some(mut context) => {
-                                c.render(
+                                let resp = c.render(
                                     ui,
                                     &mut context,
                                     None,
@@ -348,6 +368,9 @@ impl Editor {
                                     e.clip_rect,
                                     e.editor_mode,
                                 );
+                                if loop_ids.contains(&old_key) {
+                                    loop_rects.extend(resp.iter().flatten().map(|r| r.rect));
+                                }
                                 e.contexts.insert(context.id_tmp.clone(), context);
                             }
                             _ => {
@@ -374,6 +397,9 @@ impl Editor {
                         &e.grid,
                         e.editor_mode,
                     );
+                    if loop_ids.contains(&old_key) {
+                        loop_rects.extend(render_return.resp.iter().flatten().map(|r| r.rect));
+                    }
                     // only reinsert if it's not getting deleted
                     if !render_return.delete {
                         e.contexts.insert(c.get_id_ports().0, context.clone());
@@ -381,6 +407,33 @@ impl Editor {
                     !render_return.delete
                 }),
             }
+
+            // highlight combinational loops
+            let stroke = Stroke {
+                width: 2.0 * e.scale,
+                color: Color32::RED,
+            };
+            for rect in &loop_rects {
+                ui.painter().rect_stroke(
+                    *rect,
+                    CornerRadius::same(0),
+                    stroke,
+                    StrokeKind::Outside,
+                );
+            }
+            for c in &e.components {
+                if let Some(wire) = c.as_any().downcast_ref::<Wire>() {
+                    if loop_inputs.contains(&wire.input) {
+                        ui.painter().add(Shape::line(
+                            wire.pos
+                                .iter()
+                                .map(|pos| offset_helper(*pos, e.scale, e.offset_and_pan))
+                                .collect(),
+                            stroke,
+                        ));
+                    }
+                }
+            }
         });
         let e = Editor::gui_to_editor(gui);
 
@@ -128,10 +128,12 @@ pub fn last_click(e: &mut Editor, closest_uw: CloseToComponent) {
         }
     }
     let path = PathBuf::from("autosave.json");
-    ComponentStore {
-        store: e.components.clone(),
-    }
-    .save_file(&path);
+    e.source.save_file(
+        &ComponentStore {
+            store: e.components.clone(),
+        },
+        &path,
+    );
     reset_wire_mode(&mut e.wm);
 }
 
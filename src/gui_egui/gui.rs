@@ -1,22 +1,35 @@
-use crate::common::{ComponentStore, Components, Simulator};
+use crate::common::{ComponentStore, Components, EguiComponent, Simulator};
+use crate::component_store::ModelSource;
+use crate::components::SubCircuit;
+use crate::elaboration::ElaborationError;
 use crate::gui_egui::editor::EditorMode;
 use crate::gui_egui::{
+    breakpoints::BreakpointPanel,
     editor::{Editor, Library},
     keymap,
     keymap::Shortcuts,
     menu::Menu,
+    profiler::ProfilerPanel,
+    waveform::Waveform,
 };
+use crate::simulator_thread::{Command, SimulatorThread};
 use eframe::egui;
 use egui::{
     containers, CentralPanel, Color32, Context, PointerButton, Pos2, Rect, Sense, TopBottomPanel,
     Vec2,
 };
+use log::*;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
 pub struct Gui {
     pub simulator: Option<Simulator>,
     pub path: PathBuf,
+    // the model as loaded or last edited, before sub-circuits are flattened,
+    // the editor is built from and saves it
+    pub model: Components,
+    // parameter declarations and references of the model file, kept when saving
+    pub source: ModelSource,
     // History, acts like a stack
     pub scale: f32,
     // When the ui elements change size
@@ -31,6 +44,13 @@ pub struct Gui {
     pub editor_use: bool,
     pub contexts: HashMap<crate::common::Id, EguiExtra>,
     pub library: Library,
+    pub waveform: Waveform,
+    pub breakpoint_panel: BreakpointPanel,
+    pub profiler_panel: ProfilerPanel,
+    // problems found when the model was last elaborated, shown until dismissed
+    pub elaboration_error: Option<ElaborationError>,
+    // worker running the simulation, while free-running
+    pub simulator_thread: Option<SimulatorThread>,
 }
 
 #[derive(Clone, Debug)]
@@ -43,14 +63,34 @@ pub struct EguiExtra {
 
 pub fn gui(cs: ComponentStore, path: &PathBuf, library: Library) -> Result<(), eframe::Error> {
     let contexts = create_contexts(&cs.store);
-    let simulator = Simulator::new(cs).unwrap();
+    let components = cs.store.clone();
+    let source = ModelSource::load_file(path, &cs).unwrap_or_default();
     let options = eframe::NativeOptions::default();
     let path = path.to_owned();
-    simulator.save_dot(&path);
+    // a model with problems is opened in the editor, to be fixed there
+    let (simulator, editor, elaboration_error) = match Simulator::new(cs) {
+        Ok(simulator) => {
+            simulator.save_dot(&path);
+            (Some(simulator), None, None)
+        }
+        Err(err) => (
+            None,
+            Some(Editor::gui(
+                components.clone(),
+                &path,
+                &library,
+                source.clone(),
+            )),
+            Some(err),
+        ),
+    };
+    let editor_use = editor.is_some();
 
     let gui = Gui {
         path,
-        simulator: Some(simulator),
+        simulator,
+        model: components,
+        source,
         scale: 1.0f32,
         ui_change: true,
         offset: Vec2 { x: 0f32, y: 0f32 },
@@ -59,10 +99,15 @@ pub fn gui(cs: ComponentStore, path: &PathBuf, library: Library) -> Result<(), e
         shortcuts: Shortcuts::new(),
         pause: true,
         step_amount: 10,
-        editor: None,
-        editor_use: false,
+        editor,
+        editor_use,
         contexts,
         library,
+        waveform: Waveform::default(),
+        breakpoint_panel: BreakpointPanel::default(),
+        profiler_panel: ProfilerPanel::default(),
+        elaboration_error,
+        simulator_thread: None,
     };
 
     eframe::run_native("SyncRim", options, Box::new(|_cc| Ok(Box::new(gui))))
@@ -71,6 +116,7 @@ pub fn gui(cs: ComponentStore, path: &PathBuf, library: Library) -> Result<(), e
 impl eframe::App for Gui {
     fn update(&mut self, ctx: &Context, frame: &mut eframe::Frame) {
         self.shortcuts.inputs(ctx, self);
+        self.elaboration_error_window(ctx);
         if self.editor_use {
             crate::gui_egui::editor::Editor::update(ctx, frame, self);
             return;
@@ -105,13 +151,19 @@ impl eframe::App for Gui {
             self.top_bar(ctx);
             if self.simulator.is_some() {
                 // self.side_panel(ctx);
-                if self.simulator.as_ref().unwrap().is_running() {
-                    self.simulator.as_mut().unwrap().run();
-
-                    // This makes the ui run agin as to not stop the simulation
-                    // when no ui events are happening
-                    ctx.request_repaint();
-                }
+                self.run_simulator(ctx);
+                // bottom panel, needs to be shown before the central panel
+                self.waveform.render(ctx, self.simulator.as_ref().unwrap());
+                self.breakpoint_panel.render(
+                    ctx,
+                    self.simulator.as_mut().unwrap(),
+                    self.simulator_thread.is_none(),
+                );
+                self.profiler_panel.render(
+                    ctx,
+                    self.simulator.as_mut().unwrap(),
+                    self.simulator_thread.is_none(),
+                );
                 self.draw_area(ctx, frame);
             }
         }
@@ -131,18 +183,34 @@ impl Gui {
     fn draw_area(&mut self, ctx: &Context, frame: egui::Frame) {
         let central_panel = CentralPanel::default().frame(frame).show(ctx, |ui| {
             let sim = self.simulator.as_mut().unwrap();
-            ui.set_clip_rect(self.clip_rect);
             // Don't draw over the rest of the ui
+            let clip_rect = self.clip_rect.intersect(ui.max_rect());
+            ui.set_clip_rect(clip_rect);
+            // components of collapsed sub-circuits are not shown
+            let collapsed: Vec<_> = sim
+                .ordered_components
+                .iter()
+                .filter_map(|c| c.as_any().downcast_ref::<SubCircuit>())
+                .filter(|s| !s.is_expanded())
+                .map(|s| s.prefix())
+                .collect();
             for c in &sim.ordered_components.clone() {
                 let old_key = c.as_ref().get_id_ports().0;
-                let mut context = self.contexts.remove(&old_key).unwrap();
+                if collapsed.iter().any(|prefix| old_key.starts_with(prefix)) {
+                    continue;
+                }
+                // components of sub-circuits are added when elaborated
+                let mut context = self
+                    .contexts
+                    .remove(&old_key)
+                    .unwrap_or_else(|| create_context(&**c));
                 c.render(
                     ui,
                     &mut context,
                     Some(sim),
                     self.offset + self.pan,
                     self.scale,
-                    self.clip_rect,
+                    clip_rect,
                     EditorMode::Simulator,
                 );
                 self.contexts.insert(context.id_tmp.clone(), context);
@@ -163,6 +231,68 @@ impl Gui {
         }
     }
 
+    // free-running is done on a worker thread, started when the simulator is set running
+    fn run_simulator(&mut self, ctx: &Context) {
+        let simulator = self.simulator.as_mut().unwrap();
+        if let Some(thread) = self.simulator_thread.as_mut() {
+            match thread.poll(simulator) {
+                Ok(false) => {}
+                Ok(true) => self.simulator_thread = None,
+                Err(err) => {
+                    error!("{}", err);
+                    self.simulator_thread = None;
+                    let _ = simulator.stop();
+                }
+            }
+        } else if simulator.is_running() {
+            match simulator.run_threaded() {
+                Ok(thread) => self.simulator_thread = Some(thread),
+                Err(err) => {
+                    error!("{}, running in the gui thread", err);
+                    simulator.run();
+                }
+            }
+        }
+        if self.simulator_thread.is_some() || simulator.is_running() {
+            // This makes the ui run agin as to not stop the simulation
+            // when no ui events are happening
+            ctx.request_repaint();
+        }
+    }
+
+    /// send command to the simulator thread, if running, and wait for it to stop
+    /// returns false if no simulator thread was running
+    pub fn finish_simulator_thread(&mut self, command: Command) -> bool {
+        let Some(thread) = self.simulator_thread.take() else {
+            return false;
+        };
+        thread.send(command);
+        if let Err(err) = thread.finish(self.simulator.as_mut().unwrap()) {
+            error!("{}", err);
+        }
+        true
+    }
+
+    fn elaboration_error_window(&mut self, ctx: &Context) {
+        let Some(err) = &self.elaboration_error else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new("Model errors")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(format!("Model has {} problem(s):", err.problems.len()));
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for problem in &err.problems {
+                        ui.colored_label(Color32::RED, problem.to_string());
+                    }
+                });
+            });
+        if !open {
+            self.elaboration_error = None;
+        }
+    }
+
     fn top_bar(&mut self, ctx: &Context) {
         TopBottomPanel::top("topBar").show(ctx, |ui| Menu::new(ui, self));
     }
@@ -170,18 +300,18 @@ impl Gui {
 
 pub fn create_contexts(components: &Components) -> HashMap<crate::common::Id, EguiExtra> {
     let mut contexts = HashMap::new();
-    for c in &components.clone() {
-        let id = c.get_id_ports().0;
-        let pos = c.get_pos();
-        contexts.insert(
-            id.clone(),
-            EguiExtra {
-                properties_window: false,
-                size_rect: Rect::NAN,
-                id_tmp: id,
-                pos_tmp: Pos2::new(pos.0, pos.1),
-            },
-        );
+    for c in components {
+        contexts.insert(c.get_id_ports().0, create_context(&**c));
     }
     contexts
 }
+
+pub fn create_context(c: &dyn EguiComponent) -> EguiExtra {
+    let pos = c.get_pos();
+    EguiExtra {
+        properties_window: false,
+        size_rect: Rect::NAN,
+        id_tmp: c.get_id_ports().0,
+        pos_tmp: Pos2::new(pos.0, pos.1),
+    }
+}
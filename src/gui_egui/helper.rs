@@ -294,6 +294,7 @@ pub fn basic_on_hover(
                     SignalValue::Unknown => "Unknown".to_string(),
                     SignalValue::DontCare => "don't care".to_string(),
                     SignalValue::Data(v) => format!("{:#010x}", v),
+                    SignalValue::Wide(v) => format!("{:#018x}", v),
                 },
             ));
         }
@@ -307,6 +308,7 @@ pub fn basic_on_hover(
                     SignalValue::Unknown => "Unknown".to_string(),
                     SignalValue::DontCare => "Don't care".to_string(),
                     SignalValue::Data(v) => format!("{:#010x}", v),
+                    SignalValue::Wide(v) => format!("{:#018x}", v),
                 },
             ));
         }
@@ -394,3 +396,29 @@ pub fn basic_editor_popup(
         resp: Some(vec![activator_resp]),
     }
 }
+
+/// Menu of the simulator signals, a sub menu per component id with its fields in output order,
+/// `field_ui` adds the widget for each field
+pub fn signal_menu(ui: &mut Ui, simulator: &Simulator, mut field_ui: impl FnMut(&mut Ui, Input)) {
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        let mut ids: Vec<_> = simulator.id_start_index.keys().collect();
+        ids.sort();
+        for id in ids {
+            let mut fields: Vec<_> = simulator
+                .id_field_index
+                .iter()
+                .filter(|((c_id, _), _)| c_id == id)
+                .map(|((_, field), index)| (index, field))
+                .collect();
+            if fields.is_empty() {
+                continue;
+            }
+            fields.sort();
+            ui.menu_button(id, |ui| {
+                for (_, field) in fields {
+                    field_ui(ui, Input::new(id, field));
+                }
+            });
+        }
+    });
+}
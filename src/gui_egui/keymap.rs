@@ -1,11 +1,13 @@
 use crate::common::{ComponentStore, Simulator};
-use crate::component_store::ComponentStoreLoadError;
+use crate::component_store::{ComponentStoreLoadError, ModelSource};
 use crate::gui_egui::editor::{Editor, EditorMode};
 use crate::gui_egui::editor_wire_mode::reset_wire_mode;
 use crate::gui_egui::gui::create_contexts;
 use crate::gui_egui::library::reset_input_mode;
 use crate::gui_egui::Gui;
+use crate::simulator_thread::Command;
 use egui::{Key, KeyboardShortcut, Modifiers};
+use log::*;
 use rfd::FileDialog;
 use std::path::PathBuf;
 
@@ -252,6 +254,8 @@ pub fn file_open_fn(gui: &mut Gui) -> Result<(), ComponentStoreLoadError> {
         gui.path = path_buf;
     }
     let cs = ComponentStore::load_file(&gui.path)?;
+    gui.source = ModelSource::load_file(&gui.path, &cs)?;
+    gui.finish_simulator_thread(Command::Pause);
     let contexts = create_contexts(&cs.store);
     match gui.editor_use {
         true => {
@@ -259,20 +263,24 @@ pub fn file_open_fn(gui: &mut Gui) -> Result<(), ComponentStoreLoadError> {
                 // Clear all references
                 reset_wire_mode(&mut e.wm);
                 reset_input_mode(&mut e.im);
-                e.components = cs.store;
+                e.components = cs.store.clone();
                 e.contexts = contexts;
+                e.source = gui.source.clone();
             }
+            gui.model = cs.store;
         }
         false => {
+            gui.model = cs.store.clone();
             let simulator = Simulator::new(cs);
             gui.contexts = contexts;
             match simulator {
                 Err(e) => {
-                    println!("couldn't open file with simulator: {}", e);
+                    gui.elaboration_error = Some(e);
                 }
                 Ok(s) => {
                     let _ = gui.simulator.take();
                     gui.simulator = Some(s);
+                    gui.elaboration_error = None;
                 }
             }
         }
@@ -283,16 +291,20 @@ pub fn file_save_fn(gui: &mut Gui) {
     match gui.editor_use {
         true => {
             if let Some(e) = gui.editor.as_mut() {
-                ComponentStore {
-                    store: e.components.clone(),
-                }
-                .save_file(&gui.path)
+                e.source.save_file(
+                    &ComponentStore {
+                        store: e.components.clone(),
+                    },
+                    &gui.path,
+                )
             }
         }
-        false => ComponentStore {
-            store: gui.simulator.clone().unwrap().ordered_components,
-        }
-        .save_file(&PathBuf::from("file.json")),
+        false => gui.source.save_file(
+            &ComponentStore {
+                store: gui.model.clone(),
+            },
+            &PathBuf::from("file.json"),
+        ),
     }
 }
 pub fn file_save_as_fn(gui: &mut Gui) {
@@ -302,7 +314,81 @@ pub fn file_save_as_fn(gui: &mut Gui) {
         file_save_fn(gui);
     }
 }
+pub fn file_export_vcd_fn(gui: &mut Gui) {
+    gui.finish_simulator_thread(Command::Pause);
+    if let Some(s) = gui.simulator.as_ref() {
+        let mut path = gui.path.clone();
+        path.set_extension("vcd");
+        let files = FileDialog::new()
+            .add_filter("vcd", &["vcd"])
+            .set_file_name(path.file_name().unwrap_or_default().to_string_lossy())
+            .save_file();
+        if let Some(path_buf) = files {
+            if let Err(e) = s.save_vcd(&path_buf) {
+                error!("couldn't export vcd: {}", e);
+            }
+        }
+    }
+}
+pub fn file_export_stimulus_fn(gui: &mut Gui) {
+    gui.finish_simulator_thread(Command::Pause);
+    if let Some(s) = gui.simulator.as_ref() {
+        let mut path = gui.path.clone();
+        path.set_extension("stimulus.csv");
+        let files = FileDialog::new()
+            .add_filter("csv", &["csv"])
+            .set_file_name(path.file_name().unwrap_or_default().to_string_lossy())
+            .save_file();
+        if let Some(path_buf) = files {
+            if let Err(e) = s.save_stimulus(&path_buf) {
+                error!("couldn't export stimulus: {}", e);
+            }
+        }
+    }
+}
+pub fn file_export_replay_fn(gui: &mut Gui) {
+    gui.finish_simulator_thread(Command::Pause);
+    if let Some(s) = gui.simulator.as_ref() {
+        let mut path = gui.path.clone();
+        path.set_extension("replay.json");
+        let files = FileDialog::new()
+            .add_filter("json", &["json"])
+            .set_file_name(path.file_name().unwrap_or_default().to_string_lossy())
+            .save_file();
+        if let Some(path_buf) = files {
+            s.replay_model().save_file(&path_buf);
+        }
+    }
+}
+pub fn file_save_snapshot_fn(gui: &mut Gui, history: bool) {
+    gui.finish_simulator_thread(Command::Pause);
+    if let Some(s) = gui.simulator.as_ref() {
+        let mut path = gui.path.clone();
+        path.set_extension("snapshot.json");
+        let files = FileDialog::new()
+            .add_filter("json", &["json"])
+            .set_file_name(path.file_name().unwrap_or_default().to_string_lossy())
+            .save_file();
+        if let Some(path_buf) = files {
+            if let Err(e) = s.save_snapshot(&path_buf, history) {
+                error!("couldn't save snapshot: {}", e);
+            }
+        }
+    }
+}
+pub fn file_restore_snapshot_fn(gui: &mut Gui) {
+    gui.finish_simulator_thread(Command::Pause);
+    if let Some(s) = gui.simulator.as_mut() {
+        let files = FileDialog::new().add_filter("json", &["json"]).pick_file();
+        if let Some(path_buf) = files {
+            if let Err(e) = s.load_snapshot(&path_buf) {
+                error!("couldn't restore snapshot: {}", e);
+            }
+        }
+    }
+}
 pub fn file_editor_toggle_fn(gui: &mut Gui) {
+    gui.finish_simulator_thread(Command::Pause);
     // Auto-save
     file_save_fn(gui);
     match gui.editor_use {
@@ -310,25 +396,34 @@ pub fn file_editor_toggle_fn(gui: &mut Gui) {
             gui.editor_use = false;
             if let Some(e) = gui.editor.as_mut() {
                 let components = e.components.clone();
+                gui.model = components.clone();
                 gui.contexts = create_contexts(&components);
                 let simulator = Simulator::new(ComponentStore { store: components });
                 match simulator {
                     Err(e) => {
                         gui.editor_use = true;
-                        println!("error: {}", e);
+                        gui.elaboration_error = Some(e);
+                    }
+                    Ok(s) => {
+                        gui.simulator = Some(s);
+                        gui.elaboration_error = None;
                     }
-                    Ok(s) => gui.simulator = Some(s),
                 }
             }
         }
         false => {
             let editor_existed: bool = gui.editor.as_mut().is_some();
 
-            let simulator = gui.simulator.take().unwrap();
-            let components = simulator.ordered_components;
+            // the editor shows the model, not the flattened components of the simulator
+            gui.simulator.take();
 
             if !editor_existed {
-                gui.editor = Some(Editor::gui(components, &gui.path, &gui.library));
+                gui.editor = Some(Editor::gui(
+                    gui.model.clone(),
+                    &gui.path,
+                    &gui.library,
+                    gui.source.clone(),
+                ));
             }
 
             gui.editor_use = true;
@@ -386,7 +481,7 @@ pub fn control_play_toggle_fn(gui: &mut Gui) {
     }
 }
 pub fn control_play_fn(gui: &mut Gui) {
-    if !gui.editor_use {
+    if !gui.editor_use && gui.simulator_thread.is_none() {
         gui.pause = false;
         let _ = gui.simulator.as_mut().unwrap().set_running();
         //gui.simulator.as_mut().unwrap().run();
@@ -401,25 +496,41 @@ pub fn control_play_fn(gui: &mut Gui) {
 pub fn control_pause_fn(gui: &mut Gui) {
     if !gui.editor_use {
         gui.pause = true;
+        gui.finish_simulator_thread(Command::Pause);
         let _ = gui.simulator.as_mut().unwrap().stop();
     }
 }
 pub fn control_reset_fn(gui: &mut Gui) {
     if !gui.editor_use {
-        gui.simulator.as_mut().unwrap().reset();
+        if !gui.finish_simulator_thread(Command::Reset) {
+            gui.simulator.as_mut().unwrap().reset();
+        }
         gui.pause = true;
     }
 }
 pub fn control_step_forward_fn(gui: &mut Gui) {
-    if !gui.editor_use {
+    if !gui.editor_use && !gui.finish_simulator_thread(Command::Step) {
         gui.simulator.as_mut().unwrap().clock();
     }
 }
 pub fn control_step_back_fn(gui: &mut Gui) {
     if !gui.editor_use {
+        gui.finish_simulator_thread(Command::Pause);
         gui.simulator.as_mut().unwrap().un_clock();
     }
 }
+pub fn control_step_to_fn(gui: &mut Gui) {
+    if !gui.editor_use {
+        let s = gui.simulator.as_mut().unwrap();
+        let target_cycle = s.cycle + gui.step_amount;
+        match gui.simulator_thread.as_ref() {
+            Some(thread) => thread.send(Command::StepTo(target_cycle)),
+            None => {
+                let _ = s.set_step_to(target_cycle);
+            }
+        }
+    }
+}
 pub fn editor_wire_mode_fn(gui: &mut Gui) {
     if gui.editor_use {
         let editor = gui.editor.as_mut().unwrap();
@@ -154,8 +154,10 @@ pub fn add_comp_to_editor(e: &mut Editor) {
     );
     e.components.push(*instance);
     let path = PathBuf::from("autosave.json");
-    ComponentStore {
-        store: e.components.clone(),
-    }
-    .save_file(&path);
+    e.source.save_file(
+        &ComponentStore {
+            store: e.components.clone(),
+        },
+        &path,
+    );
 }
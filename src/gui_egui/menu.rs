@@ -14,8 +14,18 @@ impl Menu {
             shared_buttons_edit(gui, ui);
 
             let mut scale = gui.scale;
-            shared_buttons_view(gui, ui, &mut scale, |_| {});
+            let mut waveform_visible = gui.waveform.visible;
+            let mut breakpoints_visible = gui.breakpoint_panel.visible;
+            let mut profiler_visible = gui.profiler_panel.visible;
+            shared_buttons_view(gui, ui, &mut scale, |ui| {
+                ui.checkbox(&mut waveform_visible, "Waveform");
+                ui.checkbox(&mut breakpoints_visible, "Breakpoints");
+                ui.checkbox(&mut profiler_visible, "Profiler");
+            });
             gui.scale = scale;
+            gui.waveform.visible = waveform_visible;
+            gui.breakpoint_panel.visible = breakpoints_visible;
+            gui.profiler_panel.visible = profiler_visible;
 
             shared_buttons_help(gui, ui);
         });
@@ -43,10 +53,7 @@ impl Menu {
                     .range(0..=u32::MAX),
             );
             if ui.button("⟳").clicked() {
-                // TODO dont have simulator here add keymap
-                if let Some(s) = gui.simulator.as_mut() {
-                    let _ = s.set_step_to(s.cycle + gui.step_amount);
-                }
+                keymap::control_step_to_fn(gui);
             }
 
             ui.separator();
@@ -160,6 +167,28 @@ fn shared_buttons_file(gui: &mut Gui, ui: &mut Ui) {
         if btn(ui, "Save As", gui.shortcuts.file_save_as).clicked() {
             keymap::file_save_as_fn(gui);
         }
+        ui.menu_button("Export", |ui| {
+            if ui.button("VCD").clicked() {
+                keymap::file_export_vcd_fn(gui);
+            }
+            if ui.button("Stimulus").clicked() {
+                keymap::file_export_stimulus_fn(gui);
+            }
+            if ui.button("Replay model").clicked() {
+                keymap::file_export_replay_fn(gui);
+            }
+        });
+        ui.menu_button("Snapshot", |ui| {
+            if ui.button("Save").clicked() {
+                keymap::file_save_snapshot_fn(gui, false);
+            }
+            if ui.button("Save with history").clicked() {
+                keymap::file_save_snapshot_fn(gui, true);
+            }
+            if ui.button("Restore").clicked() {
+                keymap::file_restore_snapshot_fn(gui);
+            }
+        });
         ui.separator();
         if btn(ui, "Editor", gui.shortcuts.file_editor_toggle).clicked() {
             keymap::file_editor_toggle_fn(gui);
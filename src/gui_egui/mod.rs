@@ -1,3 +1,4 @@
+pub mod breakpoints;
 pub mod component_ui;
 pub mod editor;
 mod editor_wire_mode;
@@ -6,6 +7,8 @@ pub mod helper;
 mod keymap;
 mod library;
 mod menu;
+pub mod profiler;
+pub mod waveform;
 
 #[cfg(feature = "components")]
 pub mod components;
@@ -0,0 +1,66 @@
+use crate::common::Simulator;
+use egui::{Context, ProgressBar};
+
+// Profiler window, time spent clocking each component, most time spent first
+#[derive(Default)]
+pub struct ProfilerPanel {
+    pub visible: bool,
+}
+
+impl ProfilerPanel {
+    /// profiling can only be enabled or cleared when `editable`, i.e., not while free-running
+    pub fn render(&mut self, ctx: &Context, simulator: &mut Simulator, editable: bool) {
+        let mut visible = self.visible;
+        egui::Window::new("Profiler")
+            .open(&mut visible)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(editable, |ui| {
+                    ui.horizontal(|ui| {
+                        let mut profiling = simulator.is_profiling();
+                        if ui.checkbox(&mut profiling, "Profile").changed() {
+                            simulator.set_profiling(profiling);
+                        }
+                        if ui.button("Clear").clicked() {
+                            simulator.clear_profile();
+                        }
+                    });
+                });
+                let profile = simulator.profile();
+                if profile.components.is_empty() {
+                    ui.label("No components clocked while profiling");
+                    return;
+                }
+                let total = profile.total().as_secs_f32();
+                ui.label(format!("Total {:.3?}", profile.total()));
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("profile").striped(true).show(ui, |ui| {
+                        ui.strong("Component");
+                        ui.strong("Calls");
+                        ui.strong("Time");
+                        ui.strong("Mean");
+                        ui.strong("Share");
+                        ui.end_row();
+                        for c in &profile.components {
+                            let share = if total > 0.0 {
+                                c.time.as_secs_f32() / total
+                            } else {
+                                0.0
+                            };
+                            ui.label(&c.id);
+                            ui.label(c.calls.to_string());
+                            ui.label(format!("{:.3?}", c.time));
+                            ui.label(format!("{:.3?}", c.mean()));
+                            ui.add(
+                                ProgressBar::new(share)
+                                    .desired_width(100.0)
+                                    .text(format!("{:.1}%", 100.0 * share)),
+                            );
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        self.visible = visible;
+    }
+}
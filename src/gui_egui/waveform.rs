@@ -0,0 +1,313 @@
+use crate::common::{Input, Signal, SignalFmt, SignalValue, Simulator};
+use crate::gui_egui::helper::signal_menu;
+use egui::{Align2, Color32, Context, FontId, Pos2, Rect, Sense, Stroke, TopBottomPanel, Ui, Vec2};
+
+const ROW_HEIGHT: f32 = 24.0;
+const NAME_WIDTH: f32 = 160.0;
+const VALUE_WIDTH: f32 = 100.0;
+const MIN_CYCLE_WIDTH: f32 = 2.0;
+const MAX_CYCLE_WIDTH: f32 = 200.0;
+
+// Waveform panel, plots selected outputs over the cycles kept in the simulator history
+pub struct Waveform {
+    pub visible: bool,
+    pub signals: Vec<Input>,
+    // width of a cycle in pixels
+    pub cycle_width: f32,
+    // selected cycle, values are shown for the cursor (or current cycle if None)
+    pub cursor: Option<usize>,
+    // first cycle in view, fractional while panning
+    first_cycle: f32,
+    // keep current cycle in view
+    follow: bool,
+}
+
+impl Default for Waveform {
+    fn default() -> Self {
+        Waveform {
+            visible: false,
+            signals: vec![],
+            cycle_width: 20.0,
+            cursor: None,
+            first_cycle: 0.0,
+            follow: true,
+        }
+    }
+}
+
+impl Waveform {
+    pub fn render(&mut self, ctx: &Context, simulator: &Simulator) {
+        if !self.visible {
+            return;
+        }
+        TopBottomPanel::bottom("waveform")
+            .resizable(true)
+            .default_height(200.0)
+            .show(ctx, |ui| {
+                self.render_top(ui, simulator);
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.render_waves(ui, simulator);
+                });
+            });
+    }
+
+    fn render_top(&mut self, ui: &mut Ui, simulator: &Simulator) {
+        ui.horizontal(|ui| {
+            ui.menu_button("Signals", |ui| {
+                signal_menu(ui, simulator, |ui, input| {
+                    let mut selected = self.signals.contains(&input);
+                    if ui.checkbox(&mut selected, &input.field).changed() {
+                        if selected {
+                            self.signals.push(input);
+                        } else {
+                            self.signals.retain(|s| *s != input);
+                        }
+                    }
+                });
+            });
+            if ui.button("Clear").clicked() {
+                self.signals.clear();
+            }
+            ui.separator();
+            if ui.button("-").on_hover_text("Zoom out").clicked() {
+                self.zoom(0.5);
+            }
+            if ui.button("+").on_hover_text("Zoom in").clicked() {
+                self.zoom(2.0);
+            }
+            ui.toggle_value(&mut self.follow, "Follow")
+                .on_hover_text("Keep the current cycle in view");
+            ui.separator();
+            match self.cursor {
+                Some(cursor) => {
+                    ui.label(format!("Cursor: {}", cursor));
+                    if ui.button("⊗").on_hover_text("Clear cursor").clicked() {
+                        self.cursor = None;
+                    }
+                }
+                None => {
+                    ui.label(format!("Cycle: {}", simulator.cycle));
+                }
+            }
+        });
+    }
+
+    fn zoom(&mut self, factor: f32) {
+        self.cycle_width = (self.cycle_width * factor).clamp(MIN_CYCLE_WIDTH, MAX_CYCLE_WIDTH);
+    }
+
+    fn render_waves(&mut self, ui: &mut Ui, simulator: &Simulator) {
+        let size = Vec2::new(
+            ui.available_width(),
+            ROW_HEIGHT * (self.signals.len() + 1) as f32,
+        );
+        let (response, painter) = ui.allocate_painter(size, Sense::click_and_drag());
+        let rect = response.rect;
+        let wave_left = rect.left() + NAME_WIDTH + VALUE_WIDTH;
+        let nr_cycles = ((rect.right() - wave_left) / self.cycle_width).max(1.0);
+
+        // panning and zooming
+        if response.dragged() {
+            self.follow = false;
+            self.first_cycle -= response.drag_delta().x / self.cycle_width;
+        }
+        if response.hovered() {
+            ui.ctx().input(|i| {
+                let delta = i.raw_scroll_delta;
+                if i.modifiers.ctrl {
+                    if delta.y > 0.0 {
+                        self.zoom(1.25);
+                    } else if delta.y < 0.0 {
+                        self.zoom(0.8);
+                    }
+                } else if delta.x != 0.0 {
+                    self.follow = false;
+                    self.first_cycle -= delta.x / self.cycle_width;
+                }
+            });
+        }
+        if self.follow {
+            self.first_cycle = simulator.cycle as f32 + 1.0 - nr_cycles;
+        }
+        // only cycles kept in history can be shown
+        self.first_cycle = self.first_cycle.clamp(
+            simulator.history.first_cycle() as f32,
+            simulator.cycle as f32,
+        );
+        let first = self.first_cycle as usize;
+        let last = simulator
+            .cycle
+            .min(first + nr_cycles.ceil() as usize)
+            .max(first);
+        let cycle_x = |cycle: usize| wave_left + (cycle - first) as f32 * self.cycle_width;
+
+        // set cursor
+        if response.clicked() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                if pos.x >= wave_left {
+                    let cycle = first + ((pos.x - wave_left) / self.cycle_width) as usize;
+                    self.cursor = Some(cycle.min(simulator.cycle));
+                }
+            }
+        }
+
+        let text_color = ui.visuals().text_color();
+        let grid_color = ui.visuals().weak_text_color();
+        let font = FontId::monospace(12.0);
+
+        // header with cycle numbers, spaced to not overlap
+        let label_every = (50.0 / self.cycle_width).ceil().max(1.0) as usize;
+        for cycle in first..=last {
+            if cycle % label_every == 0 {
+                let x = cycle_x(cycle);
+                painter.line_segment(
+                    [Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())],
+                    Stroke::new(0.5, grid_color),
+                );
+                painter.text(
+                    Pos2::new(x + 2.0, rect.top() + ROW_HEIGHT / 2.0),
+                    Align2::LEFT_CENTER,
+                    cycle.to_string(),
+                    font.clone(),
+                    grid_color,
+                );
+            }
+        }
+
+        // cursor, or current cycle
+        let value_cycle = self.cursor.unwrap_or(simulator.cycle);
+        if (first..=last).contains(&value_cycle) {
+            let x = cycle_x(value_cycle);
+            painter.rect_filled(
+                Rect::from_min_max(
+                    Pos2::new(x, rect.top()),
+                    Pos2::new(x + self.cycle_width, rect.bottom()),
+                ),
+                0.0,
+                Color32::from_rgba_unmultiplied(0, 0, 255, 24),
+            );
+        }
+
+        for (row, input) in self.signals.iter().enumerate() {
+            let top = rect.top() + ROW_HEIGHT * (row + 1) as f32;
+            let y_high = top + 4.0;
+            let y_low = top + ROW_HEIGHT - 4.0;
+            let y_mid = top + ROW_HEIGHT / 2.0;
+
+            painter.text(
+                Pos2::new(rect.left() + 4.0, y_mid),
+                Align2::LEFT_CENTER,
+                format!("{}.{}", input.id, input.field),
+                font.clone(),
+                text_color,
+            );
+
+            let index = match simulator.get_input_index(input) {
+                Some(index) => index,
+                None => {
+                    painter.text(
+                        Pos2::new(rect.left() + NAME_WIDTH, y_mid),
+                        Align2::LEFT_CENTER,
+                        "not found",
+                        font.clone(),
+                        Color32::RED,
+                    );
+                    continue;
+                }
+            };
+
+            if let Some(signal) = simulator.get_signal_at(value_cycle, index) {
+                painter.text(
+                    Pos2::new(rect.left() + NAME_WIDTH, y_mid),
+                    Align2::LEFT_CENTER,
+                    signal.to_string(),
+                    font.clone(),
+                    text_color,
+                );
+            }
+
+            // segments of equal value, (start cycle, end cycle, signal)
+            let mut segments: Vec<(usize, usize, Signal)> = vec![];
+            for cycle in first..=last {
+                let Some(signal) = simulator.get_signal_at(cycle, index) else {
+                    break;
+                };
+                match segments.last_mut() {
+                    Some((_, end, s)) if s.get_value() == signal.get_value() => *end = cycle,
+                    _ => segments.push((cycle, cycle, signal)),
+                }
+            }
+
+            let wave = Stroke::new(1.5, Color32::DARK_GREEN);
+            let mut prev_level: Option<f32> = None;
+            for (start, end, signal) in segments {
+                let x0 = cycle_x(start).max(wave_left);
+                let x1 = cycle_x(end) + self.cycle_width;
+                match (signal.get_value(), signal.get_fmt()) {
+                    (SignalValue::Data(data), SignalFmt::Bool) => {
+                        // single bit, drawn as a level
+                        let level = if data != 0 { y_high } else { y_low };
+                        if let Some(prev) = prev_level {
+                            painter.line_segment([Pos2::new(x0, prev), Pos2::new(x0, level)], wave);
+                        }
+                        painter.line_segment([Pos2::new(x0, level), Pos2::new(x1, level)], wave);
+                        prev_level = Some(level);
+                    }
+                    (SignalValue::Data(_) | SignalValue::Wide(_), _) => {
+                        // bus, drawn as a box with the value when it fits
+                        let slant = (self.cycle_width / 4.0).min(3.0);
+                        painter.line_segment(
+                            [Pos2::new(x0, y_mid), Pos2::new(x0 + slant, y_high)],
+                            wave,
+                        );
+                        painter.line_segment(
+                            [Pos2::new(x0, y_mid), Pos2::new(x0 + slant, y_low)],
+                            wave,
+                        );
+                        painter.line_segment(
+                            [Pos2::new(x0 + slant, y_high), Pos2::new(x1, y_high)],
+                            wave,
+                        );
+                        painter.line_segment(
+                            [Pos2::new(x0 + slant, y_low), Pos2::new(x1, y_low)],
+                            wave,
+                        );
+                        let text = signal.to_string();
+                        // monospace glyphs are roughly 7 pixels wide at size 12
+                        if (text.len() as f32) * 7.0 + 2.0 * slant < x1 - x0 {
+                            painter.text(
+                                Pos2::new((x0 + x1) / 2.0, y_mid),
+                                Align2::CENTER_CENTER,
+                                text,
+                                font.clone(),
+                                text_color,
+                            );
+                        }
+                        prev_level = None;
+                    }
+                    (value, _) => {
+                        // Unknown, DontCare or Uninitialized
+                        painter.rect_filled(
+                            Rect::from_min_max(Pos2::new(x0, y_high), Pos2::new(x1, y_low)),
+                            0.0,
+                            Color32::from_rgba_unmultiplied(255, 0, 0, 48),
+                        );
+                        let text = match value {
+                            SignalValue::DontCare => "z",
+                            _ => "x",
+                        };
+                        painter.text(
+                            Pos2::new((x0 + x1) / 2.0, y_mid),
+                            Align2::CENTER_CENTER,
+                            text,
+                            font.clone(),
+                            Color32::RED,
+                        );
+                        prev_level = None;
+                    }
+                }
+            }
+        }
+    }
+}
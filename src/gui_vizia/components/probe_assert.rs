@@ -13,7 +13,7 @@ impl ViziaComponent for ProbeAssert {
         V::new(cx, self, |cx| {
             trace!("---- Create ProbeAssert View");
 
-            let values = self.values.clone();
+            let values = self.values().to_vec();
 
             let input = self.input.clone();
             VStack::new(cx, |cx| {
@@ -12,7 +12,7 @@ impl ViziaComponent for ProbeStim {
     fn view<'a>(&self, cx: &'a mut Context) -> Handle<'a, V> {
         V::new(cx, self, |cx| {
             trace!("---- Create ProbeStim View");
-            let values = self.values.clone();
+            let values = self.values().to_vec();
             VStack::new(cx, |cx| {
                 Binding::new(
                     cx,
@@ -0,0 +1,362 @@
+// Headless (display-less) batch execution of models, e.g., for CI
+//
+// The command line options of a headless run (`RunArgs`) are shared by the binaries, flattened
+// into their arguments, which then either open the model (`RunArgs::load_model`) in their GUI or
+// run it (`run_model`).
+use crate::common::{ComponentStore, Condition, Id, Input, RunningState, Simulator};
+use crate::component_store::{load_parameters, parse_parameter, Parameters};
+use crate::fault::Campaign;
+use crate::golden::{parse_signal, Golden};
+use log::*;
+use std::{fmt, path::PathBuf, process::ExitCode};
+
+/// Options of a headless run, `#[command(flatten)]` into the arguments of a binary
+#[derive(clap::Args, Debug, Clone)]
+pub struct RunArgs {
+    /// Override a model parameter, as name=value (the value is json, or else a string)
+    #[arg(short, long = "param", value_parser = parse_parameter)]
+    pub param: Vec<(String, serde_json::Value)>,
+
+    /// Load model parameter overrides from a json file, `--param` takes precedence
+    #[arg(long)]
+    pub params: Option<PathBuf>,
+
+    /// Run the model without a GUI, e.g., for CI
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Maximum number of cycles to run in headless mode
+    #[arg(short, long, default_value_t = 1000)]
+    pub cycles: usize,
+
+    /// Stream the simulator state to a VCD file in headless mode
+    #[arg(long)]
+    pub vcd: Option<PathBuf>,
+
+    /// Maximum number of cycles kept in history in headless mode (unbounded if not set)
+    #[arg(long)]
+    pub history_cap: Option<usize>,
+
+    /// Only clock components whose inputs changed in headless mode
+    #[arg(long)]
+    pub event_driven: bool,
+
+    /// Resume from a snapshot in headless mode
+    #[arg(long)]
+    pub restore: Option<PathBuf>,
+
+    /// Save a snapshot after the headless run
+    #[arg(long)]
+    pub snapshot: Option<PathBuf>,
+
+    /// Include the history in the snapshot, allowing reverse simulation past the resumed cycle
+    #[arg(long)]
+    pub snapshot_history: bool,
+
+    /// Print the time spent clocking each component after the headless run
+    #[arg(long)]
+    pub profile: bool,
+
+    /// Print the output bits toggled and component bins hit (e.g., mux inputs selected)
+    /// after the headless run
+    #[arg(long)]
+    pub coverage: bool,
+
+    /// Save the coverage of the headless run as json
+    #[arg(long)]
+    pub coverage_json: Option<PathBuf>,
+
+    /// Record the outputs of each cycle of the headless run to a golden file
+    #[arg(long)]
+    pub record_golden: Option<PathBuf>,
+
+    /// Check the headless run against a golden file, stopping at the first divergence
+    #[arg(long, conflicts_with = "record_golden")]
+    pub golden: Option<PathBuf>,
+
+    /// Output recorded to the golden file, as id.field (repeatable, all outputs if not given)
+    #[arg(long, value_parser = parse_signal)]
+    pub golden_signal: Vec<Input>,
+
+    /// Run a fault injection campaign (json) without a GUI, reporting the faults detected
+    #[arg(long)]
+    pub faults: Option<PathBuf>,
+
+    /// Save the report of the fault injection campaign as json
+    #[arg(long, requires = "faults")]
+    pub faults_json: Option<PathBuf>,
+
+    /// Replace the ProbeEdit components by ProbeStim components replaying a stimulus file
+    #[cfg(feature = "components")]
+    #[arg(long)]
+    pub stimulus: Option<PathBuf>,
+}
+
+impl RunArgs {
+    /// load the model with the parameters (`--params`, `--param`) and stimulus given
+    pub fn load_model(&self, path: &PathBuf) -> Result<ComponentStore, String> {
+        let mut parameters = match &self.params {
+            Some(params) => load_parameters(params).map_err(|err| err.to_string())?,
+            None => Parameters::new(),
+        };
+        parameters.extend(self.param.iter().cloned());
+        let cs = ComponentStore::load_file_with_parameters(path, &parameters)
+            .map_err(|err| err.to_string())?;
+        #[cfg(feature = "components")]
+        let cs = match &self.stimulus {
+            Some(stimulus) => cs.with_stimulus(stimulus),
+            None => cs,
+        };
+        Ok(cs)
+    }
+
+    /// the model is run headless, also when built with a GUI
+    pub fn is_headless(&self) -> bool {
+        self.headless || self.faults.is_some()
+    }
+}
+
+/// run the model at `path` as given by `args`: the fault campaign if given, else `run`
+pub fn run_model(path: &PathBuf, args: &RunArgs) -> ExitCode {
+    let cs = match args.load_model(path) {
+        Ok(cs) => cs,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Some(campaign) = &args.faults {
+        // each run loads a new model, loaded successfully above
+        let result =
+            Campaign::load(campaign).and_then(|c| c.run(|| args.load_model(path).unwrap()));
+        return match result {
+            Ok(report) => {
+                println!("{}", report);
+                if let Some(json) = &args.faults_json {
+                    let json_report = serde_json::to_string_pretty(&report).unwrap();
+                    if let Err(err) = std::fs::write(json, json_report) {
+                        eprintln!("{:?}: {}", json, err);
+                        return ExitCode::FAILURE;
+                    }
+                }
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                eprintln!("{}", err);
+                ExitCode::FAILURE
+            }
+        };
+    }
+    match Simulator::new(cs) {
+        Ok(mut simulator) => run(&mut simulator, args),
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// run the simulator for at most `--cycles` cycles, or as given by `args`,
+/// returns the exit code (see `HeadlessReport::exit_code`)
+pub fn run(simulator: &mut Simulator, args: &RunArgs) -> ExitCode {
+    if let Some(snapshot) = &args.restore {
+        if let Err(err) = simulator.load_snapshot(snapshot) {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    }
+    simulator.set_history_cap(args.history_cap);
+    simulator.set_event_driven(args.event_driven);
+    simulator.set_profiling(args.profile);
+    simulator.set_coverage(args.coverage || args.coverage_json.is_some());
+    if let Some(vcd) = &args.vcd {
+        if let Err(err) = simulator.vcd_start(vcd) {
+            eprintln!("{:?}: {}", vcd, err);
+            return ExitCode::FAILURE;
+        }
+    }
+    if let Some(golden) = &args.golden {
+        let result = Golden::load(golden).and_then(|golden| golden.check(simulator));
+        if let Err(err) = simulator.vcd_stop() {
+            eprintln!("Error while writing VCD file: {}", err);
+            return ExitCode::FAILURE;
+        }
+        return match result {
+            Ok(()) => {
+                println!("cycle: {}, matches {:?}", simulator.cycle, golden);
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                eprintln!("{}", err);
+                ExitCode::FAILURE
+            }
+        };
+    }
+    let report = match &args.record_golden {
+        Some(golden) => {
+            let signals = match args.golden_signal.is_empty() {
+                true => simulator.outputs(),
+                false => args.golden_signal.clone(),
+            };
+            let result = Golden::record(simulator, &signals, args.cycles)
+                .and_then(|recorded| recorded.save(golden));
+            if let Err(err) = result {
+                eprintln!("{}", err);
+                return ExitCode::FAILURE;
+            }
+            simulator.headless_report()
+        }
+        None => simulator.run_headless(args.cycles),
+    };
+    if let Err(err) = simulator.vcd_stop() {
+        eprintln!("Error while writing VCD file: {}", err);
+        return ExitCode::FAILURE;
+    }
+    println!("{}", report);
+    if args.profile {
+        println!("{}", simulator.profile());
+    }
+    if args.coverage {
+        println!("{}", simulator.coverage());
+    }
+    if let Some(coverage) = &args.coverage_json {
+        let result = serde_json::to_string_pretty(&simulator.coverage())
+            .map_err(|err| err.to_string())
+            .and_then(|json| std::fs::write(coverage, json).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            eprintln!("{:?}: {}", coverage, err);
+            return ExitCode::FAILURE;
+        }
+    }
+    if let Some(snapshot) = &args.snapshot {
+        if let Err(err) = simulator.save_snapshot(snapshot, args.snapshot_history) {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    }
+    report.exit_code()
+}
+
+/// Outcome of a headless run
+#[derive(Debug, Clone)]
+pub struct HeadlessReport {
+    pub cycle: usize,
+    pub running_state: RunningState,
+    pub component_condition: Vec<(Id, Condition)>,
+}
+
+impl HeadlessReport {
+    /// most severe condition reported in the last cycle, if any
+    pub fn max_condition(&self) -> Option<&Condition> {
+        self.component_condition.iter().map(|(_, c)| c).max()
+    }
+
+    /// exit status for the run
+    /// 0: ran all cycles, or halted by a `Condition::Halt`
+    /// 1: halted by a `Condition::Assert`
+    /// 2: stopped by a `Condition::Error`
+    pub fn exit_code(&self) -> ExitCode {
+        match self.max_condition() {
+            Some(Condition::Error(_)) => ExitCode::from(2),
+            Some(Condition::Assert(_)) => ExitCode::from(1),
+            _ if self.running_state == RunningState::Err => ExitCode::from(2),
+            _ => ExitCode::SUCCESS,
+        }
+    }
+}
+
+impl fmt::Display for HeadlessReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "cycle: {}", self.cycle)?;
+        write!(f, "state: {:?}", self.running_state)?;
+        for (id, condition) in &self.component_condition {
+            write!(f, "\n  {}: {:?}", id, condition)?;
+        }
+        Ok(())
+    }
+}
+
+impl Simulator {
+    /// clock the simulator for at most `cycles` cycles,
+    /// stops early on `Condition::Halt`, `Condition::Assert` or `Condition::Error`
+    pub fn run_headless(&mut self, cycles: usize) -> HeadlessReport {
+        for _ in 0..cycles {
+            self.clock();
+            match self.running_state {
+                RunningState::Halt | RunningState::Err => break,
+                _ => {}
+            }
+        }
+        trace!("headless run stopped at cycle {}", self.cycle);
+        self.headless_report()
+    }
+
+    /// report of the current cycle
+    pub fn headless_report(&self) -> HeadlessReport {
+        HeadlessReport {
+            cycle: self.cycle,
+            running_state: self.running_state.clone(),
+            component_condition: self.component_condition.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{ComponentStore, Input};
+    use crate::components::*;
+
+    #[test]
+    fn test_run_all_cycles() {
+        let cs = ComponentStore {
+            store: vec![ProbeStim::rc_new("stim", (0.0, 0.0), vec![0, 1, 2, 3, 4])],
+        };
+        let mut simulator = Simulator::new(cs).unwrap();
+
+        let report = simulator.run_headless(3);
+        assert_eq!(report.cycle, 4);
+        assert_eq!(report.running_state, RunningState::Stopped);
+        assert_eq!(report.exit_code(), ExitCode::SUCCESS);
+    }
+
+    #[test]
+    fn test_warning_keeps_running() {
+        let cs = ComponentStore {
+            store: vec![ProbeStim::rc_new("stim", (0.0, 0.0), vec![0, 1])],
+        };
+        let mut simulator = Simulator::new(cs).unwrap();
+
+        // stimuli run out at cycle 2, `ProbeStim` then reports a warning
+        let report = simulator.run_headless(3);
+        assert_eq!(report.cycle, 4);
+        assert!(matches!(
+            report.max_condition(),
+            Some(Condition::Warning(_))
+        ));
+        assert_eq!(report.exit_code(), ExitCode::SUCCESS);
+    }
+
+    #[test]
+    fn test_stop_on_error() {
+        let cs = ComponentStore {
+            store: vec![
+                Constant::rc_new("select", (0.0, 0.0), 2),
+                Constant::rc_new("c", (0.0, 0.0), 0),
+                Mux::rc_new(
+                    "mux",
+                    (0.0, 0.0),
+                    Input::new("select", CONSTANT_OUT_ID),
+                    vec![Input::new("c", CONSTANT_OUT_ID)],
+                ),
+            ],
+        };
+        let mut simulator = Simulator::new(cs).unwrap();
+
+        // select out of range is reported as an error already at reset
+        let report = simulator.run_headless(10);
+        assert_eq!(report.cycle, 1);
+        assert_eq!(report.running_state, RunningState::Err);
+        assert_eq!(report.exit_code(), ExitCode::from(2));
+    }
+}
@@ -0,0 +1,384 @@
+// Simulation history, used for reverse simulation (`un_clock`) and inspection of past cycles
+//
+// The state is stored as periodic full checkpoints, with the changed signals (deltas)
+// for the cycles in between. The history can be capped, in which case the oldest cycles
+// are dropped.
+use crate::common::{Condition, Id, RunningState, Signal};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::Arc,
+};
+
+pub const HISTORY_CHECKPOINT_INTERVAL: usize = 64;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+enum Snapshot {
+    // complete sim_state
+    Full(Vec<Signal>),
+    // (index, signal) changed since previous cycle, sorted by index
+    Delta(Vec<(usize, Signal)>),
+}
+
+/// Simulator information kept per cycle, besides the state
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoryEntry {
+    // shared between cycles as long as the set of active components is unchanged
+    pub active: Arc<HashSet<Id>>,
+    pub component_condition: Vec<(Id, Condition)>,
+    pub running_state: RunningState,
+    // ordered components not clocked in the following cycle (event-driven evaluation)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct History {
+    checkpoint_interval: usize,
+    cap: Option<usize>,
+    entries: VecDeque<(Snapshot, HistoryEntry)>,
+    // cycle of the oldest entry kept
+    first: usize,
+    // state of the newest entry
+    tail: Vec<Signal>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History::new(HISTORY_CHECKPOINT_INTERVAL, None)
+    }
+}
+
+impl History {
+    /// a full checkpoint is stored every `checkpoint_interval` cycles,
+    /// at most `cap` cycles are kept (unbounded if None)
+    pub fn new(checkpoint_interval: usize, cap: Option<usize>) -> Self {
+        History {
+            checkpoint_interval: checkpoint_interval.max(1),
+            cap,
+            entries: VecDeque::new(),
+            first: 0,
+            tail: vec![],
+        }
+    }
+
+    /// set the maximum number of cycles kept, oldest cycles are dropped
+    pub fn set_cap(&mut self, cap: Option<usize>) {
+        self.cap = cap;
+        self.trim();
+    }
+
+    pub fn cap(&self) -> Option<usize> {
+        self.cap
+    }
+
+    /// set the number of cycles between full checkpoints, applies to new cycles
+    pub fn set_checkpoint_interval(&mut self, checkpoint_interval: usize) {
+        self.checkpoint_interval = checkpoint_interval.max(1);
+    }
+
+    /// number of cycles kept
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// cycle of the oldest entry kept
+    pub fn first_cycle(&self) -> usize {
+        self.first
+    }
+
+    /// one past the cycle of the newest entry
+    pub fn end_cycle(&self) -> usize {
+        self.first + self.entries.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.first = 0;
+        self.tail = vec![];
+    }
+
+    /// clear the history, the next cycle pushed being `cycle`
+    pub fn restart(&mut self, cycle: usize) {
+        self.clear();
+        self.first = cycle;
+    }
+
+    /// append the cycles of `other` if it continues this history, otherwise
+    /// replace the cycles kept by those of `other` (cap and checkpoint interval are kept)
+    pub fn append(&mut self, mut other: History) {
+        if other.first != self.end_cycle() || self.is_empty() {
+            self.entries = other.entries;
+            self.first = other.first;
+            self.tail = other.tail;
+        } else if !other.is_empty() {
+            self.entries.append(&mut other.entries);
+            self.tail = other.tail;
+        }
+        self.trim();
+    }
+
+    /// push the state of the next cycle
+    pub fn push(
+        &mut self,
+        state: &[Signal],
+        active: &HashSet<Id>,
+        component_condition: Vec<(Id, Condition)>,
+        running_state: RunningState,
+    ) {
+        let snapshot = if self.entries.is_empty()
+            || self.end_cycle().is_multiple_of(self.checkpoint_interval)
+            || self.tail.len() != state.len()
+        {
+            Snapshot::Full(state.to_vec())
+        } else {
+            Snapshot::Delta(
+                state
+                    .iter()
+                    .zip(self.tail.iter())
+                    .enumerate()
+                    .filter(|(_, (new, old))| new != old)
+                    .map(|(index, (new, _))| (index, *new))
+                    .collect(),
+            )
+        };
+        let active = match self.entries.back() {
+            Some((_, prev)) if *prev.active == *active => prev.active.clone(),
+            _ => Arc::new(active.clone()),
+        };
+        self.entries.push_back((
+            snapshot,
+            HistoryEntry {
+                active,
+                component_condition,
+                running_state,
+                skipped: vec![],
+            },
+        ));
+        self.tail.clear();
+        self.tail.extend_from_slice(state);
+        self.trim();
+    }
+
+    /// set the components not clocked in the cycle following the newest entry
+    pub fn set_skipped(&mut self, skipped: Vec<usize>) {
+        if let Some((_, entry)) = self.entries.back_mut() {
+            entry.skipped = skipped;
+        }
+    }
+
+    /// pop the newest cycle, returning its state
+    pub fn pop(&mut self) -> Option<(Vec<Signal>, HistoryEntry)> {
+        let (_, entry) = self.entries.pop_back()?;
+        let tail = match self.entries.len() {
+            0 => vec![],
+            len => self.state(len - 1),
+        };
+        let state = std::mem::replace(&mut self.tail, tail);
+        Some((state, entry))
+    }
+
+    /// state at cycle, None if not kept
+    pub fn get_state(&self, cycle: usize) -> Option<Vec<Signal>> {
+        let index = cycle.checked_sub(self.first)?;
+        if index + 1 == self.entries.len() {
+            Some(self.tail.clone())
+        } else if index < self.entries.len() {
+            Some(self.state(index))
+        } else {
+            None
+        }
+    }
+
+    /// signal at cycle, None if not kept
+    pub fn get_signal(&self, cycle: usize, signal_index: usize) -> Option<Signal> {
+        let index = cycle.checked_sub(self.first)?;
+        if index >= self.entries.len() {
+            return None;
+        }
+        let checkpoint = self.checkpoint(index);
+        let mut signal = match &self.entries[checkpoint].0 {
+            Snapshot::Full(state) => *state.get(signal_index)?,
+            Snapshot::Delta(_) => unreachable!(),
+        };
+        for (snapshot, _) in self.entries.range(checkpoint + 1..=index) {
+            if let Snapshot::Delta(delta) = snapshot {
+                if let Ok(i) = delta.binary_search_by_key(&signal_index, |(i, _)| *i) {
+                    signal = delta[i].1;
+                }
+            }
+        }
+        Some(signal)
+    }
+
+    /// entry at cycle, None if not kept
+    pub fn get_entry(&self, cycle: usize) -> Option<&HistoryEntry> {
+        let index = cycle.checked_sub(self.first)?;
+        self.entries.get(index).map(|(_, entry)| entry)
+    }
+
+    /// call `f` with the (cycle, state) of each cycle kept, oldest first
+    pub fn try_for_each_state<E>(
+        &self,
+        mut f: impl FnMut(usize, &[Signal]) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let mut state = vec![];
+        for (index, (snapshot, _)) in self.entries.iter().enumerate() {
+            apply(&mut state, snapshot);
+            f(self.first + index, &state)?;
+        }
+        Ok(())
+    }
+
+    // index of the closest full checkpoint at or before index
+    fn checkpoint(&self, index: usize) -> usize {
+        (0..=index)
+            .rev()
+            .find(|i| matches!(self.entries[*i].0, Snapshot::Full(_)))
+            .expect("ICE: history without checkpoint")
+    }
+
+    // reconstruct state at index
+    fn state(&self, index: usize) -> Vec<Signal> {
+        let mut state = vec![];
+        for (snapshot, _) in self.entries.range(self.checkpoint(index)..=index) {
+            apply(&mut state, snapshot);
+        }
+        state
+    }
+
+    // drop oldest entries exceeding the cap
+    fn trim(&mut self) {
+        let Some(cap) = self.cap else {
+            return;
+        };
+        while self.entries.len() > cap {
+            let (snapshot, _) = self.entries.pop_front().unwrap();
+            self.first += 1;
+            // the new oldest entry must be a full checkpoint
+            if let (Snapshot::Full(mut state), Some((next, _))) =
+                (snapshot, self.entries.front_mut())
+            {
+                if let Snapshot::Delta(_) = next {
+                    apply(&mut state, next);
+                    *next = Snapshot::Full(state);
+                }
+            }
+        }
+        if self.entries.is_empty() {
+            self.tail.clear();
+        }
+    }
+}
+
+// apply snapshot on top of state
+fn apply(state: &mut Vec<Signal>, snapshot: &Snapshot) {
+    match snapshot {
+        Snapshot::Full(full) => {
+            state.clear();
+            state.extend_from_slice(full);
+        }
+        Snapshot::Delta(delta) => {
+            for (index, signal) in delta {
+                state[*index] = *signal;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn push(history: &mut History, state: &[u32]) {
+        let state: Vec<Signal> = state.iter().map(|v| (*v).into()).collect();
+        history.push(&state, &HashSet::new(), vec![], RunningState::Stopped);
+    }
+
+    fn state(state: &[u32]) -> Vec<Signal> {
+        state.iter().map(|v| (*v).into()).collect()
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut history = History::new(2, None);
+        for i in 0..5 {
+            push(&mut history, &[i, 0, i / 2]);
+        }
+        assert_eq!(history.len(), 5);
+        for i in 0..5 {
+            assert_eq!(history.get_state(i as usize), Some(state(&[i, 0, i / 2])));
+            assert_eq!(history.get_signal(i as usize, 2), Some((i / 2).into()));
+        }
+        assert_eq!(history.get_state(5), None);
+
+        for i in (0..5).rev() {
+            let (s, _) = history.pop().unwrap();
+            assert_eq!(s, state(&[i, 0, i / 2]));
+        }
+        assert!(history.pop().is_none());
+    }
+
+    #[test]
+    fn test_cap() {
+        let mut history = History::new(4, Some(3));
+        for i in 0..10 {
+            push(&mut history, &[i, i % 2]);
+        }
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.first_cycle(), 7);
+        assert_eq!(history.get_state(6), None);
+        for i in 7..10 {
+            assert_eq!(history.get_state(i as usize), Some(state(&[i, i % 2])));
+            assert_eq!(history.get_signal(i as usize, 1), Some((i % 2).into()));
+        }
+
+        let mut cycles = vec![];
+        history
+            .try_for_each_state(|cycle, s| {
+                let i = cycle as u32;
+                assert_eq!(s, state(&[i, i % 2]));
+                cycles.push(cycle);
+                Ok::<(), ()>(())
+            })
+            .unwrap();
+        assert_eq!(cycles, vec![7, 8, 9]);
+
+        for i in (7..10).rev() {
+            let (s, _) = history.pop().unwrap();
+            assert_eq!(s, state(&[i, i % 2]));
+        }
+        assert!(history.pop().is_none());
+    }
+
+    #[test]
+    fn test_append() {
+        let mut history = History::new(4, Some(6));
+        for i in 0..5 {
+            push(&mut history, &[i]);
+        }
+        // continued from cycle 5
+        let mut other = History::default();
+        other.restart(5);
+        for i in 5..8 {
+            push(&mut other, &[i]);
+        }
+        history.append(other);
+        assert_eq!((history.first_cycle(), history.end_cycle()), (2, 8));
+        for i in 2..8 {
+            assert_eq!(history.get_state(i as usize), Some(state(&[i])));
+        }
+        assert_eq!(history.pop().unwrap().0, state(&[7]));
+
+        // not continuing, replaced
+        let mut other = History::default();
+        push(&mut other, &[9]);
+        history.append(other);
+        assert_eq!((history.first_cycle(), history.end_cycle()), (0, 1));
+        assert_eq!(history.cap(), Some(6));
+    }
+}
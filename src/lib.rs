@@ -1,13 +1,37 @@
+pub mod breakpoint;
+pub mod clock_domain;
 pub mod common;
 pub mod component_store;
+pub mod coverage;
+pub mod elaboration;
+pub mod fault;
 pub mod fern;
+pub mod golden;
+pub mod headless;
+pub mod history;
+pub mod profiler;
 pub mod signal;
 pub mod simulator;
+pub mod simulator_thread;
+pub mod snapshot;
+pub mod vcd;
 
 // Default provided components
 #[cfg(feature = "components")]
 pub mod components;
 
+// Testbench driving models through ProbeEdit components
+#[cfg(feature = "components")]
+pub mod testbench;
+
+// Stimulus recorded from ProbeEdit components, replayed by ProbeStim components
+#[cfg(feature = "components")]
+pub mod stimulus;
+
+// Models shared by the unit tests
+#[cfg(all(test, feature = "components"))]
+mod fixtures;
+
 // Vizia frontend
 #[cfg(feature = "gui-vizia")]
 pub mod gui_vizia;
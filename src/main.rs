@@ -1,8 +1,11 @@
 use clap::Parser;
-use std::path::PathBuf;
+use std::{path::PathBuf, process::ExitCode};
 #[cfg(feature = "gui-egui")]
 use syncrim::gui_egui::editor::Library;
-use syncrim::{common::ComponentStore, fern::fern_setup};
+use syncrim::{
+    fern::fern_setup,
+    headless::{run_model, RunArgs},
+};
 /// Simple program to greet a person
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -10,21 +13,35 @@ struct Args {
     /// Path to the model to load on startup
     #[arg(short, long, default_value = "empty.json")]
     model: String,
+
+    #[command(flatten)]
+    run: RunArgs,
 }
 
-fn main() {
+fn main() -> ExitCode {
     fern_setup();
     let args = Args::parse();
     let path = PathBuf::from(args.model);
 
-    let cs = ComponentStore::load_file(&path).unwrap();
+    // without a GUI feature the model is always run headless
+    #[cfg(any(feature = "gui-vizia", feature = "gui-egui"))]
+    if !args.run.is_headless() {
+        let cs = match args.run.load_model(&path) {
+            Ok(cs) => cs,
+            Err(err) => {
+                eprintln!("{}", err);
+                return ExitCode::FAILURE;
+            }
+        };
+
+        #[cfg(feature = "gui-egui")]
+        syncrim::gui_egui::gui(cs, &path, Library::default()).ok();
 
-    #[cfg(feature = "gui-egui")]
-    syncrim::gui_egui::gui(cs, &path, Library::default()).ok();
+        #[cfg(feature = "gui-vizia")]
+        syncrim::gui_vizia::gui(cs, &path);
 
-    #[cfg(feature = "gui-vizia")]
-    syncrim::gui_vizia::gui(cs, &path);
+        return ExitCode::SUCCESS;
+    }
 
-    #[cfg(not(any(feature = "gui-vizia", feature = "gui-egui")))]
-    syncrim::common::Simulator::new(cs).unwrap();
+    run_model(&path, &args.run)
 }
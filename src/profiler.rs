@@ -0,0 +1,150 @@
+// Per-component profiling, the time spent in and the number of calls to `Component::clock`
+//
+// Profiling is off by default, when enabled each component clock is timed, adding some
+// overhead to every clock. Counts accumulate (also over reset) until cleared.
+use crate::common::{Id, Simulator};
+use std::{fmt, time::Duration};
+
+/// Accumulated clock calls and time of an ordered component
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ComponentTiming {
+    pub calls: u64,
+    pub time: Duration,
+}
+
+/// Profile of a component
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComponentProfile {
+    pub id: Id,
+    pub calls: u64,
+    pub time: Duration,
+}
+
+impl ComponentProfile {
+    /// mean time per call
+    pub fn mean(&self) -> Duration {
+        match u32::try_from(self.calls) {
+            Ok(0) => Duration::ZERO,
+            Ok(calls) => self.time / calls,
+            Err(_) => Duration::from_secs_f64(self.time.as_secs_f64() / self.calls as f64),
+        }
+    }
+}
+
+/// Profile of all components, most time spent first
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+    pub components: Vec<ComponentProfile>,
+}
+
+impl Profile {
+    /// total time spent clocking components
+    pub fn total(&self) -> Duration {
+        self.components.iter().map(|c| c.time).sum()
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total().as_secs_f64();
+        write!(
+            f,
+            "{:<24} {:>10} {:>12} {:>10} {:>6}",
+            "component", "calls", "time", "mean", "%"
+        )?;
+        for c in &self.components {
+            let share = if total > 0.0 {
+                100.0 * c.time.as_secs_f64() / total
+            } else {
+                0.0
+            };
+            write!(
+                f,
+                "\n{:<24} {:>10} {:>12.3?} {:>10.3?} {:>6.1}",
+                c.id,
+                c.calls,
+                c.time,
+                c.mean(),
+                share
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Simulator {
+    /// enable or disable profiling, enabling clears the counts
+    pub fn set_profiling(&mut self, profiling: bool) {
+        self.profile =
+            profiling.then(|| vec![ComponentTiming::default(); self.component_ids.len()]);
+    }
+
+    pub fn is_profiling(&self) -> bool {
+        self.profile.is_some()
+    }
+
+    /// clear the counts, if profiling
+    pub fn clear_profile(&mut self) {
+        if let Some(profile) = &mut self.profile {
+            profile.fill(ComponentTiming::default());
+        }
+    }
+
+    /// profile of the components clocked since profiling was enabled or cleared,
+    /// empty if not profiling
+    pub fn profile(&self) -> Profile {
+        let mut components: Vec<_> = self
+            .profile
+            .iter()
+            .flatten()
+            .zip(&self.component_ids)
+            .filter(|(timing, _)| timing.calls > 0)
+            .map(|(timing, id)| ComponentProfile {
+                id: id.clone(),
+                calls: timing.calls,
+                time: timing.time,
+            })
+            .collect();
+        components.sort_by(|a, b| b.time.cmp(&a.time).then_with(|| a.id.cmp(&b.id)));
+        Profile { components }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::counter;
+
+    #[test]
+    fn test_profile() {
+        let mut simulator = Simulator::new(counter(1)).unwrap();
+        simulator.clock();
+        assert!(!simulator.is_profiling());
+        assert!(simulator.profile().components.is_empty());
+
+        simulator.set_profiling(true);
+        for _ in 0..5 {
+            simulator.clock();
+        }
+        let profile = simulator.profile();
+        let mut calls: Vec<_> = profile
+            .components
+            .iter()
+            .map(|c| (c.id.as_str(), c.calls))
+            .collect();
+        calls.sort();
+        assert_eq!(calls, [("add", 5), ("reg", 5), ("step", 5)]);
+        // header and a line per component
+        assert_eq!(profile.to_string().lines().count(), 4);
+
+        // only components clocked are counted when event-driven
+        simulator.clear_profile();
+        simulator.set_event_driven(true);
+        simulator.clock();
+        simulator.clock();
+        assert!(simulator.profile().components.iter().all(|c| c.calls <= 2));
+
+        simulator.set_profiling(false);
+        assert!(simulator.profile().components.is_empty());
+    }
+}
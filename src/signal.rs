@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use std::{
     convert::{From, TryFrom},
     fmt,
+    ops::{BitAnd, BitOr, BitXor, Not},
+    str::FromStr,
 };
 
 pub type Id = String;
@@ -11,6 +13,9 @@ pub type Id = String;
 pub type SignalUnsigned = u32;
 pub type SignalSigned = i32;
 pub type SignalBool = bool;
+// data wider than `SignalUnsigned`, e.g., 64 bit datapaths and timers
+pub type SignalWide = u64;
+pub type SignalWideSigned = i64;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Copy, Clone)]
 pub struct Signal {
@@ -37,22 +42,108 @@ impl Signal {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq)]
 pub enum SignalValue {
     Uninitialized,
     Unknown,
     DontCare,
     Data(SignalUnsigned), // Maybe we should have something even more generic here
+    Wide(SignalWide),     // 64 bit data, components only handling `Data` accept it if it fits
+}
+
+// Four-state semantics
+//
+// `Data` and `Wide` are known values. `Uninitialized` is the value of outputs not yet
+// evaluated, `Unknown` (X) a value that cannot be determined (e.g., computed from an
+// invalid input) and `DontCare` a value that is not relevant. Any value that is not
+// known data is X to an operation, which then yields `Unknown`, unless the result is
+// determined by the known operands alone (e.g., `0 & X == 0`).
+// Components should propagate X on their outputs rather than panic.
+impl SignalValue {
+    /// true for known data, `Data` or `Wide`
+    pub fn is_known(&self) -> bool {
+        matches!(self, SignalValue::Data(_) | SignalValue::Wide(_))
+    }
+
+    /// apply `f` to the data, X-propagating
+    /// the result is `Wide` for `Wide` data, otherwise truncated to `Data`
+    pub fn map(self, f: impl FnOnce(SignalWide) -> SignalWide) -> SignalValue {
+        match self {
+            SignalValue::Data(data) => SignalValue::Data(f(data as SignalWide) as SignalUnsigned),
+            SignalValue::Wide(data) => SignalValue::Wide(f(data)),
+            _ => SignalValue::Unknown,
+        }
+    }
+
+    /// apply `f` to the data of both operands, X-propagating
+    /// the result is `Wide` if either operand is `Wide`, otherwise truncated to `Data`
+    pub fn zip_with(
+        self,
+        other: SignalValue,
+        f: impl FnOnce(SignalWide, SignalWide) -> SignalWide,
+    ) -> SignalValue {
+        match (self, other) {
+            (SignalValue::Data(a), SignalValue::Data(b)) => {
+                SignalValue::Data(f(a as SignalWide, b as SignalWide) as SignalUnsigned)
+            }
+            _ => match (self.as_wide(), other.as_wide()) {
+                (Some(a), Some(b)) => SignalValue::Wide(f(a, b)),
+                _ => SignalValue::Unknown,
+            },
+        }
+    }
+
+    /// select among `inputs`, X-propagating
+    /// an unknown select gives the common value of the inputs if all are equal,
+    /// a select out of range gives `Unknown`
+    pub fn select(self, inputs: &[SignalValue]) -> SignalValue {
+        match usize::try_from(self) {
+            Ok(select) => inputs.get(select).copied().unwrap_or(SignalValue::Unknown),
+            Err(_) => match inputs.split_first() {
+                Some((first, rest)) if first.is_known() && rest.iter().all(|v| v == first) => {
+                    *first
+                }
+                _ => SignalValue::Unknown,
+            },
+        }
+    }
+
+    /// number of bits of the data, None if not `Data` or `Wide`
+    pub fn width(&self) -> Option<u32> {
+        match self {
+            SignalValue::Data(_) => Some(SignalUnsigned::BITS),
+            SignalValue::Wide(_) => Some(SignalWide::BITS),
+            _ => None,
+        }
+    }
+
+    /// data zero extended to `SignalWide`, None if not `Data` or `Wide`
+    pub fn as_wide(&self) -> Option<SignalWide> {
+        match self {
+            SignalValue::Data(data) => Some(*data as SignalWide),
+            SignalValue::Wide(data) => Some(*data),
+            _ => None,
+        }
+    }
+}
+
+// known data is equal by value, regardless of width, i.e., `Data(n) == Wide(n)`
+impl PartialEq for SignalValue {
+    fn eq(&self, other: &SignalValue) -> bool {
+        match (self.as_wide(), other.as_wide()) {
+            (Some(a), Some(b)) => a == b,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
 }
 
 impl TryFrom<Signal> for bool {
     type Error = String;
 
     fn try_from(signal: Signal) -> Result<Self, Self::Error> {
-        if let SignalValue::Data(data) = signal.data {
-            Ok(data == 1)
-        } else {
-            Err(format!("Could not convert {:?} into bool", signal))
+        match signal.data.as_wide() {
+            Some(data) => Ok(data == 1),
+            None => Err(format!("Could not convert {:?} into bool", signal)),
         }
     }
 }
@@ -61,10 +152,9 @@ impl TryFrom<SignalValue> for bool {
     type Error = String;
 
     fn try_from(data: SignalValue) -> Result<Self, Self::Error> {
-        if let SignalValue::Data(data) = data {
-            Ok(data == 1)
-        } else {
-            Err(format!("Could not convert {:?} into bool", data))
+        match data.as_wide() {
+            Some(value) => Ok(value == 1),
+            None => Err(format!("Could not convert {:?} into bool", data)),
         }
     }
 }
@@ -73,11 +163,9 @@ impl TryFrom<SignalValue> for usize {
     type Error = String;
 
     fn try_from(data: SignalValue) -> Result<Self, Self::Error> {
-        if let SignalValue::Data(data) = data {
-            Ok(data as usize)
-        } else {
-            Err(format!("Could not convert {:?} into usize", data))
-        }
+        data.as_wide()
+            .and_then(|value| usize::try_from(value).ok())
+            .ok_or(format!("Could not convert {:?} into usize", data))
     }
 }
 
@@ -85,34 +173,97 @@ impl TryFrom<Signal> for SignalUnsigned {
     type Error = String;
 
     fn try_from(signal: Signal) -> Result<Self, Self::Error> {
-        if let SignalValue::Data(data) = signal.data {
-            Ok(data)
-        } else {
-            Err(format!(
-                "Could not convert {:?} into SignalUnsigned",
-                signal
-            ))
-        }
+        SignalUnsigned::try_from(signal.data)
+            .map_err(|_| format!("Could not convert {:?} into SignalUnsigned", signal))
     }
 }
 
+// `Wide` data is converted only if it fits
 impl TryFrom<SignalValue> for SignalUnsigned {
     type Error = String;
 
     fn try_from(data: SignalValue) -> Result<Self, Self::Error> {
-        if let SignalValue::Data(data) = data {
-            Ok(data)
-        } else {
-            Err(format!("Could not convert {:?} into SignalUnsigned", data))
+        data.as_wide()
+            .and_then(|value| SignalUnsigned::try_from(value).ok())
+            .ok_or(format!("Could not convert {:?} into SignalUnsigned", data))
+    }
+}
+
+impl TryFrom<Signal> for SignalWide {
+    type Error = String;
+
+    fn try_from(signal: Signal) -> Result<Self, Self::Error> {
+        signal
+            .data
+            .as_wide()
+            .ok_or(format!("Could not convert {:?} into SignalWide", signal))
+    }
+}
+
+impl TryFrom<SignalValue> for SignalWide {
+    type Error = String;
+
+    fn try_from(data: SignalValue) -> Result<Self, Self::Error> {
+        data.as_wide()
+            .ok_or(format!("Could not convert {:?} into SignalWide", data))
+    }
+}
+
+impl BitAnd for SignalValue {
+    type Output = SignalValue;
+
+    /// a known zero operand gives zero
+    fn bitand(self, rhs: SignalValue) -> SignalValue {
+        match (self, rhs) {
+            (SignalValue::Data(0), _) | (_, SignalValue::Data(0)) => SignalValue::Data(0),
+            (SignalValue::Wide(0), _) | (_, SignalValue::Wide(0)) => SignalValue::Wide(0),
+            _ => self.zip_with(rhs, |a, b| a & b),
         }
     }
 }
 
+impl BitOr for SignalValue {
+    type Output = SignalValue;
+
+    /// a known all ones operand gives all ones
+    fn bitor(self, rhs: SignalValue) -> SignalValue {
+        match (self, rhs) {
+            (a, b) if a.is_known() && b.is_known() => a.zip_with(b, |a, b| a | b),
+            (SignalValue::Data(SignalUnsigned::MAX), _)
+            | (_, SignalValue::Data(SignalUnsigned::MAX)) => SignalValue::Data(SignalUnsigned::MAX),
+            (SignalValue::Wide(SignalWide::MAX), _) | (_, SignalValue::Wide(SignalWide::MAX)) => {
+                SignalValue::Wide(SignalWide::MAX)
+            }
+            _ => SignalValue::Unknown,
+        }
+    }
+}
+
+impl BitXor for SignalValue {
+    type Output = SignalValue;
+
+    fn bitxor(self, rhs: SignalValue) -> SignalValue {
+        self.zip_with(rhs, |a, b| a ^ b)
+    }
+}
+
+impl Not for SignalValue {
+    type Output = SignalValue;
+
+    fn not(self) -> SignalValue {
+        self.map(|a| !a)
+    }
+}
+
 impl From<SignalValue> for Signal {
     fn from(data: SignalValue) -> Signal {
+        let size = match data {
+            SignalValue::Wide(_) => SignalSize::_64,
+            _ => SignalSize::_32,
+        };
         Signal {
             data,
-            fmt: SignalFmt::Hex(SignalSize::_32, false),
+            fmt: SignalFmt::Hex(size, false),
         }
     }
 }
@@ -156,6 +307,54 @@ impl From<bool> for SignalValue {
     }
 }
 
+/// Error parsing a signal value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSignalError(pub String);
+
+impl fmt::Display for ParseSignalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid signal value {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseSignalError {}
+
+// decimal (negative values are two's complement), hexadecimal (0x) or binary (0b),
+// values not fitting 32 bits are `Wide`, `x`/`X` is `Unknown` and `-` is `DontCare`
+impl FromStr for SignalValue {
+    type Err = ParseSignalError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let err = || ParseSignalError(text.to_string());
+        let wide = match text {
+            "x" | "X" | "Unknown" => return Ok(SignalValue::Unknown),
+            "-" | "DontCare" => return Ok(SignalValue::DontCare),
+            "Uninitialized" => return Ok(SignalValue::Uninitialized),
+            _ => {
+                let digits = text.replace('_', "");
+                if let Some(hex) = digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+                    SignalWide::from_str_radix(hex, 16).map_err(|_| err())?
+                } else if let Some(bin) = digits.strip_prefix("0b").or(digits.strip_prefix("0B")) {
+                    SignalWide::from_str_radix(bin, 2).map_err(|_| err())?
+                } else if digits.starts_with('-') {
+                    let signed = digits.parse::<SignalWideSigned>().map_err(|_| err())?;
+                    match SignalSigned::try_from(signed) {
+                        Ok(signed) => return Ok((signed as SignalUnsigned).into()),
+                        Err(_) => signed as SignalWide,
+                    }
+                } else {
+                    digits.parse::<SignalWide>().map_err(|_| err())?
+                }
+            }
+        };
+        Ok(match SignalUnsigned::try_from(wide) {
+            Ok(data) => SignalValue::Data(data),
+            Err(_) => SignalValue::Wide(wide),
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Copy, Clone)]
 pub enum SignalFmt {
     Ascii(SignalSize),
@@ -172,16 +371,17 @@ pub enum SignalSize {
     _8 = 1,
     _16 = 2,
     _32 = 4,
+    _64 = 8,
 }
 
 impl fmt::Display for Signal {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.data {
-            SignalValue::Data(value) => match self.fmt {
+        match self.data.as_wide() {
+            Some(value) => match self.fmt {
                 SignalFmt::Ascii(signal_size) => {
                     let s: u8 = signal_size.into();
 
-                    let bytes = &value.to_ne_bytes()[0..s as usize];
+                    let bytes = &value.to_le_bytes()[0..s as usize];
                     let s: String = bytes
                         .iter()
                         .map(|b| {
@@ -198,7 +398,8 @@ impl fmt::Display for Signal {
                     write!(f, "{}", s)
                 }
                 SignalFmt::Binary(size) => {
-                    write!(f, "0b{}", &format!("{:032b}", value)[32 - size as usize..])
+                    let size = (size as u32).min(SignalWide::BITS) as usize;
+                    write!(f, "0b{}", &format!("{:064b}", value)[64 - size..])
                 }
                 SignalFmt::Unsigned(size) => write!(
                     f,
@@ -206,7 +407,8 @@ impl fmt::Display for Signal {
                     match size {
                         SignalSize::_8 => format!("{}", value as u8),
                         SignalSize::_16 => format!("{}", value as u16),
-                        SignalSize::_32 => format!("{}", value),
+                        SignalSize::_32 => format!("{}", value as u32),
+                        SignalSize::_64 => format!("{}", value),
                     }
                 ),
                 SignalFmt::Signed(size) => write!(
@@ -216,6 +418,7 @@ impl fmt::Display for Signal {
                         SignalSize::_8 => format!("{}", value as i8),
                         SignalSize::_16 => format!("{}", value as i16),
                         SignalSize::_32 => format!("{}", value as i32),
+                        SignalSize::_64 => format!("{}", value as i64),
                     }
                 ),
                 SignalFmt::Hex(size, true) => write!(
@@ -224,7 +427,8 @@ impl fmt::Display for Signal {
                     match size {
                         SignalSize::_8 => format!("{:#04x}", value as u8),
                         SignalSize::_16 => format!("{:#06x}", value as u16),
-                        SignalSize::_32 => format!("{:#010x}", value),
+                        SignalSize::_32 => format!("{:#010x}", value as u32),
+                        SignalSize::_64 => format!("{:#018x}", value),
                     }
                 ),
                 SignalFmt::Hex(size, false) => write!(
@@ -233,12 +437,13 @@ impl fmt::Display for Signal {
                     match size {
                         SignalSize::_8 => format!("{:#x}", value as u8),
                         SignalSize::_16 => format!("{:#x}", value as u16),
-                        SignalSize::_32 => format!("{:#x}", value),
+                        SignalSize::_32 => format!("{:#x}", value as u32),
+                        SignalSize::_64 => format!("{:#x}", value),
                     }
                 ),
                 SignalFmt::Bool => write!(f, "{}", value != 0),
             },
-            _ => write!(f, "{:?}", self.data),
+            None => write!(f, "{:?}", self.data),
         }
     }
 }
@@ -247,6 +452,21 @@ impl fmt::Display for Signal {
 mod test {
     use super::*;
 
+    #[test]
+    fn test_parse() {
+        assert_eq!("42".parse(), Ok(SignalValue::Data(42)));
+        assert_eq!(" 0x1000 ".parse(), Ok(SignalValue::Data(0x1000)));
+        assert_eq!("0b1010".parse(), Ok(SignalValue::Data(10)));
+        assert_eq!("-1".parse(), Ok(SignalValue::Data(0xffff_ffff)));
+        assert_eq!(
+            "0x1_0000_0000".parse(),
+            Ok(SignalValue::Wide(0x1_0000_0000))
+        );
+        assert_eq!("x".parse(), Ok(SignalValue::Unknown));
+        assert_eq!("-".parse(), Ok(SignalValue::DontCare));
+        assert!("0xg".parse::<SignalValue>().is_err());
+    }
+
     #[test]
     fn test_bool_fmt() {
         let mut signal: Signal = false.into();
@@ -398,4 +618,102 @@ mod test {
         println!("{}", s);
         assert_eq!(s, "¤");
     }
+
+    #[test]
+    fn test_wide_fmt() {
+        let mut signal: Signal = SignalValue::Wide(0xF000_0000_0000_0001).into();
+        let s = format!("{}", signal);
+        println!("{}", s);
+        assert_eq!(&s, "0xf000000000000001");
+
+        signal.set_fmt(SignalFmt::Hex(SignalSize::_64, true));
+        signal.set_value(SignalValue::Wide(0x1_0000_0000));
+        let s = format!("{}", signal);
+        println!("{}", s);
+        assert_eq!(&s, "0x0000000100000000");
+
+        signal.set_fmt(SignalFmt::Signed(SignalSize::_64));
+        signal.set_value(SignalValue::Wide(-2i64 as SignalWide));
+        let s = format!("{}", signal);
+        println!("{}", s);
+        assert_eq!(&s, "-2");
+
+        signal.set_fmt(SignalFmt::Binary(36));
+        signal.set_value(SignalValue::Wide(0x8_0000_0001));
+        let s = format!("{}", signal);
+        println!("{}", s);
+        assert_eq!(&s, "0b100000000000000000000000000000000001");
+    }
+
+    #[test]
+    fn test_wide_conversions() {
+        let wide = SignalValue::Wide(0x1_0000_0000);
+        assert_eq!(wide.width(), Some(64));
+        assert_eq!(SignalWide::try_from(wide), Ok(0x1_0000_0000));
+        // does not fit
+        assert!(SignalUnsigned::try_from(wide).is_err());
+
+        // wide data that fits converts to narrower types
+        assert_eq!(SignalUnsigned::try_from(SignalValue::Wide(42)), Ok(42));
+        assert_eq!(usize::try_from(SignalValue::Wide(42)), Ok(42));
+        assert_eq!(bool::try_from(SignalValue::Wide(1)), Ok(true));
+
+        // data is zero extended
+        let data = SignalValue::Data(0xFFFF_FFFF);
+        assert_eq!(data.width(), Some(32));
+        assert_eq!(SignalWide::try_from(data), Ok(0xFFFF_FFFF));
+        assert!(SignalWide::try_from(SignalValue::Unknown).is_err());
+
+        // known data is compared by value
+        assert_eq!(SignalValue::Data(42), SignalValue::Wide(42));
+        assert_ne!(SignalValue::Data(1), SignalValue::Wide(0x1_0000_0001));
+        assert_ne!(SignalValue::Data(0), SignalValue::Unknown);
+        assert_ne!(SignalValue::Unknown, SignalValue::DontCare);
+        assert_eq!(SignalValue::Unknown, SignalValue::Unknown);
+    }
+
+    #[test]
+    fn test_x_propagation() {
+        use SignalValue::*;
+        let x = Unknown;
+
+        assert_eq!(Data(6) & Data(3), Data(2));
+        assert_eq!(Data(0) & x, Data(0));
+        assert_eq!(Data(1) & x, Unknown);
+        assert_eq!(DontCare & Data(1), Unknown);
+
+        assert_eq!(Data(6) | Data(3), Data(7));
+        assert_eq!(x | Data(SignalUnsigned::MAX), Data(SignalUnsigned::MAX));
+        assert_eq!(Uninitialized | Data(1), Unknown);
+
+        assert_eq!(Data(6) ^ Data(3), Data(5));
+        assert_eq!(Data(6) ^ x, Unknown);
+        assert_eq!(!Data(0), Data(SignalUnsigned::MAX));
+        assert_eq!(!x, Unknown);
+
+        assert_eq!(Data(2).map(|a| a + 1), Data(3));
+        assert_eq!(DontCare.map(|a| a + 1), Unknown);
+        assert_eq!(Data(2).zip_with(x, |a, b| a + b), Unknown);
+
+        // Wide operands give Wide results
+        let wide = Wide(0x1_0000_00ff);
+        assert_eq!(wide & Data(0xf0f), Wide(0xf));
+        assert_eq!(Data(0xf0f) & wide, Wide(0xf));
+        assert_eq!((Data(0xf0f) & wide).width(), Some(64));
+        assert_eq!(wide & x, Unknown);
+        assert_eq!(Wide(0) & x, Wide(0));
+        assert_eq!(wide | Data(0x100), Wide(0x1_0000_01ff));
+        assert_eq!(wide ^ wide, Wide(0));
+        assert_eq!(!wide, Wide(0xffff_fffe_ffff_ff00));
+        assert_eq!(!Wide(5), Wide(!5));
+        assert_eq!(wide.map(|a| a >> 32), Wide(1));
+        assert_eq!(Data(SignalUnsigned::MAX).map(|a| a + 1), Data(0));
+        assert_eq!(wide.zip_with(Data(1), |a, b| a + b), Wide(0x1_0000_0100));
+
+        let inputs = [Data(1), Data(2)];
+        assert_eq!(Data(1).select(&inputs), Data(2));
+        assert_eq!(Data(2).select(&inputs), Unknown);
+        assert_eq!(x.select(&inputs), Unknown);
+        assert_eq!(x.select(&[Data(1), Data(1)]), Data(1));
+    }
 }
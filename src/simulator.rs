@@ -1,7 +1,9 @@
 use crate::common::{
-    Component, ComponentStore, Condition, Id, Input, OutputType, RunningState, Signal, SignalFmt,
-    SignalValue, Simulator, SimulatorError,
+    Component, ComponentStore, Condition, Id, Input, InputHandle, OutputType, RunningState, Signal,
+    SignalFmt, SignalValue, Simulator, SimulatorError,
 };
+use crate::elaboration::{combinational_loops, ElaborationError, ElaborationProblem};
+use crate::history::History;
 use log::*;
 use petgraph::{
     algo::toposort,
@@ -9,16 +11,30 @@ use petgraph::{
     Graph,
 };
 use std::collections::{HashMap, HashSet};
-use std::{fs::File, io::prelude::*, path::PathBuf};
+use std::{fs::File, io::prelude::*, path::PathBuf, rc::Rc, time::Instant};
 
 pub struct IdComponent(pub HashMap<String, Box<dyn Component>>);
 
 // Notice:
 // The topological order does not enforce any specific order of registers
-// Thus registers cannot point to other registers in a cyclic fashion
-// This is (likely) not occurring in practice.
+// Thus registers reading other registers sample their inputs before any register output is
+// updated, outputs read by registers are committed after all registers are clocked
 impl Simulator {
-    pub fn new(component_store: ComponentStore) -> Result<Self, &'static str> {
+    /// elaborate the model into a simulator, reset to cycle 1
+    /// all problems found in the model are reported at once
+    pub fn new(component_store: ComponentStore) -> Result<Self, ElaborationError> {
+        #[cfg(feature = "components")]
+        let (component_store, mut problems) =
+            crate::components::flatten_sub_circuits(component_store);
+        #[cfg(feature = "components")]
+        let component_store = {
+            let (component_store, vector_problems) =
+                crate::components::load_vectors(component_store);
+            problems.extend(vector_problems);
+            component_store
+        };
+        #[cfg(not(feature = "components"))]
+        let mut problems = vec![];
         for component in &component_store.store {
             component.reset();
         }
@@ -38,20 +54,19 @@ impl Simulator {
             trace!("{:?}", c.get_id_ports().0);
             let (id, ports) = c.get_id_ports();
 
+            trace!("id {}, ports {:?}", id, ports);
+            // start index for outputs related to component
+            if id_start_index.contains_key(&id) {
+                problems.push(ElaborationProblem::DuplicateId { id });
+                continue;
+            }
+            id_start_index.insert(id.clone(), lens_values.len());
+
             // push all sinks
             if c.is_sink() {
                 sinks.push(id.clone());
             }
 
-            trace!("id {}, ports {:?}", id, ports);
-            // start index for outputs related to component
-            if id_start_index
-                .insert(id.clone(), lens_values.len())
-                .is_some()
-            {
-                panic!("Component identifier {:?} is defined twice", id);
-            }
-
             id_component.insert(id.clone(), c);
 
             // create placeholder for output
@@ -63,7 +78,10 @@ impl Simulator {
                     .insert((id.clone(), field_id.into()), index)
                     .is_some()
                 {
-                    panic!("Component {:?} field {:?} is defined twice", id, field_id)
+                    problems.push(ElaborationProblem::DuplicateField {
+                        id: id.clone(),
+                        field: field_id.clone(),
+                    });
                 };
             }
             id_nr_outputs.insert(id.clone(), ports.outputs.len());
@@ -75,11 +93,15 @@ impl Simulator {
         let mut id_node = HashMap::new();
         let mut node_comp = HashMap::new();
 
-        // insert nodes
-        for (id, c) in &id_component {
-            let node = graph.add_node(id.to_owned());
+        // insert nodes, in model order for a deterministic evaluation order
+        for c in &component_store.store {
+            let id = c.get_id_ports().0;
+            if id_node.contains_key(&id) {
+                continue;
+            }
+            let node = graph.add_node(id.clone());
+            node_comp.insert(node, &id_component[&id]);
             id_node.insert(id, node);
-            node_comp.insert(node, c);
         }
 
         trace!("\nid_node {:?}", id_node);
@@ -88,41 +110,70 @@ impl Simulator {
             trace!("node {:?}, comp_id {:?}", node, c.get_id_ports());
         }
 
-        // insert edges
-        for (to_id, c) in &id_component {
-            let to_component = id_component.get(to_id).unwrap();
-            let (_, ports) = to_component.get_id_ports();
-
+        // insert edges, in model order for deterministic reports
+        for c in &component_store.store {
+            let (to_id, ports) = c.get_id_ports();
+            if !id_component.get(&to_id).is_some_and(|to| Rc::ptr_eq(to, c)) {
+                // duplicate id, already reported
+                continue;
+            }
             trace!("to_id :{}, ports: {:?}", to_id, ports);
 
-            if ports.out_type == OutputType::Combinatorial {
-                trace!("combinatorial, id:{}", to_component.get_id_ports().0);
-                let to_node = id_node.get(to_id).unwrap();
-                let (_, ports) = c.get_id_ports();
-                for in_port in &ports.inputs {
-                    let from_id = &in_port.input.id;
-                    let from_node = id_node.get(from_id);
-                    if from_node.is_none() {
-                        println!("to id: {} from port {} is not connected", to_id, from_id);
-                        return Err("A port left unconnected");
+            for in_port in &ports.inputs {
+                let input = &in_port.input;
+                let Some(from_node) = id_node.get(&input.id) else {
+                    problems.push(ElaborationProblem::UnconnectedPort {
+                        id: to_id.clone(),
+                        port_id: in_port.port_id.clone(),
+                        input: input.clone(),
+                    });
+                    continue;
+                };
+                if !id_field_index.contains_key(&(input.id.clone(), input.field.clone())) {
+                    problems.push(ElaborationProblem::UnconnectedPort {
+                        id: to_id.clone(),
+                        port_id: in_port.port_id.clone(),
+                        input: input.clone(),
+                    });
+                    continue;
+                }
+
+                match ports.out_type {
+                    OutputType::Combinatorial => {
+                        trace!("combinatorial, id:{}", to_id);
+                        let to_node = id_node.get(&to_id).unwrap();
+                        graph.add_edge(*from_node, *to_node, ());
+                        trace!(
+                            "add_edge {}:{:?} -> {}:{:?}",
+                            input.id,
+                            from_node,
+                            to_id,
+                            to_node
+                        );
                     }
-                    let from_node = from_node.unwrap();
-
-                    graph.add_edge(*from_node, *to_node, ());
-                    trace!(
-                        "add_edge {}:{:?} -> {}:{:?}",
-                        from_id,
-                        from_node,
-                        to_id,
-                        to_node
-                    );
+                    // sequential to sequential is handled by the two-phase update in clock
+                    OutputType::Sequential => {}
                 }
             }
         }
 
         // topological order
-        let top =
-            toposort(&graph, None).expect("Topological sort failed, your model contains loops.");
+        let top = match toposort(&graph, None) {
+            Ok(top) => top,
+            Err(_) => {
+                let inputs = id_component
+                    .iter()
+                    .map(|(id, c)| (id.clone(), c.get_id_ports().1.inputs))
+                    .collect();
+                for path in combinational_loops(&graph, &inputs) {
+                    problems.push(ElaborationProblem::CombinationalLoop { path });
+                }
+                vec![]
+            }
+        };
+        if !problems.is_empty() {
+            return Err(ElaborationError { problems });
+        }
         trace!("--- topologically ordered graph \n{:?}", top);
         //two passes, first add all sequential roots
         let mut ordered_components = vec![];
@@ -136,26 +187,6 @@ impl Simulator {
             }
         }
 
-        // check if a sequential components is linking to another sequential component and panic
-        // this avoids that the order of sequential components matter as they can't affect one other.
-        for seq_node in &ordered_components {
-            for seq_node_inputs in seq_node
-                .get_id_ports()
-                .1
-                .inputs
-                .iter()
-                .map(|port| &port.input)
-            {
-                if ordered_components
-                    .iter()
-                    .find(|node| node.get_id_ports().0 == seq_node_inputs.id)
-                    .is_some()
-                {
-                    panic!("Component {} read data from {}. Sequential to sequential is not allowed, consider adding a pass trough component", seq_node.get_id_ports().0, seq_node_inputs.id)
-                }
-            }
-        }
-
         //then the rest...
         for node in &top {
             #[allow(suspicious_double_ref_op)]
@@ -175,6 +206,70 @@ impl Simulator {
             component_ids
         );
 
+        // resolve inputs, and dependencies between ordered components for event-driven evaluation
+        let component_index: HashMap<Id, usize> = component_ids
+            .iter()
+            .enumerate()
+            .map(|(index, id)| (id.clone(), index))
+            .collect();
+        let mut always_clock = vec![];
+        let mut output_range = vec![];
+        let mut fanout = vec![vec![]; ordered_components.len()];
+        let mut inputs = vec![];
+        let mut input_handles = vec![];
+        let mut output_fields = vec![];
+        // sequential components are ordered first, those read by sequential components
+        // are committed after all sequential components are clocked
+        let nr_sequential = ordered_components
+            .iter()
+            .take_while(|c| c.get_id_ports().1.out_type == OutputType::Sequential)
+            .count();
+        let mut deferred = vec![false; nr_sequential];
+        for (index, c) in ordered_components.iter().enumerate() {
+            let (id, ports) = c.get_id_ports();
+            let start = id_start_index[&id];
+            output_range.push(start..start + id_nr_outputs[&id]);
+            inputs.push(ports.inputs.iter().map(|p| p.input.clone()).collect());
+            input_handles.push(
+                ports
+                    .inputs
+                    .iter()
+                    .map(|p| {
+                        InputHandle(
+                            id_start_index[&p.input.id]
+                                + id_field_index[&(p.input.id.clone(), p.input.field.clone())],
+                        )
+                    })
+                    .collect(),
+            );
+            output_fields.push(ports.outputs.clone());
+            always_clock.push(
+                ports.out_type == OutputType::Sequential
+                    || ports.inputs.is_empty()
+                    || c.always_clock(),
+            );
+            if ports.out_type == OutputType::Sequential {
+                for in_port in &ports.inputs {
+                    if let Some(deferred) = deferred.get_mut(component_index[&in_port.input.id]) {
+                        *deferred = true;
+                    }
+                }
+            }
+            if ports.out_type == OutputType::Combinatorial {
+                for in_port in &ports.inputs {
+                    let readers: &mut Vec<usize> = &mut fanout[component_index[&in_port.input.id]];
+                    if !readers.contains(&index) {
+                        readers.push(index);
+                    }
+                }
+            }
+        }
+
+        let inputs_read = inputs
+            .iter()
+            .map(|inputs: &Vec<Input>| vec![false; inputs.len()])
+            .collect();
+        let component_domain = vec![None; component_ids.len()];
         let mut simulator = Simulator {
             cycle: 0,
             id_start_index,
@@ -182,20 +277,45 @@ impl Simulator {
             id_nr_outputs,
             id_field_index,
             sim_state: lens_values,
-            history: vec![],
+            history: History::default(),
             component_ids,
             graph,
             halt_on_warning: false,
             running_state: RunningState::Stopped,
             component_condition: vec![],
-            running_state_history: vec![],
-            component_condition_history: vec![],
             // used for determine active components
             sinks,
-            inputs_read: HashMap::new(),
+            inputs_read,
             active: HashSet::new(),
+            event_driven: false,
+            full_sweep: true,
+            always_clock,
+            output_range,
+            fanout,
+            inputs,
+            input_handles,
+            output_fields,
+            component_index,
+            current: None,
+            deferred,
+            clock_domains: vec![],
+            component_domain,
+            breakpoints: vec![],
+            breakpoint_conditions: 0,
+            profile: None,
+            coverage: None,
+            faults: vec![],
+            vcd: None,
         };
 
+        #[cfg(feature = "components")]
+        {
+            let problems = simulator.add_clock_dividers();
+            if !problems.is_empty() {
+                return Err(ElaborationError { problems });
+            }
+        }
+
         trace!("sim_state {:?}", simulator.sim_state);
         simulator.reset();
         Ok(simulator)
@@ -206,9 +326,15 @@ impl Simulator {
         self.sim_state[index]
     }
 
-    /// get input signal
+    /// get input signal, looked up by id and field on each call (slow path, e.g., for tests and
+    /// the GUI), components read their ports by `get_port_value` or handles when clocked
     pub fn get_input_signal(&self, input: &Input) -> Signal {
-        #[allow(unreachable_code)]
+        // inputs of the component being clocked are resolved at elaboration
+        if let Some(current) = self.current {
+            if let Some(port) = self.inputs[current].iter().position(|i| i == input) {
+                return self.get(self.input_handles[current][port].0);
+            }
+        }
         let nr_out = *self
             .id_nr_outputs
             .get(&input.id)
@@ -237,7 +363,88 @@ impl Simulator {
         }
     }
 
-    /// get input value
+    /// get sim_state index of input, None if the component or field is not found
+    pub fn get_input_index(&self, input: &Input) -> Option<usize> {
+        let index = self
+            .id_field_index
+            .get(&(input.id.clone(), input.field.clone()))?;
+        Some(self.id_start_index.get(&input.id)? + index)
+    }
+
+    /// resolve input to a handle for fast access, None if the component or field is not found
+    pub fn resolve_input(&self, input: &Input) -> Option<InputHandle> {
+        self.get_input_index(input).map(InputHandle)
+    }
+
+    /// handles of the input ports of the component being clocked, in port order
+    /// (empty when no component is being clocked)
+    pub fn input_handles(&self) -> &[InputHandle] {
+        match self.current {
+            Some(current) => &self.input_handles[current],
+            None => &[],
+        }
+    }
+
+    /// get value of input port `port` (in `get_id_ports` order) of the component being clocked,
+    /// resolved at elaboration
+    /// panics if no component is being clocked
+    pub fn get_port_value(&self, port: usize) -> SignalValue {
+        self.get_handle_value(self.input_handles()[port])
+    }
+
+    /// get value of input port `port` of the component being clocked,
+    /// and update the inputs read (see `get_input_value_mut`)
+    pub fn get_port_value_mut(&mut self, port: usize) -> SignalValue {
+        let current = self.current.expect("no component being clocked");
+        self.inputs_read[current][port] = true;
+        self.get_handle_value(self.input_handles[current][port])
+    }
+
+    /// set value of output `output` (in `get_id_ports` order) of the component being clocked
+    /// panics if no component is being clocked
+    pub fn set_output_value(&mut self, output: usize, value: impl Into<SignalValue>) {
+        let current = self.current.expect("no component being clocked");
+        let range = self.output_range[current].clone();
+        assert!(output < range.len(), "output {} out of range", output);
+        self.set_value(range.start + output, value.into());
+    }
+
+    /// set fmt of output `output` (in `get_id_ports` order) of the component being clocked
+    /// panics if no component is being clocked
+    pub fn set_output_fmt(&mut self, output: usize, fmt: SignalFmt) {
+        let current = self.current.expect("no component being clocked");
+        let range = self.output_range[current].clone();
+        assert!(output < range.len(), "output {} out of range", output);
+        self.set_fmt(range.start + output, fmt);
+    }
+
+    /// get signal by resolved handle
+    pub fn get_handle_signal(&self, handle: InputHandle) -> Signal {
+        self.get(handle.0)
+    }
+
+    /// get value by resolved handle
+    pub fn get_handle_value(&self, handle: InputHandle) -> SignalValue {
+        self.get(handle.0).get_value()
+    }
+
+    /// get component by id, downcast to its type
+    /// None if not found or of another type
+    pub fn get_component<T: 'static>(&self, id: &str) -> Option<&T> {
+        let index = *self.component_index.get(id)?;
+        self.ordered_components[index].as_any().downcast_ref()
+    }
+
+    /// get signal by index at a past or the current cycle, None if out of range
+    pub fn get_signal_at(&self, cycle: usize, index: usize) -> Option<Signal> {
+        if cycle == self.cycle {
+            self.sim_state.get(index).copied()
+        } else {
+            self.history.get_signal(cycle, index)
+        }
+    }
+
+    /// get input value, looked up by id and field (see `get_input_signal`)
     pub fn get_input_value(&self, input: &Input) -> SignalValue {
         // trace!("get_input_value, input {:?}", input);
 
@@ -250,16 +457,15 @@ impl Simulator {
     pub fn get_input_value_mut(&mut self, id: Id, input: &Input) -> SignalValue {
         trace!("get_input_value_mut {:?} reading {:?}", id, input);
 
-        self.inputs_read
-            .entry(id)
-            .and_modify(|hs| {
-                hs.insert(input.id.clone());
-            })
-            .or_insert({
-                let mut hs = HashSet::new();
-                hs.insert(input.id.clone());
-                hs
-            });
+        let index = match self.current {
+            Some(current) if self.component_ids[current] == id => Some(current),
+            _ => self.component_index.get(&id).copied(),
+        };
+        if let Some(index) = index {
+            if let Some(port) = self.inputs[index].iter().position(|i| i == input) {
+                self.inputs_read[index][port] = true;
+            }
+        }
 
         self.get_input_signal(input).get_value()
     }
@@ -284,8 +490,24 @@ impl Simulator {
         self.sim_state[index].set_fmt(fmt);
     }
 
+    // index of output of the component being clocked, None if another component
+    fn current_output_index(&self, id: &str, field: &str) -> Option<usize> {
+        let current = self.current?;
+        if self.component_ids[current] != id {
+            return None;
+        }
+        let index = self.output_fields[current]
+            .iter()
+            .position(|f| f == field)?;
+        Some(self.output_range[current].start + index)
+    }
+
     /// set value by Id (instance) and Id (field)
     pub fn set_out_value(&mut self, id: &str, field: &str, value: impl Into<SignalValue>) {
+        if let Some(index) = self.current_output_index(id, field) {
+            self.set_value(index, value.into());
+            return;
+        }
         let index = *self
             .id_field_index
             .get(&(id.into(), field.into()))
@@ -298,6 +520,10 @@ impl Simulator {
 
     /// set fmt by Id (instance) and Id (field)
     pub fn set_out_fmt(&mut self, id: &str, field: &str, fmt: SignalFmt) {
+        if let Some(index) = self.current_output_index(id, field) {
+            self.set_fmt(index, fmt);
+            return;
+        }
         let index = *self
             .id_field_index
             .get(&(id.into(), field.into()))
@@ -306,6 +532,19 @@ impl Simulator {
         self.set_fmt(start_index + index, fmt);
     }
 
+    // commit the outputs of sequential components kept until all were clocked
+    fn commit_sequential(&mut self, pending: &mut Vec<(usize, Vec<Signal>)>, changed: &mut [bool]) {
+        for (index, outputs) in pending.drain(..) {
+            let range = self.output_range[index].clone();
+            if self.event_driven && self.sim_state[range.clone()] != outputs[..] {
+                for reader in &self.fanout[index] {
+                    changed[*reader] = true;
+                }
+            }
+            self.sim_state[range].copy_from_slice(&outputs);
+        }
+    }
+
     /// iterate over the evaluators and increase clock by one
     pub fn clock(&mut self) {
         // if state is error stop simulator from clocking
@@ -313,27 +552,99 @@ impl Simulator {
             return;
         }
         // push current state
-        self.history
-            .push((self.sim_state.clone(), self.active.clone()));
+        self.history.push(
+            &self.sim_state,
+            &self.active,
+            self.component_condition.clone(),
+            self.running_state.clone(),
+        );
         trace!("cycle:{}", self.cycle);
 
-        self.component_condition_history
-            .push(self.component_condition.clone());
-        self.running_state_history.push(self.running_state.clone());
-        self.clean_active();
-
-        // clear component condition data for this new cycle
-        self.component_condition.clear();
-
-        for component in self.ordered_components.clone() {
-            trace!("evaluating component:{}", component.get_id_ports().0);
+        // in event-driven evaluation components are only clocked if an input changed,
+        // otherwise they keep their outputs, inputs read and condition from the previous cycle
+        let full = !self.event_driven || self.full_sweep;
+        self.full_sweep = false;
+        let mut previous_condition = std::mem::take(&mut self.component_condition);
+        // breakpoint conditions are not carried to skipped components
+        previous_condition.truncate(
+            previous_condition.len() - self.breakpoint_conditions.min(previous_condition.len()),
+        );
+        // breakpoints are not evaluated on the clock done by reset
+        let breakpoint_values = (self.cycle > 0).then(|| self.breakpoint_values());
+        if full {
+            self.clean_active();
+        }
+        let mut changed = vec![full; self.ordered_components.len()];
+        for index in self.faulted_components() {
+            changed[index] = true;
+        }
+        let mut skipped = vec![];
+        let mut outputs = vec![];
+        let mut pending = vec![];
+        self.apply_memory_faults();
+
+        for index in 0..self.ordered_components.len() {
+            if index == self.deferred.len() {
+                self.commit_sequential(&mut pending, &mut changed);
+            }
+            // components of clock domains not clocked in this cycle are skipped,
+            // and always clocked when the domain is clocked (inputs may have changed since)
+            let ticks = self.ticks(index);
+            if !ticks
+                || (!changed[index]
+                    && !self.always_clock[index]
+                    && self.component_domain[index].is_none())
+            {
+                let id = &self.component_ids[index];
+                trace!("skipping component:{}", id);
+                if let Some(cond) = previous_condition.iter().find(|(c_id, _)| c_id == id) {
+                    self.component_condition.push(cond.clone());
+                }
+                skipped.push(index);
+                // inputs unchanged, the bins are those hit as if clocked
+                if ticks {
+                    self.cover(index);
+                }
+                if self.apply_output_faults(index) {
+                    for reader in &self.fanout[index] {
+                        changed[*reader] = true;
+                    }
+                }
+                continue;
+            }
+            trace!("evaluating component:{}", self.component_ids[index]);
+            if self.event_driven {
+                let range = self.output_range[index].clone();
+                outputs.clear();
+                outputs.extend_from_slice(&self.sim_state[range]);
+                self.inputs_read[index].fill(false);
+            }
 
             // Clock component and add its condition if error self.component_condition
-            match component.clock(self) {
+            let component = self.ordered_components[index].clone();
+            let deferred = self.deferred.get(index) == Some(&true);
+            let range = self.output_range[index].clone();
+            let sampled = deferred.then(|| self.sim_state[range.clone()].to_vec());
+            self.current = Some(index);
+            let start = self.profile.is_some().then(Instant::now);
+            let res = component.clock(self);
+            if let (Some(start), Some(profile)) = (start, &mut self.profile) {
+                profile[index].calls += 1;
+                profile[index].time += start.elapsed();
+            }
+            self.apply_output_faults(index);
+            self.current = None;
+            self.cover(index);
+            // keep the outputs read by other sequential components until all are clocked
+            if let Some(sampled) = sampled {
+                pending.push((index, self.sim_state[range.clone()].to_vec()));
+                self.sim_state[range].copy_from_slice(&sampled);
+            }
+            match res {
                 Ok(_) => {}
                 Err(cond) => {
                     self.component_condition
-                        .push((component.get_id_ports().0, cond.clone()));
+                        .push((self.component_ids[index].clone(), cond.clone()));
                     // is this trace necessary?
                     match cond {
                         Condition::Warning(warn) => {
@@ -351,7 +662,27 @@ impl Simulator {
                     }
                 }
             }
+
+            if self.event_driven && outputs[..] != self.sim_state[self.output_range[index].clone()]
+            {
+                for reader in &self.fanout[index] {
+                    changed[*reader] = true;
+                }
+            }
+        }
+        self.commit_sequential(&mut pending, &mut changed);
+        self.history.set_skipped(skipped);
+        if let Some(mut coverage) = self.coverage.take() {
+            coverage.sample(self, self.cycle > 0);
+            self.coverage = Some(coverage);
+        }
+
+        let conditions = self.component_condition.len();
+        if let Some(before) = breakpoint_values {
+            self.check_breakpoints(&before);
         }
+        self.breakpoint_conditions = self.component_condition.len() - conditions;
+
         // if there exist a component condition
         // get the most severe component condition
         // and update running state accordingly
@@ -375,15 +706,18 @@ impl Simulator {
                 }
             }
         }
-        self.cycle = self.history.len();
+        self.cycle += 1;
+        self.vcd_dump();
         self.active_components()
         // self.clock_mode = false;
     }
 
     // internal function to clear inputs read
-    fn clean_active(&mut self) {
+    pub(crate) fn clean_active(&mut self) {
         trace!("clear_active");
-        self.inputs_read = HashMap::new();
+        self.inputs_read
+            .iter_mut()
+            .for_each(|read| read.fill(false));
     }
 
     // internal function to determine active components
@@ -400,10 +734,14 @@ impl Simulator {
         while let Some(id) = to_visit.pop() {
             if !self.active.contains(&id) {
                 trace!("id not found {}", id);
-                if let Some(ids) = self.inputs_read.get(&id) {
-                    trace!("reading input(s) {:?}", ids);
-                    for id in ids {
-                        to_visit.push(id.clone());
+                if let Some(index) = self.component_index.get(&id) {
+                    for (input, _) in self.inputs[*index]
+                        .iter()
+                        .zip(&self.inputs_read[*index])
+                        .filter(|(_, read)| **read)
+                    {
+                        trace!("reading input {:?}", input);
+                        to_visit.push(input.id.clone());
                     }
                 }
                 self.active.insert(id);
@@ -411,6 +749,38 @@ impl Simulator {
         }
     }
 
+    // the most severe error or failed assertion reported in the last cycle, if any
+    pub(crate) fn failed_condition(&self) -> Option<&(Id, Condition)> {
+        self.component_condition
+            .iter()
+            .filter(|(_, c)| matches!(c, Condition::Error(_) | Condition::Assert(_)))
+            .max_by(|a, b| a.1.cmp(&b.1))
+    }
+
+    // record the coverage bins hit by component `index`, clocks done by reset are not counted
+    fn cover(&mut self, index: usize) {
+        if self.coverage.is_some() && self.cycle > 0 {
+            let bins = self.ordered_components[index].coverage(self);
+            if let Some(coverage) = &mut self.coverage {
+                coverage.hit(index, bins);
+            }
+        }
+    }
+
+    /// set the maximum number of cycles kept in history, unbounded if None
+    /// notice, `un_clock` is limited to the cycles kept
+    pub fn set_history_cap(&mut self, cap: Option<usize>) {
+        self.history.set_cap(cap);
+    }
+
+    /// only clock components downstream of changed outputs (sequential components,
+    /// components without inputs and components requesting it are always clocked),
+    /// the results are identical to clocking all components
+    pub fn set_event_driven(&mut self, event_driven: bool) {
+        self.event_driven = event_driven;
+        self.full_sweep = true;
+    }
+
     /// check if component is active
     pub fn is_active(&self, id: &Id) -> bool {
         self.active.contains(id)
@@ -442,8 +812,6 @@ impl Simulator {
         trace!("clock per run {}", i)
     }
 
-    pub fn run_threaded(&mut self) {}
-
     /// stop the simulator from gui or other external reason
     pub fn stop(&mut self) -> Result<(), SimulatorError> {
         if self.running_state != RunningState::Err {
@@ -455,28 +823,36 @@ impl Simulator {
     }
 
     /// reverse simulation using history if clock > 1
+    /// (and the previous cycle is still kept in a capped history)
     pub fn un_clock(&mut self) {
         if self.cycle > 1 {
-            let (state, active) = self.history.pop().unwrap();
+            let Some((state, entry)) = self.history.pop() else {
+                trace!("un_clock: cycle {} not kept in history", self.cycle - 1);
+                return;
+            };
             // set old state
             self.sim_state = state;
-            self.active = active;
+            self.active = (*entry.active).clone();
 
-            // to ensure that history length and cycle count complies
-            self.cycle = self.history.len();
-            // TODO add component_condition history pop
+            self.cycle -= 1;
 
-            self.component_condition = self.component_condition_history.pop().unwrap();
-            match self.running_state_history.pop().unwrap() {
+            self.component_condition = entry.component_condition;
+            match entry.running_state {
                 RunningState::Halt => self.running_state = RunningState::Halt,
                 RunningState::Err => self.running_state = RunningState::Err,
                 _ => self.running_state = RunningState::Stopped,
             };
 
-            // reverse eval order before uncloak
-            for component in self.ordered_components.clone().into_iter().rev() {
-                component.un_clock(self);
+            // reverse eval order before uncloak, skipping components not clocked
+            for index in (0..self.ordered_components.len()).rev() {
+                if entry.skipped.binary_search(&index).is_err() {
+                    let component = self.ordered_components[index].clone();
+                    self.current = Some(index);
+                    component.un_clock(self);
+                    self.current = None;
+                }
             }
+            self.full_sweep = true;
         }
     }
 
@@ -484,20 +860,21 @@ impl Simulator {
     pub fn reset(&mut self) {
         // The order of the following is not important
         // with the exception that self.clock() needs to be last
-        self.history = vec![];
+        self.history.clear();
         self.cycle = 0;
         self.running_state = RunningState::Stopped;
-        self.component_condition_history = vec![];
-        self.running_state_history = vec![];
         let _ = self.stop();
 
         self.sim_state.iter_mut().for_each(|val| *val = 0.into());
 
+        self.breakpoints.iter_mut().for_each(|b| b.hits = 0);
+
         // TODO probably needed to reset component_condition, maybe is handeld correctly by clock who knows?
-        for component in self.ordered_components.clone() {
+        for component in &self.ordered_components {
             component.reset();
         }
 
+        self.full_sweep = true;
         self.clock();
     }
 
@@ -563,6 +940,7 @@ impl Simulator {
 mod test {
     use super::*;
     use crate::components::*;
+    use crate::elaboration::LoopStep;
     use std::rc::Rc;
 
     #[test]
@@ -577,31 +955,253 @@ mod test {
     }
 
     #[test]
-    #[should_panic(expected = "Component identifier \"po1\" is defined twice")]
     fn test_redefined() {
         let cs = ComponentStore {
             store: vec![Rc::new(ProbeOut::new("po1")), Rc::new(ProbeOut::new("po1"))],
         };
 
-        let simulator = Simulator::new(cs).unwrap();
+        let err = Simulator::new(cs).err().unwrap();
 
-        assert_eq!(simulator.cycle, 1);
+        assert_eq!(
+            err.problems,
+            vec![ElaborationProblem::DuplicateId { id: "po1".into() }]
+        );
+        assert_eq!(
+            err.problems[0].to_string(),
+            "Component identifier \"po1\" is defined twice"
+        );
     }
 
     #[test]
-    #[should_panic(
-        expected = "Component r_2 read data from r_1. Sequential to sequential is not allowed, consider adding a pass trough component"
-    )]
     fn test_sequential_to_sequential() {
+        // shift register, each register reads the output of the previous cycle,
+        // independent of the order registers are clocked in
+        let cs = ComponentStore {
+            store: vec![
+                Register::rc_new("r_3", (0.0, 0.0), Input::new("r_2", REGISTER_OUT_ID)),
+                Register::rc_new("r_2", (0.0, 0.0), Input::new("r_1", REGISTER_OUT_ID)),
+                Register::rc_new("r_1", (0.0, 0.0), Input::new("in", "out")),
+                ProbeStim::rc_new("in", (0.0, 0.0), vec![1, 2, 3, 4, 5]),
+            ],
+        };
+        let json = serde_json::to_string(&cs).unwrap();
+        let mut simulator = Simulator::new(cs).unwrap();
+        let outputs = |simulator: &Simulator| {
+            ["r_1", "r_2", "r_3"]
+                .map(|id| simulator.get_input_value(&Input::new(id, REGISTER_OUT_ID)))
+        };
+        let mut values = vec![];
+        for _ in 0..4 {
+            simulator.clock();
+            values.push(outputs(&simulator));
+        }
+        assert_eq!(values[3], [4, 3, 2].map(SignalValue::from));
+
+        // the same in the reverse model order, and when event-driven
+        let mut cs = ComponentStore::load(&json).unwrap();
+        cs.store.reverse();
+        let mut simulator = Simulator::new(cs).unwrap();
+        simulator.set_event_driven(true);
+        for value in &values {
+            simulator.clock();
+            assert_eq!(&outputs(&simulator), value);
+        }
+        simulator.un_clock();
+        assert_eq!(outputs(&simulator), values[2]);
+    }
+
+    #[test]
+    fn test_all_problems() {
+        let cs = ComponentStore {
+            store: vec![
+                Constant::rc_new("c", (0.0, 0.0), 1),
+                Add::rc_new(
+                    "a1",
+                    (0.0, 0.0),
+                    Input::new("a2", ADD_OUT_ID),
+                    Input::new("missing", CONSTANT_OUT_ID),
+                ),
+                Add::rc_new(
+                    "a2",
+                    (0.0, 0.0),
+                    Input::new("a1", ADD_OUT_ID),
+                    Input::new("c", "missing"),
+                ),
+                Constant::rc_new("c", (0.0, 0.0), 2),
+            ],
+        };
+
+        let err = Simulator::new(cs).err().unwrap();
+
+        assert_eq!(
+            err.problems,
+            vec![
+                ElaborationProblem::DuplicateId { id: "c".into() },
+                ElaborationProblem::UnconnectedPort {
+                    id: "a1".into(),
+                    port_id: ADD_B_IN_ID.into(),
+                    input: Input::new("missing", CONSTANT_OUT_ID),
+                },
+                ElaborationProblem::UnconnectedPort {
+                    id: "a2".into(),
+                    port_id: ADD_B_IN_ID.into(),
+                    input: Input::new("c", "missing"),
+                },
+                ElaborationProblem::CombinationalLoop {
+                    path: vec![
+                        LoopStep {
+                            id: "a1".into(),
+                            port_id: ADD_A_IN_ID.into(),
+                            input: Input::new("a2", ADD_OUT_ID),
+                        },
+                        LoopStep {
+                            id: "a2".into(),
+                            port_id: ADD_A_IN_ID.into(),
+                            input: Input::new("a1", ADD_OUT_ID),
+                        },
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_combinational_loops() {
         let cs = ComponentStore {
             store: vec![
-                Register::rc_new("r_1", (0.0, 0.0), Input::new("pass", PASS_THROUGH_OUT_ID)),
-                Register::rc_new("r_2", (70.0, 0.0), Input::new("r_1", REGISTER_OUT_ID)),
-                PassThrough::rc_new("pass", (35.0, 35.0), Input::new("r_2", REGISTER_OUT_ID)),
+                Constant::rc_new("c", (0.0, 0.0), 1),
+                // self loop
+                Add::rc_new(
+                    "s",
+                    (0.0, 0.0),
+                    Input::new("c", CONSTANT_OUT_ID),
+                    Input::new("s", ADD_OUT_ID),
+                ),
+                // three component loop, entered from c
+                PassThrough::rc_new("p3", (0.0, 0.0), Input::new("p2", PASS_THROUGH_OUT_ID)),
+                PassThrough::rc_new("p2", (0.0, 0.0), Input::new("a", ADD_OUT_ID)),
+                Add::rc_new(
+                    "a",
+                    (0.0, 0.0),
+                    Input::new("c", CONSTANT_OUT_ID),
+                    Input::new("p3", PASS_THROUGH_OUT_ID),
+                ),
+                // reads the loop, but is not part of it
+                Rc::new(ProbeOut::new("po")),
+                PassThrough::rc_new("out", (0.0, 0.0), Input::new("a", ADD_OUT_ID)),
             ],
         };
 
-        let _simulator = Simulator::new(cs).unwrap();
+        let err = Simulator::new(cs).err().unwrap();
+        let loops: Vec<Vec<String>> = err
+            .loops()
+            .map(|path| path.iter().map(|step| step.id.clone()).collect())
+            .collect();
+        assert_eq!(
+            loops,
+            vec![
+                vec!["a".to_string(), "p2".into(), "p3".into()],
+                vec!["s".to_string()]
+            ]
+        );
+        assert_eq!(
+            err.problems[0].to_string(),
+            "Combinational loop: p3:pass_through_out -> a:b_in, a:add_out -> p2:pass_through_in, p2:pass_through_out -> p3:pass_through_in"
+        );
+    }
+
+    #[test]
+    fn test_event_driven() {
+        let model = || ComponentStore {
+            store: vec![
+                Constant::rc_new("c1", (0.0, 0.0), 1),
+                Constant::rc_new("c2", (0.0, 0.0), 2),
+                // never changes, only clocked in the first cycle
+                Add::rc_new(
+                    "sum",
+                    (0.0, 0.0),
+                    Input::new("c1", CONSTANT_OUT_ID),
+                    Input::new("c2", CONSTANT_OUT_ID),
+                ),
+                Register::rc_new("reg", (0.0, 0.0), Input::new("inc", ADD_OUT_ID)),
+                Add::rc_new(
+                    "inc",
+                    (0.0, 0.0),
+                    Input::new("reg", REGISTER_OUT_ID),
+                    Input::new("c1", CONSTANT_OUT_ID),
+                ),
+                // changes every second cycle
+                Register::rc_new("half", (0.0, 0.0), Input::new("sel", AND_OUT_ID)),
+                And::rc_new(
+                    "sel",
+                    (0.0, 0.0),
+                    Input::new("reg", REGISTER_OUT_ID),
+                    Input::new("c2", CONSTANT_OUT_ID),
+                ),
+                Mux::rc_new(
+                    "mux",
+                    (0.0, 0.0),
+                    Input::new("half", REGISTER_OUT_ID),
+                    vec![
+                        Input::new("sum", ADD_OUT_ID),
+                        Input::new("c1", CONSTANT_OUT_ID),
+                        Input::new("inc", ADD_OUT_ID),
+                    ],
+                ),
+                Rc::new(ProbeEdit::new("edit", (0.0, 0.0))),
+            ],
+        };
+        let mut full = Simulator::new(model()).unwrap();
+        let mut event = Simulator::new(model()).unwrap();
+        event.set_event_driven(true);
+
+        for _ in 0..16 {
+            full.clock();
+            event.clock();
+            assert_eq!(event.sim_state, full.sim_state);
+            assert_eq!(event.component_condition, full.component_condition);
+            assert_eq!(event.active, full.active);
+        }
+        let sum = event.component_ids.iter().position(|id| id == "sum");
+        let entry = event.history.get_entry(event.cycle - 1).unwrap();
+        assert!(entry.skipped.contains(&sum.unwrap()));
+
+        for _ in 0..4 {
+            full.un_clock();
+            event.un_clock();
+            assert_eq!(event.sim_state, full.sim_state);
+        }
+        for _ in 0..4 {
+            full.clock();
+            event.clock();
+            assert_eq!(event.sim_state, full.sim_state);
+        }
+    }
+
+    #[test]
+    fn test_input_handles() {
+        let cs = ComponentStore {
+            store: vec![
+                Constant::rc_new("c", (0.0, 0.0), 3),
+                Register::rc_new("reg", (0.0, 0.0), Input::new("c", CONSTANT_OUT_ID)),
+            ],
+        };
+        let mut simulator = Simulator::new(cs).unwrap();
+        simulator.clock();
+
+        let handle = simulator
+            .resolve_input(&Input::new("reg", REGISTER_OUT_ID))
+            .unwrap();
+        assert_eq!(simulator.get_handle_value(handle), 3.into());
+        assert!(simulator
+            .resolve_input(&Input::new("reg", "missing"))
+            .is_none());
+        // only available while clocking a component
+        assert!(simulator.input_handles().is_empty());
+
+        assert!(simulator.get_component::<Register>("reg").is_some());
+        assert!(simulator.get_component::<Constant>("reg").is_none());
+        assert!(simulator.get_component::<Register>("missing").is_none());
     }
 
     #[test]
@@ -629,6 +1229,33 @@ mod test {
         let _ = simulator.get_input_value(&Input::new("po1", "missing"));
     }
 
+    #[test]
+    fn test_history_cap() {
+        let cs = ComponentStore {
+            store: vec![ProbeStim::rc_new("stim", (0.0, 0.0), vec![0, 1, 2, 3, 4])],
+        };
+
+        let mut simulator = Simulator::new(cs).unwrap();
+        simulator.set_history_cap(Some(2));
+        let out = &Input::new("stim", "out");
+
+        for _ in 0..4 {
+            simulator.clock();
+        }
+        assert_eq!(simulator.cycle, 5);
+        assert_eq!(simulator.history.first_cycle(), 3);
+
+        simulator.un_clock();
+        simulator.un_clock();
+        assert_eq!(simulator.cycle, 3);
+        assert_eq!(simulator.get_input_value(out), 2.into());
+
+        // cycle 2 is no longer kept
+        simulator.un_clock();
+        assert_eq!(simulator.cycle, 3);
+        assert_eq!(simulator.get_input_value(out), 2.into());
+    }
+
     #[test]
     fn test_get_input_fmt() {
         let cs = ComponentStore {
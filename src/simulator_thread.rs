@@ -0,0 +1,471 @@
+// Simulation on a worker thread, used by the GUI to free-run independent of the repaint rate
+//
+// Components share internal state through `Rc`/`RefCell` and can not be sent to another
+// thread. Instead the worker builds its own simulator from the serialized model, restored
+// from a snapshot of the simulator it was started from. While running, the worker sends
+// frames with the simulator state (at most one per `FRAME_INTERVAL`). When it stops (paused,
+// halted or the target cycle reached) it sends a snapshot including the internal state of
+// components and the history of the cycles it ran, appended to the history kept, and exits.
+//
+// State edited in the GUI while running (`ProbeEdit` values and the `gui_state` of components,
+// e.g., breakpoints) is sent to the worker. Views of component internal state (e.g., register
+// file or memory contents) show the state the worker was started from until it stops.
+use crate::breakpoint::Breakpoint;
+use crate::common::{ComponentStore, Condition, Id, RunningState, Signal, Simulator};
+#[cfg(feature = "components")]
+use crate::components::{ProbeEdit, TextSignal};
+use crate::coverage::Coverage;
+use crate::profiler::ComponentTiming;
+use crate::snapshot::SimulatorSnapshot;
+use log::*;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+pub const FRAME_INTERVAL: Duration = Duration::from_millis(1000 / 60);
+
+/// Commands sent to the worker
+#[derive(Clone, Debug)]
+pub enum Command {
+    /// stop running
+    Pause,
+    /// stop running and clock one cycle
+    Step,
+    /// run until the target cycle
+    StepTo(usize),
+    /// reset the simulator, which stops it
+    Reset,
+    /// set the value of a `ProbeEdit` for the next cycle
+    #[cfg(feature = "components")]
+    Edit { id: Id, value: TextSignal },
+    /// restore the `gui_state` of a component
+    GuiState { id: Id, state: serde_json::Value },
+}
+
+/// Simulator state sent by the worker while running
+#[derive(Clone, Debug)]
+pub struct SimulatorFrame {
+    pub cycle: usize,
+    pub sim_state: Vec<Signal>,
+    pub running_state: RunningState,
+    pub component_condition: Vec<(Id, Condition)>,
+    pub active: HashSet<Id>,
+    pub profile: Option<Vec<ComponentTiming>>,
+}
+
+/// Updates sent by the worker
+#[derive(Debug)]
+pub enum Update {
+    Frame(SimulatorFrame),
+    // the worker stopped with its breakpoints (hit counts), profile and coverage,
+    // last update sent
+    Stopped(
+        Box<SimulatorSnapshot>,
+        Vec<Breakpoint>,
+        Option<Vec<ComponentTiming>>,
+        Option<Box<Coverage>>,
+    ),
+    // the worker failed to set up its simulator, last update sent
+    Error(String),
+}
+
+#[derive(Debug)]
+pub enum SimulatorThreadError {
+    Model(serde_json::Error),
+    Spawn(std::io::Error),
+    Worker(String),
+}
+
+impl Display for SimulatorThreadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimulatorThreadError::Model(error) => {
+                write!(f, "Error while encoding model: {}", error)
+            }
+            SimulatorThreadError::Spawn(error) => {
+                write!(f, "Error while starting simulator thread: {}", error)
+            }
+            SimulatorThreadError::Worker(error) => {
+                write!(f, "Error in simulator thread: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SimulatorThreadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SimulatorThreadError::Model(error) => Some(error),
+            SimulatorThreadError::Spawn(error) => Some(error),
+            SimulatorThreadError::Worker(_) => None,
+        }
+    }
+}
+
+/// Handle to a simulator running on a worker thread
+pub struct SimulatorThread {
+    commands: Sender<Command>,
+    updates: Receiver<Update>,
+    handle: Option<JoinHandle<()>>,
+    // last ProbeEdit values sent, to forward edits made on the started from simulator
+    #[cfg(feature = "components")]
+    edits: HashMap<Id, TextSignal>,
+    // last gui state sent of each component, to forward changes as edits
+    gui_states: HashMap<Id, serde_json::Value>,
+}
+
+impl Simulator {
+    /// continue the simulation on a worker thread, see `SimulatorThread`
+    pub fn run_threaded(&self) -> Result<SimulatorThread, SimulatorThreadError> {
+        SimulatorThread::spawn(self)
+    }
+
+    // update the state from a frame, component internal state is updated when stopped
+    fn apply_frame(&mut self, frame: SimulatorFrame) {
+        self.cycle = frame.cycle;
+        self.sim_state = frame.sim_state;
+        self.running_state = frame.running_state;
+        self.component_condition = frame.component_condition;
+        self.active = frame.active;
+        self.profile = frame.profile;
+    }
+}
+
+impl SimulatorThread {
+    /// start a worker continuing from the simulator state
+    pub fn spawn(simulator: &Simulator) -> Result<Self, SimulatorThreadError> {
+        // the model order (graph nodes) gives the same sim_state layout in the worker
+        let store = simulator
+            .graph
+            .node_weights()
+            .map(|id| simulator.ordered_components[simulator.component_index[id]].clone())
+            .collect();
+        let model = serde_json::to_string(&ComponentStore { store })
+            .map_err(SimulatorThreadError::Model)?;
+        // component internal state includes what is needed to un_clock past the start
+        let snapshot = simulator.snapshot_state(true);
+        let running_state = simulator.running_state.clone();
+        let event_driven = simulator.event_driven;
+        let halt_on_warning = simulator.halt_on_warning;
+        let breakpoints = simulator.breakpoints.clone();
+        let clock_domains = simulator.clock_domains.clone();
+        let component_domain = simulator.component_domain.clone();
+        let profile = simulator.profile.clone();
+        let coverage = simulator.coverage.clone();
+        let faults = simulator.faults.clone();
+        let gui_states = gui_states(simulator);
+        let worker_gui_states = gui_states.clone();
+        // the history is not sent, the worker starts its own at the current cycle
+        let history_cap = simulator.history.cap();
+
+        let (commands, command_rx) = mpsc::channel();
+        let (update_tx, updates) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name("simulator".into())
+            .spawn(move || {
+                let mut simulator = match ComponentStore::load(&model)
+                    .map_err(|err| err.to_string())
+                    .and_then(|cs| Simulator::new(cs).map_err(|err| err.to_string()))
+                {
+                    Ok(simulator) => simulator,
+                    Err(err) => {
+                        let _ = update_tx.send(Update::Error(err));
+                        return;
+                    }
+                };
+                if let Err(err) = simulator.restore(snapshot) {
+                    let _ = update_tx.send(Update::Error(err.to_string()));
+                    return;
+                }
+                simulator.running_state = running_state;
+                simulator.event_driven = event_driven;
+                simulator.halt_on_warning = halt_on_warning;
+                simulator.breakpoints = breakpoints;
+                simulator.clock_domains = clock_domains;
+                simulator.component_domain = component_domain;
+                simulator.profile = profile;
+                simulator.coverage = coverage;
+                simulator.faults = faults;
+                simulator.set_history_cap(history_cap);
+                for (id, state) in worker_gui_states {
+                    handle(&mut simulator, Command::GuiState { id, state });
+                }
+                worker(simulator, command_rx, update_tx);
+            })
+            .map_err(SimulatorThreadError::Spawn)?;
+
+        Ok(SimulatorThread {
+            commands,
+            updates,
+            handle: Some(handle),
+            #[cfg(feature = "components")]
+            edits: probe_edits(simulator),
+            gui_states,
+        })
+    }
+
+    /// send command to the worker, ignored if the worker has stopped
+    pub fn send(&self, command: Command) {
+        let _ = self.commands.send(command);
+    }
+
+    /// forward edits and apply the updates received to the simulator the worker was started from
+    /// returns true when the worker has stopped, the simulator then holds its final state
+    pub fn poll(&mut self, simulator: &mut Simulator) -> Result<bool, SimulatorThreadError> {
+        #[cfg(feature = "components")]
+        for (id, value) in probe_edits(simulator) {
+            if self.edits.get(&id) != Some(&value) {
+                self.send(Command::Edit {
+                    id: id.clone(),
+                    value: value.clone(),
+                });
+                self.edits.insert(id, value);
+            }
+        }
+        for (id, state) in gui_states(simulator) {
+            if self.gui_states.get(&id) != Some(&state) {
+                self.send(Command::GuiState {
+                    id: id.clone(),
+                    state: state.clone(),
+                });
+                self.gui_states.insert(id, state);
+            }
+        }
+
+        let mut frame = None;
+        loop {
+            match self.updates.try_recv() {
+                Ok(Update::Frame(f)) => frame = Some(f),
+                Ok(Update::Stopped(snapshot, breakpoints, profile, coverage)) => {
+                    self.join();
+                    simulator.breakpoints = breakpoints;
+                    simulator.profile = profile;
+                    simulator.coverage = coverage.map(|coverage| *coverage);
+                    let mut snapshot = *snapshot;
+                    let worker_history = snapshot.history.take();
+                    let mut history = std::mem::take(&mut simulator.history);
+                    simulator
+                        .restore(snapshot)
+                        .map_err(|err| SimulatorThreadError::Worker(format!("restore: {}", err)))?;
+                    if let Some(worker_history) = worker_history {
+                        history.append(worker_history);
+                    }
+                    simulator.history = history;
+                    return Ok(true);
+                }
+                Ok(Update::Error(err)) => {
+                    self.join();
+                    return Err(SimulatorThreadError::Worker(err));
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.join();
+                    return Err(SimulatorThreadError::Worker("stopped unexpectedly".into()));
+                }
+            }
+        }
+        if let Some(frame) = frame {
+            simulator.apply_frame(frame);
+        }
+        Ok(false)
+    }
+
+    /// wait for the worker to stop (after sending `Pause`, `Step` or `Reset`,
+    /// or when the target cycle is reached), the simulator then holds its final state
+    pub fn finish(mut self, simulator: &mut Simulator) -> Result<(), SimulatorThreadError> {
+        loop {
+            if self.poll(simulator)? {
+                return Ok(());
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn join(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            if handle.join().is_err() {
+                error!("simulator thread panicked");
+            }
+        }
+    }
+}
+
+impl Drop for SimulatorThread {
+    fn drop(&mut self) {
+        self.send(Command::Pause);
+        self.join();
+    }
+}
+
+// next value of each ProbeEdit
+#[cfg(feature = "components")]
+fn probe_edits(simulator: &Simulator) -> HashMap<Id, TextSignal> {
+    simulator
+        .ordered_components
+        .iter()
+        .filter_map(|c| c.as_any().downcast_ref::<ProbeEdit>())
+        .map(|p| (p.id.clone(), p.next_value()))
+        .collect()
+}
+
+// gui state of each component having one
+fn gui_states(simulator: &Simulator) -> HashMap<Id, serde_json::Value> {
+    simulator
+        .ordered_components
+        .iter()
+        .filter_map(|c| Some((c.get_id_ports().0, c.gui_state()?)))
+        .collect()
+}
+
+fn worker(mut simulator: Simulator, commands: Receiver<Command>, updates: Sender<Update>) {
+    trace!("simulator thread started at cycle {}", simulator.cycle);
+    let mut last_frame = Instant::now();
+    while simulator.is_running() {
+        match simulator.running_state {
+            RunningState::StepTo(target_cycle) if simulator.cycle >= target_cycle => {
+                simulator.running_state = RunningState::Stopped;
+            }
+            _ => simulator.clock(),
+        }
+
+        if last_frame.elapsed() < FRAME_INTERVAL && simulator.is_running() {
+            continue;
+        }
+        last_frame = Instant::now();
+        loop {
+            match commands.try_recv() {
+                Ok(command) => handle(&mut simulator, command),
+                Err(TryRecvError::Empty) => break,
+                // nobody is listening
+                Err(TryRecvError::Disconnected) => return,
+            }
+        }
+        let frame = SimulatorFrame {
+            cycle: simulator.cycle,
+            sim_state: simulator.sim_state.clone(),
+            running_state: simulator.running_state.clone(),
+            component_condition: simulator.component_condition.clone(),
+            active: simulator.active.clone(),
+            profile: simulator.profile.clone(),
+        };
+        if updates.send(Update::Frame(frame)).is_err() {
+            return;
+        }
+    }
+    // commands sent before the worker noticed it stopped
+    while let Ok(command) = commands.try_recv() {
+        handle(&mut simulator, command);
+    }
+    trace!("simulator thread stopped at cycle {}", simulator.cycle);
+    let mut snapshot = simulator.snapshot_state(true);
+    snapshot.history = Some(std::mem::take(&mut simulator.history));
+    let _ = updates.send(Update::Stopped(
+        Box::new(snapshot),
+        simulator.breakpoints,
+        simulator.profile,
+        simulator.coverage.map(Box::new),
+    ));
+}
+
+fn handle(simulator: &mut Simulator, command: Command) {
+    trace!("simulator thread command {:?}", command);
+    match command {
+        Command::Pause => {
+            let _ = simulator.stop();
+        }
+        Command::Step => {
+            let _ = simulator.stop();
+            simulator.clock();
+        }
+        Command::StepTo(target_cycle) => {
+            let _ = simulator.set_step_to(target_cycle);
+        }
+        Command::Reset => simulator.reset(),
+        #[cfg(feature = "components")]
+        Command::Edit { id, value } => {
+            if let Some(probe_edit) = simulator.get_component::<ProbeEdit>(&id) {
+                probe_edit.set_next_value(value);
+            }
+        }
+        Command::GuiState { id, state } => {
+            if let Some(index) = simulator.component_index.get(&id) {
+                if let Err(err) = simulator.ordered_components[*index].restore_gui_state(&state) {
+                    error!("{}: {}", id, err);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::components::*;
+    use crate::fixtures::accumulator;
+
+    #[test]
+    fn test_run_threaded() {
+        let mut simulator = Simulator::new(accumulator()).unwrap();
+        let mut expected = Simulator::new(accumulator()).unwrap();
+        for simulator in [&mut simulator, &mut expected] {
+            let edit = simulator.get_component::<ProbeEdit>("in").unwrap();
+            edit.set_next_value(TextSignal {
+                text: "2".into(),
+                signal: 2.into(),
+            });
+        }
+
+        simulator.set_step_to(1000).ok();
+        simulator
+            .run_threaded()
+            .unwrap()
+            .finish(&mut simulator)
+            .unwrap();
+        while expected.cycle < 1000 {
+            expected.clock();
+        }
+        assert_eq!(simulator.cycle, 1000);
+        assert_eq!(simulator.running_state, RunningState::Stopped);
+        assert_eq!(simulator.sim_state, expected.sim_state);
+
+        // history and component state are brought back
+        simulator.un_clock();
+        expected.un_clock();
+        assert_eq!(simulator.sim_state, expected.sim_state);
+        simulator.clock();
+        expected.clock();
+        assert_eq!(simulator.sim_state, expected.sim_state);
+    }
+
+    #[test]
+    fn test_run_threaded_commands() {
+        let mut simulator = Simulator::new(accumulator()).unwrap();
+        simulator.set_running().ok();
+        let mut thread = simulator.run_threaded().unwrap();
+
+        let edit = simulator.get_component::<ProbeEdit>("in").unwrap();
+        let value = TextSignal {
+            text: "3".into(),
+            signal: 3.into(),
+        };
+        edit.set_next_value(value.clone());
+        // forwards the edit
+        thread.poll(&mut simulator).unwrap();
+        thread.send(Command::Step);
+        thread.finish(&mut simulator).unwrap();
+
+        assert_eq!(simulator.running_state, RunningState::Stopped);
+        let edit = simulator.get_component::<ProbeEdit>("in").unwrap();
+        assert_eq!(edit.next_value(), value);
+
+        simulator.set_running().ok();
+        let thread = simulator.run_threaded().unwrap();
+        thread.send(Command::Reset);
+        thread.finish(&mut simulator).unwrap();
+        assert_eq!(simulator.cycle, 1);
+    }
+}
@@ -0,0 +1,316 @@
+// Snapshots of a running simulator, saved to file to resume the simulation later
+//
+// A snapshot holds the simulator state, together with the internal state of components
+// (see `Component::snapshot`), and optionally the history. It can only be restored into
+// a simulator created from the same model.
+use crate::common::{Condition, Id, RunningState, Signal, Simulator};
+use crate::history::History;
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Display,
+    fs::File,
+    io::{prelude::*, BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SimulatorSnapshot {
+    pub cycle: usize,
+    pub sim_state: Vec<Signal>,
+    pub running_state: RunningState,
+    pub component_condition: Vec<(Id, Condition)>,
+    pub active: HashSet<Id>,
+    // None if taken without history, restored as an empty history starting at the snapshot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<History>,
+    // component id -> internal state, for components having internal state
+    pub components: BTreeMap<Id, serde_json::Value>,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Json(serde_json::Error),
+    Io(std::io::Error, PathBuf),
+    // the snapshot was taken from another model
+    Mismatch(String),
+    // a component failed to restore its internal state
+    Component(Id, String),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Json(error) => {
+                write!(f, "Error while decoding snapshot: {}", error)
+            }
+            SnapshotError::Io(error, path) => {
+                write!(f, "Error while accessing file {:?} : {}", path, error)
+            }
+            SnapshotError::Mismatch(reason) => {
+                write!(f, "Snapshot does not match the model: {}", reason)
+            }
+            SnapshotError::Component(id, error) => {
+                write!(f, "Error while restoring component {}: {}", id, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnapshotError::Json(error) => Some(error),
+            SnapshotError::Io(error, _) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+impl Simulator {
+    /// take a snapshot of the simulator, including component internal state but not the history
+    pub fn snapshot(&self) -> SimulatorSnapshot {
+        self.snapshot_state(false)
+    }
+
+    /// take a snapshot of the simulator, including the history
+    pub fn snapshot_with_history(&self) -> SimulatorSnapshot {
+        SimulatorSnapshot {
+            history: Some(self.history.clone()),
+            ..self.snapshot_state(true)
+        }
+    }
+
+    // snapshot without the simulator history,
+    // the component internal state includes what is needed to un_clock if `history` is set
+    pub(crate) fn snapshot_state(&self, history: bool) -> SimulatorSnapshot {
+        let components = self
+            .ordered_components
+            .iter()
+            .filter_map(|c| Some((c.get_id_ports().0, c.snapshot(history)?)))
+            .collect();
+
+        SimulatorSnapshot {
+            cycle: self.cycle,
+            sim_state: self.sim_state.clone(),
+            running_state: self.running_state.clone(),
+            component_condition: self.component_condition.clone(),
+            active: self.active.clone(),
+            history: None,
+            components,
+        }
+    }
+
+    /// restore a snapshot taken from a simulator of the same model,
+    /// a running simulation is restored as stopped
+    /// if a component fails to restore its internal state, the simulator is reset
+    pub fn restore(&mut self, snapshot: SimulatorSnapshot) -> Result<(), SnapshotError> {
+        if snapshot.sim_state.len() != self.sim_state.len() {
+            return Err(SnapshotError::Mismatch(format!(
+                "{} outputs in snapshot, {} in model",
+                snapshot.sim_state.len(),
+                self.sim_state.len()
+            )));
+        }
+        if let Some(id) = snapshot
+            .components
+            .keys()
+            .find(|id| !self.component_ids.contains(id))
+        {
+            return Err(SnapshotError::Mismatch(format!(
+                "component {} not found in model",
+                id
+            )));
+        }
+
+        for component in self.ordered_components.clone() {
+            let id = component.get_id_ports().0;
+            let res = match snapshot.components.get(&id) {
+                Some(state) => component.restore(state),
+                // no internal state saved, start over
+                None => {
+                    component.reset();
+                    Ok(())
+                }
+            };
+            if let Err(err) = res {
+                error!("restore {}: {}", id, err);
+                self.reset();
+                return Err(SnapshotError::Component(id, err));
+            }
+        }
+
+        self.cycle = snapshot.cycle;
+        self.sim_state = snapshot.sim_state;
+        self.running_state = match snapshot.running_state {
+            RunningState::Halt => RunningState::Halt,
+            RunningState::Err => RunningState::Err,
+            _ => RunningState::Stopped,
+        };
+        self.component_condition = snapshot.component_condition;
+        self.active = snapshot.active;
+        match snapshot.history {
+            Some(history) => self.history = history,
+            None => self.history.restart(self.cycle),
+        }
+        self.clean_active();
+        self.full_sweep = true;
+        trace!("restored snapshot at cycle {}", self.cycle);
+        Ok(())
+    }
+
+    /// save a snapshot of the simulator as a json file, with the history if `history` is set
+    pub fn save_snapshot(&self, path: &Path, history: bool) -> Result<(), SnapshotError> {
+        let snapshot = match history {
+            true => self.snapshot_with_history(),
+            false => self.snapshot(),
+        };
+        let file = File::create(path).map_err(|err| SnapshotError::Io(err, path.into()))?;
+        let mut w = BufWriter::new(file);
+        serde_json::to_writer(&mut w, &snapshot)?;
+        w.flush().map_err(|err| SnapshotError::Io(err, path.into()))
+    }
+
+    /// restore a snapshot saved by `save_snapshot`
+    pub fn load_snapshot(&mut self, path: &Path) -> Result<(), SnapshotError> {
+        let file = File::open(path).map_err(|err| SnapshotError::Io(err, path.into()))?;
+        let snapshot = serde_json::from_reader(BufReader::new(file))?;
+        self.restore(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{ComponentStore, Input};
+    use crate::components::*;
+    use crate::fixtures::counter;
+    use std::rc::Rc;
+
+    // counter writing its value to memory each cycle
+    fn model() -> ComponentStore {
+        let mut cs = counter(1);
+        cs.store.push(Rc::new(ProbeEdit::new("edit", (0.0, 0.0))));
+        cs.store
+            .push(Constant::rc_new("ctrl", (0.0, 0.0), MemCtrl::Write as u32));
+        cs.store.push(Constant::rc_new("size", (0.0, 0.0), 4));
+        cs.store.push(Constant::rc_new("sext", (0.0, 0.0), false));
+        cs.store.push(Mem::rc_new(
+            "mem",
+            (0.0, 0.0),
+            0.0,
+            0.0,
+            false,
+            Input::new("reg", REGISTER_OUT_ID),
+            Input::new("edit", PROBE_EDIT_OUT_ID),
+            Input::new("ctrl", CONSTANT_OUT_ID),
+            Input::new("sext", CONSTANT_OUT_ID),
+            Input::new("size", CONSTANT_OUT_ID),
+            0..16,
+        ));
+        cs
+    }
+
+    fn memory(simulator: &Simulator) -> BTreeMap<usize, u8> {
+        let mem = simulator
+            .ordered_components
+            .iter()
+            .find_map(|c| c.as_any().downcast_ref::<Mem>())
+            .unwrap();
+        mem.memory.0.borrow().clone()
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut simulator = Simulator::new(model()).unwrap();
+        for _ in 0..4 {
+            simulator.clock();
+        }
+        let json = serde_json::to_string(&simulator.snapshot_with_history()).unwrap();
+        let expected_memory = memory(&simulator);
+        let expected_state = simulator.sim_state.clone();
+
+        // continue in a fresh simulator of the same model
+        let mut restored = Simulator::new(model()).unwrap();
+        restored
+            .restore(serde_json::from_str(&json).unwrap())
+            .unwrap();
+        assert_eq!(restored.cycle, 5);
+        assert_eq!(restored.sim_state, expected_state);
+        assert_eq!(memory(&restored), expected_memory);
+
+        simulator.clock();
+        restored.clock();
+        assert_eq!(restored.sim_state, simulator.sim_state);
+        assert_eq!(memory(&restored), memory(&simulator));
+
+        // history is restored, allowing reverse simulation past the snapshot
+        restored.un_clock();
+        restored.un_clock();
+        simulator.un_clock();
+        simulator.un_clock();
+        assert_eq!(restored.cycle, 4);
+        assert_eq!(restored.sim_state, simulator.sim_state);
+
+        // without history, reverse simulation stops at the snapshot
+        let snapshot = simulator.snapshot();
+        assert!(snapshot.history.is_none());
+        assert!(!serde_json::to_string(&snapshot)
+            .unwrap()
+            .contains("history"));
+        // the ProbeEdit keeps only what is needed to un_clock back to the snapshot
+        assert_eq!(snapshot.components["edit"].as_array().unwrap().len(), 2);
+        let mut restored = Simulator::new(model()).unwrap();
+        restored.restore(snapshot).unwrap();
+        assert_eq!(restored.history.first_cycle(), 4);
+        restored.clock();
+        restored.un_clock();
+        restored.un_clock();
+        assert_eq!(restored.cycle, 4);
+        assert_eq!(restored.sim_state, simulator.sim_state);
+    }
+
+    #[test]
+    fn test_save_snapshot_history() {
+        let mut simulator = Simulator::new(model()).unwrap();
+        for _ in 0..4 {
+            simulator.clock();
+        }
+        let dir = std::env::temp_dir().join(format!("syncrim_snapshot_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("snapshot.json");
+
+        for history in [false, true] {
+            simulator.save_snapshot(&file, history).unwrap();
+            let mut restored = Simulator::new(model()).unwrap();
+            restored.load_snapshot(&file).unwrap();
+            assert_eq!(restored.sim_state, simulator.sim_state);
+            restored.un_clock();
+            match history {
+                true => assert_eq!(restored.cycle, 4),
+                false => assert_eq!(restored.cycle, 5),
+            }
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_mismatch() {
+        let simulator = Simulator::new(model()).unwrap();
+        let mut other = Simulator::new(ComponentStore {
+            store: vec![Constant::rc_new("one", (0.0, 0.0), 1)],
+        })
+        .unwrap();
+        assert!(matches!(
+            other.restore(simulator.snapshot()),
+            Err(SnapshotError::Mismatch(_))
+        ));
+    }
+}
@@ -0,0 +1,142 @@
+// Stimulus recorded from the ProbeEdit values of a session, to replay it as a deterministic test
+//
+// The values output by each ProbeEdit up to the current cycle are exported as test vectors, a row
+// for the first and last cycle and each cycle a value was edited. A session is replayed by the
+// model with its ProbeEdit components replaced by ProbeStim components of the same id, outputting
+// the values inline (`Simulator::replay_model`) or read from a saved stimulus file
+// (`ComponentStore::with_stimulus`, or `--stimulus` on the command line).
+use crate::common::{ComponentStore, Components, Signal, Simulator};
+use crate::components::{ProbeEdit, ProbeStim, TestVectors, VectorFile};
+use std::{fs, path::Path, rc::Rc};
+
+impl Simulator {
+    // each ProbeEdit with its values output in the cycles before the current one
+    fn probe_edit_values(&self) -> Vec<(&ProbeEdit, Vec<Signal>)> {
+        self.ordered_components
+            .iter()
+            .filter_map(|c| c.as_any().downcast_ref::<ProbeEdit>())
+            .map(|probe_edit| (probe_edit, probe_edit.output_history(self.cycle)))
+            .collect()
+    }
+
+    /// the values output by each ProbeEdit, from cycle 0 up to the current cycle
+    pub fn stimulus(&self) -> TestVectors {
+        let probe_edits = self.probe_edit_values();
+        let mut vectors = TestVectors::default();
+        for (probe_edit, _) in &probe_edits {
+            vectors.columns.insert(probe_edit.id.clone(), vec![]);
+        }
+        // only values changed are given, held by the ProbeStim in the cycles between
+        let changed = |values: &[Signal], cycle: usize| {
+            let value = values.get(cycle).map(Signal::get_value);
+            (cycle == 0 || value != values.get(cycle - 1).map(Signal::get_value))
+                .then_some(value)
+                .flatten()
+        };
+        for cycle in 0..self.cycle {
+            let last = cycle + 1 == self.cycle;
+            if !last
+                && probe_edits
+                    .iter()
+                    .all(|(_, values)| changed(values, cycle).is_none())
+            {
+                continue;
+            }
+            vectors.cycles.push(cycle);
+            for (probe_edit, values) in &probe_edits {
+                vectors
+                    .columns
+                    .get_mut(&probe_edit.id)
+                    .unwrap()
+                    .push(changed(values, cycle));
+            }
+        }
+        vectors
+    }
+
+    /// save the stimulus as CSV, replayed by `ComponentStore::with_stimulus`
+    pub fn save_stimulus(&self, path: &Path) -> std::io::Result<()> {
+        fs::write(path, self.stimulus().to_csv())
+    }
+
+    /// the (flattened) model replaying the session up to the current cycle,
+    /// with the ProbeEdit components replaced by ProbeStim components
+    pub fn replay_model(&self) -> ComponentStore {
+        let store = self
+            .graph
+            .node_weights()
+            .map(|id| {
+                let c = &self.ordered_components[self.component_index[id]];
+                match c.as_any().downcast_ref::<ProbeEdit>() {
+                    Some(probe_edit) => Rc::new(ProbeStim::new(
+                        &probe_edit.id,
+                        probe_edit.pos,
+                        probe_edit.output_history(self.cycle),
+                    )),
+                    None => c.clone(),
+                }
+            })
+            .collect();
+        ComponentStore { store }
+    }
+}
+
+impl ComponentStore {
+    /// the model with its (top level) ProbeEdit components replaced by ProbeStim components,
+    /// reading the column of their id from the stimulus `file`, e.g., saved by `save_stimulus`
+    pub fn with_stimulus(self, file: &Path) -> Self {
+        let store: Components = self
+            .store
+            .into_iter()
+            .map(|c| match c.as_any().downcast_ref::<ProbeEdit>() {
+                Some(probe_edit) => Rc::new(ProbeStim::new_with_vectors(
+                    &probe_edit.id,
+                    probe_edit.pos,
+                    VectorFile::new(file, None),
+                )),
+                None => c,
+            })
+            .collect();
+        ComponentStore { store }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::Input;
+    use crate::components::*;
+    use crate::fixtures::accumulator;
+    use crate::testbench::Testbench;
+
+    #[test]
+    fn test_stimulus() {
+        // a session, editing the value in cycles 1, 3 and 4
+        let mut tb = Testbench::new(accumulator()).unwrap();
+        for (value, cycles) in [(2, 2), (5, 1), (0, 2)] {
+            tb.poke("in", value).unwrap();
+            tb.step(cycles).unwrap();
+        }
+        let session = tb.simulator;
+        let acc = Input::new("acc", REGISTER_OUT_ID);
+        assert_eq!(session.get_input_value(&acc), 9.into());
+        assert_eq!(
+            session.stimulus().to_csv(),
+            "cycle,in\n0,0x0\n1,0x2\n3,0x5\n4,0x0\n5,\n"
+        );
+
+        let dir = std::env::temp_dir().join(format!("syncrim_stimulus_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("stimulus.csv");
+        session.save_stimulus(&file).unwrap();
+        for replay in [session.replay_model(), accumulator().with_stimulus(&file)] {
+            let mut simulator = Simulator::new(replay).unwrap();
+            while simulator.cycle < session.cycle {
+                simulator.clock();
+            }
+            assert_eq!(simulator.get_input_value(&acc), 9.into());
+            assert!(simulator.get_component_condition().is_none());
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
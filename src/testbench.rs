@@ -0,0 +1,283 @@
+// Testbench, driving and checking a model from Rust instead of by ProbeStim/ProbeAssert vectors
+//
+// Inputs are driven by `ProbeEdit` components of the model, a value poked is output from the
+// next step on. Failures are reported with the cycle and signal names, formatted as by the
+// signal format, also when unwrapped, e.g.,
+// `cycle 5: add.add_out expected 0x10, found 0xf (cycle 2: 0xc, cycle 3: 0xd, cycle 4: 0xe)`.
+use crate::common::{
+    ComponentStore, Condition, Id, Input, InputHandle, Signal, SignalValue, Simulator,
+};
+use crate::components::{ProbeEdit, TextSignal};
+use crate::elaboration::ElaborationError;
+use std::fmt;
+
+// number of past cycles shown for a mismatching signal
+const TRACE_CYCLES: usize = 3;
+
+pub struct Testbench {
+    pub simulator: Simulator,
+}
+
+/// The output did not have the expected value, with its values in past cycles
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub cycle: usize,
+    pub input: Input,
+    pub expected: Signal,
+    pub actual: Signal,
+    pub trace: Vec<(usize, Signal)>,
+}
+
+pub enum TestbenchFailure {
+    Mismatch(Box<Mismatch>),
+    // a component reported an error or failed assertion
+    Condition {
+        cycle: usize,
+        id: Id,
+        condition: Condition,
+    },
+    // run_until predicate did not hold within the cycles given
+    Timeout {
+        cycle: usize,
+        cycles: usize,
+    },
+    // poke of a component that is not a ProbeEdit
+    NotProbeEdit {
+        id: Id,
+    },
+}
+
+impl fmt::Display for TestbenchFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TestbenchFailure::Mismatch(mismatch) => {
+                let Mismatch {
+                    cycle,
+                    input,
+                    expected,
+                    actual,
+                    trace,
+                } = mismatch.as_ref();
+                write!(
+                    f,
+                    "cycle {}: {}.{} expected {}, found {}",
+                    cycle, input.id, input.field, expected, actual
+                )?;
+                for (i, (cycle, signal)) in trace.iter().enumerate() {
+                    let sep = if i == 0 { " (" } else { ", " };
+                    write!(f, "{}cycle {}: {}", sep, cycle, signal)?;
+                }
+                if !trace.is_empty() {
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+            TestbenchFailure::Condition {
+                cycle,
+                id,
+                condition,
+            } => {
+                let (kind, message) = match condition {
+                    Condition::Warning(message) => ("warning", message),
+                    Condition::Halt(message) => ("halt", message),
+                    Condition::Assert(message) => ("assertion failed", message),
+                    Condition::Error(message) => ("error", message),
+                };
+                write!(f, "cycle {}: {} {}: {}", cycle, id, kind, message)
+            }
+            TestbenchFailure::Timeout { cycle, cycles } => write!(
+                f,
+                "cycle {}: condition not reached within {} cycles",
+                cycle, cycles
+            ),
+            TestbenchFailure::NotProbeEdit { id } => {
+                write!(
+                    f,
+                    "Component {} is not a ProbeEdit, it can not be poked",
+                    id
+                )
+            }
+        }
+    }
+}
+
+// the report is shown when a failing test unwraps the result
+impl fmt::Debug for TestbenchFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl std::error::Error for TestbenchFailure {}
+
+impl From<Simulator> for Testbench {
+    fn from(simulator: Simulator) -> Self {
+        Testbench { simulator }
+    }
+}
+
+impl Testbench {
+    /// elaborate the model, reset to cycle 1
+    pub fn new(component_store: ComponentStore) -> Result<Self, ElaborationError> {
+        Ok(Testbench {
+            simulator: Simulator::new(component_store)?,
+        })
+    }
+
+    pub fn cycle(&self) -> usize {
+        self.simulator.cycle
+    }
+
+    /// drive the ProbeEdit `id` with `value` from the next step on
+    pub fn poke(
+        &mut self,
+        id: &str,
+        value: impl Into<SignalValue>,
+    ) -> Result<(), TestbenchFailure> {
+        let probe_edit = self
+            .simulator
+            .get_component::<ProbeEdit>(id)
+            .ok_or_else(|| TestbenchFailure::NotProbeEdit { id: id.into() })?;
+        let value = value.into();
+        let signal = Signal::from(value);
+        probe_edit.set_next_value(TextSignal {
+            text: signal.to_string(),
+            signal,
+        });
+        Ok(())
+    }
+
+    /// value of an output in the current cycle
+    /// panics if the output is not found
+    pub fn peek(&self, output: &Input) -> SignalValue {
+        self.simulator.get_handle_value(self.resolve(output))
+    }
+
+    // handle of an output, panics if not found
+    fn resolve(&self, output: &Input) -> InputHandle {
+        self.simulator
+            .resolve_input(output)
+            .unwrap_or_else(|| panic!("{:?} not found", output))
+    }
+
+    /// clock `cycles` cycles, stops at a component error or failed assertion
+    pub fn step(&mut self, cycles: usize) -> Result<(), TestbenchFailure> {
+        for _ in 0..cycles {
+            self.simulator.clock();
+            self.check_conditions()?;
+        }
+        Ok(())
+    }
+
+    /// the output has the expected value in the current cycle, `DontCare` matches any value
+    pub fn expect(
+        &self,
+        output: &Input,
+        expected: impl Into<SignalValue>,
+    ) -> Result<(), TestbenchFailure> {
+        let expected = expected.into();
+        let handle = self.resolve(output);
+        let actual = self.simulator.get_handle_signal(handle);
+        if expected == SignalValue::DontCare || actual.get_value() == expected {
+            return Ok(());
+        }
+        let cycle = self.simulator.cycle;
+        let trace = (cycle.saturating_sub(TRACE_CYCLES)..cycle)
+            .filter_map(|c| Some((c, self.simulator.get_signal_at(c, handle.0)?)))
+            .collect();
+        let mut expected_signal = actual;
+        expected_signal.set_value(expected);
+        Err(TestbenchFailure::Mismatch(Box::new(Mismatch {
+            cycle,
+            input: output.clone(),
+            expected: expected_signal,
+            actual,
+            trace,
+        })))
+    }
+
+    /// step until `predicate` holds, at most `cycles` cycles, returns the number of cycles stepped
+    pub fn run_until(
+        &mut self,
+        cycles: usize,
+        mut predicate: impl FnMut(&Testbench) -> bool,
+    ) -> Result<usize, TestbenchFailure> {
+        for stepped in 0..=cycles {
+            if predicate(self) {
+                return Ok(stepped);
+            }
+            if stepped < cycles {
+                self.step(1)?;
+            }
+        }
+        Err(TestbenchFailure::Timeout {
+            cycle: self.simulator.cycle,
+            cycles,
+        })
+    }
+
+    // first error or failed assertion reported in the last cycle
+    fn check_conditions(&self) -> Result<(), TestbenchFailure> {
+        match self.simulator.failed_condition() {
+            Some((id, condition)) => Err(TestbenchFailure::Condition {
+                // the cycle the condition was reported in
+                cycle: self.simulator.cycle - 1,
+                id: id.clone(),
+                condition: condition.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::components::*;
+    use crate::fixtures::accumulator;
+
+    // accumulator, adding the ProbeEdit value each cycle
+    fn testbench() -> Testbench {
+        Testbench::new(accumulator()).unwrap()
+    }
+
+    #[test]
+    fn test_testbench() {
+        let mut tb = testbench();
+        let acc = Input::new("acc", REGISTER_OUT_ID);
+        tb.poke("in", 2).unwrap();
+        tb.step(3).unwrap();
+        assert_eq!(tb.cycle(), 4);
+        tb.expect(&acc, 4).unwrap();
+        assert_eq!(tb.peek(&Input::new("add", ADD_OUT_ID)), 6.into());
+        tb.expect(&acc, SignalValue::DontCare).unwrap();
+
+        let stepped = tb.run_until(10, |tb| tb.peek(&acc) == 10.into()).unwrap();
+        assert_eq!(stepped, 3);
+        // known data is compared by value, regardless of width
+        tb.expect(&acc, SignalValue::Wide(10)).unwrap();
+
+        tb.poke("in", 0).unwrap();
+        let failure = tb.run_until(5, |tb| tb.peek(&acc) == 100.into()).err();
+        assert!(matches!(
+            failure,
+            Some(TestbenchFailure::Timeout { cycles: 5, .. })
+        ));
+
+        assert!(tb.poke("acc", 1).is_err());
+    }
+
+    #[test]
+    fn test_testbench_report() {
+        let mut tb = testbench();
+        let acc = Input::new("acc", REGISTER_OUT_ID);
+        tb.poke("in", 1).unwrap();
+        tb.step(3).unwrap();
+        let failure = tb.expect(&acc, 5).unwrap_err();
+        assert_eq!(
+            format!("{:?}", failure),
+            "cycle 4: acc.register_out expected 0x5, found 0x2 \
+             (cycle 1: 0x0, cycle 2: 0x0, cycle 3: 0x1)"
+        );
+    }
+}
@@ -0,0 +1,216 @@
+// IEEE 1364 Value Change Dump (VCD) export of the simulator state
+//
+// Each component is dumped as a module scope, with one wire per output field.
+// Any output may hold `SignalValue::Wide` data later in the dump, so all wires are
+// declared 64 bits wide.
+// `SignalValue::Unknown` and `SignalValue::Uninitialized` map to `x`,
+// `SignalValue::DontCare` maps to `z`.
+use crate::common::{Id, Signal, SignalValue, SignalWide, Simulator};
+use log::*;
+use std::{
+    fs::File,
+    io::{self, prelude::*, BufWriter},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+pub struct VcdWriter<W: Write> {
+    w: W,
+    // (sim_state index, identifier code), in dump order
+    vars: Vec<(usize, String)>,
+    // last dumped value per var, `None` before first dump
+    last: Vec<Option<SignalValue>>,
+    // last dumped time, time in VCD is strictly increasing
+    time: Option<usize>,
+}
+
+impl VcdWriter<BufWriter<File>> {
+    /// create a VCD file for the simulator model
+    pub fn create(path: &Path, simulator: &Simulator) -> io::Result<Self> {
+        let file = File::create(path)?;
+        VcdWriter::new(BufWriter::new(file), simulator)
+    }
+}
+
+impl<W: Write> VcdWriter<W> {
+    /// write the VCD header, declaring all outputs of the simulator model
+    pub fn new(mut w: W, simulator: &Simulator) -> io::Result<Self> {
+        // sort components and fields for a stable dump order
+        let mut ids: Vec<&Id> = simulator.id_start_index.keys().collect();
+        ids.sort();
+
+        writeln!(w, "$version SyncRim {} $end", env!("CARGO_PKG_VERSION"))?;
+        writeln!(w, "$timescale 1 ns $end")?;
+
+        let mut vars = vec![];
+        for id in ids {
+            let start_index = simulator.id_start_index[id];
+            let mut fields: Vec<(&Id, usize)> = simulator
+                .id_field_index
+                .iter()
+                .filter(|((c_id, _), _)| c_id == id)
+                .map(|((_, field), index)| (field, *index))
+                .collect();
+            if fields.is_empty() {
+                continue;
+            }
+            fields.sort_by_key(|(_, index)| *index);
+
+            writeln!(w, "$scope module {} $end", identifier(id))?;
+            for (field, index) in fields {
+                let code = code(vars.len());
+                writeln!(
+                    w,
+                    "$var wire {} {} {} $end",
+                    SignalWide::BITS,
+                    code,
+                    identifier(field)
+                )?;
+                vars.push((start_index + index, code));
+            }
+            writeln!(w, "$upscope $end")?;
+        }
+        writeln!(w, "$enddefinitions $end")?;
+
+        Ok(VcdWriter {
+            w,
+            last: vec![None; vars.len()],
+            vars,
+            time: None,
+        })
+    }
+
+    /// dump the values changed since last dump,
+    /// states at or before the last dumped cycle are ignored
+    pub fn dump(&mut self, cycle: usize, state: &[Signal]) -> io::Result<()> {
+        if self.time.is_some_and(|time| cycle <= time) {
+            trace!("vcd: cycle {} already dumped", cycle);
+            return Ok(());
+        }
+        let mut changes = String::new();
+        for ((index, code), last) in self.vars.iter().zip(self.last.iter_mut()) {
+            let value = state[*index].get_value();
+            if *last != Some(value) {
+                changes += &match value {
+                    SignalValue::Data(data) => format!("b{:b} {}\n", data, code),
+                    SignalValue::Wide(data) => format!("b{:b} {}\n", data, code),
+                    SignalValue::DontCare => format!("bz {}\n", code),
+                    SignalValue::Unknown | SignalValue::Uninitialized => format!("bx {}\n", code),
+                };
+                *last = Some(value);
+            }
+        }
+        // only cycles with changes are dumped
+        if !changes.is_empty() {
+            write!(self.w, "#{}\n{}", cycle, changes)?;
+        }
+        self.time = Some(cycle);
+        Ok(())
+    }
+
+    /// flush the underlying writer
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.w.flush()
+    }
+}
+
+// VCD identifier codes are made from the printable ASCII characters `!` to `~`
+fn code(mut n: usize) -> String {
+    let mut code = String::new();
+    loop {
+        code.push((b'!' + (n % 94) as u8) as char);
+        n /= 94;
+        if n == 0 {
+            break code;
+        }
+        n -= 1;
+    }
+}
+
+// VCD identifiers may not contain whitespace
+fn identifier(id: &str) -> String {
+    id.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+impl Simulator {
+    /// start streaming the simulator state to a VCD file, each `clock` dumps a new cycle
+    pub fn vcd_start(&mut self, path: &Path) -> io::Result<()> {
+        let mut vcd = VcdWriter::create(path, self)?;
+        vcd.dump(self.cycle, &self.sim_state)?;
+        self.vcd = Some(Arc::new(Mutex::new(vcd)));
+        Ok(())
+    }
+
+    /// stop streaming the simulator state
+    pub fn vcd_stop(&mut self) -> io::Result<()> {
+        match self.vcd.take() {
+            Some(vcd) => vcd.lock().unwrap().flush(),
+            None => Ok(()),
+        }
+    }
+
+    // dump current state if streaming, called by `clock`
+    pub(crate) fn vcd_dump(&mut self) {
+        let res = match &self.vcd {
+            Some(vcd) => vcd.lock().unwrap().dump(self.cycle, &self.sim_state),
+            None => Ok(()),
+        };
+        if let Err(err) = res {
+            error!("vcd: {}, streaming stopped", err);
+            self.vcd = None;
+        }
+    }
+
+    /// save the cycles kept in the simulator history up to the current cycle as a VCD file
+    pub fn save_vcd(&self, path: &Path) -> io::Result<()> {
+        let mut vcd = VcdWriter::create(path, self)?;
+        self.history
+            .try_for_each_state(|cycle, state| vcd.dump(cycle, state))?;
+        vcd.dump(self.cycle, &self.sim_state)?;
+        vcd.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{ComponentStore, Input};
+    use crate::components::*;
+
+    #[test]
+    fn test_code() {
+        assert_eq!(code(0), "!");
+        assert_eq!(code(93), "~");
+        assert_eq!(code(94), "!!");
+        assert_eq!(code(94 + 93), "~!");
+        assert_eq!(code(94 + 94), "!\"");
+    }
+
+    #[test]
+    fn test_vcd() {
+        let cs = ComponentStore {
+            store: vec![
+                ProbeStim::rc_new("stim", (0.0, 0.0), vec![0, 1, 1, 2]),
+                Register::rc_new("reg", (0.0, 0.0), Input::new("stim", "out")),
+            ],
+        };
+        let mut simulator = Simulator::new(cs).unwrap();
+        simulator.clock();
+        simulator.clock();
+
+        let mut vcd = VcdWriter::new(vec![], &simulator).unwrap();
+        simulator
+            .history
+            .try_for_each_state(|cycle, state| vcd.dump(cycle, state))
+            .unwrap();
+        vcd.dump(simulator.cycle, &simulator.sim_state).unwrap();
+        // already dumped
+        vcd.dump(1, &simulator.sim_state).unwrap();
+
+        let vcd = String::from_utf8(vcd.w).unwrap();
+        let body = vcd.split("$enddefinitions $end\n").nth(1).unwrap();
+        assert!(vcd.contains("$scope module reg $end\n$var wire 64 ! register_out $end"));
+        assert!(vcd.contains("$scope module stim $end\n$var wire 64 \" out $end"));
+        assert_eq!(body, "#0\nb0 !\nb0 \"\n#2\nb1 \"\n#3\nb1 !\n");
+    }
+}
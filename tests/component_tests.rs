@@ -1 +1,134 @@
+use syncrim::{
+    common::{ComponentStore, Condition, Input, SignalUnsigned, Simulator},
+    components::*,
+    fault::{Campaign, Fault, Outcome},
+    testbench::{Testbench, TestbenchFailure},
+};
 
+#[test]
+fn test_mux_testbench() {
+    let cs = ComponentStore {
+        store: vec![
+            ProbeEdit::rc_new("select", (0.0, 0.0)),
+            Constant::rc_new("c0", (0.0, 0.0), 10),
+            Constant::rc_new("c1", (0.0, 0.0), 11),
+            Mux::rc_new(
+                "mux",
+                (0.0, 0.0),
+                Input::new("select", PROBE_EDIT_OUT_ID),
+                vec![
+                    Input::new("c0", CONSTANT_OUT_ID),
+                    Input::new("c1", CONSTANT_OUT_ID),
+                ],
+            ),
+        ],
+    };
+    let mut tb = Testbench::new(cs).unwrap();
+    let out = Input::new("mux", MUX_OUT_ID);
+    tb.expect(&out, 10).unwrap();
+    tb.poke("select", 1).unwrap();
+    tb.step(1).unwrap();
+    tb.expect(&out, 11).unwrap();
+
+    // select out of range is reported by the mux
+    tb.poke("select", 2).unwrap();
+    let failure = tb.step(1).unwrap_err();
+    assert!(matches!(&failure, TestbenchFailure::Condition { id, .. } if id == "mux"));
+    assert!(failure.to_string().starts_with("cycle 2: mux error"));
+}
+
+// memory read by word at address 0, each cycle asserted to be 0
+fn mem_model() -> ComponentStore {
+    let constant = |id: &str, value| Constant::rc_new(id, (0.0, 0.0), value);
+    ComponentStore {
+        store: vec![
+            constant("data", 0),
+            constant("addr", 0),
+            constant("ctrl", MemCtrl::Read as SignalUnsigned),
+            constant("sext", 0),
+            constant("size", 4),
+            Mem::rc_new(
+                "mem",
+                (0.0, 0.0),
+                100.0,
+                100.0,
+                true,
+                Input::new("data", CONSTANT_OUT_ID),
+                Input::new("addr", CONSTANT_OUT_ID),
+                Input::new("ctrl", CONSTANT_OUT_ID),
+                Input::new("sext", CONSTANT_OUT_ID),
+                Input::new("size", CONSTANT_OUT_ID),
+                0..16,
+            ),
+            ProbeAssert::rc_new(
+                "assert",
+                (0.0, 0.0),
+                Input::new("mem", MEM_DATA_OUT_ID),
+                vec![0; 10],
+            ),
+        ],
+    }
+}
+
+#[test]
+fn test_fault_campaign() {
+    let campaign = Campaign {
+        cycles: 8,
+        faults: vec![
+            Fault::MemoryCorrupt {
+                id: "mem".into(),
+                address: 2,
+                mask: 0xff,
+                cycle: 3,
+            },
+            Fault::MemoryCorrupt {
+                id: "mem".into(),
+                address: 4,
+                mask: 0xff,
+                cycle: 3,
+            },
+        ],
+    };
+    let report = campaign.run(mem_model).unwrap();
+    // only the word read is asserted
+    assert!(matches!(
+        &report.results[0].outcome,
+        Outcome::Detected { cycle: 3, id, condition: Condition::Assert(_) } if id == "assert"
+    ));
+    assert_eq!(report.results[1].outcome, Outcome::Undetected);
+}
+
+#[test]
+fn test_memory_corrupt_undo() {
+    let mut simulator = Simulator::new(mem_model()).unwrap();
+    // the memory inputs never change, it is clocked in the cycle corrupted
+    simulator.set_event_driven(true);
+    simulator
+        .inject_fault(Fault::MemoryCorrupt {
+            id: "mem".into(),
+            address: 2,
+            mask: 0xff,
+            cycle: 3,
+        })
+        .unwrap();
+    let out = Input::new("mem", MEM_DATA_OUT_ID);
+    while simulator.cycle < 5 {
+        simulator.clock();
+    }
+    assert_eq!(simulator.get_input_value(&out), 0xff00.into());
+
+    // undoing the cycle corrupted restores the memory
+    simulator.un_clock();
+    simulator.un_clock();
+    simulator.clear_faults();
+    simulator.clock();
+    assert_eq!(simulator.get_input_value(&out), 0.into());
+
+    // as do corruptions between cycles
+    simulator.corrupt_memory("mem", 0, 1).unwrap();
+    simulator.clock();
+    assert_eq!(simulator.get_input_value(&out), 0x0100_0000.into());
+    simulator.un_clock();
+    simulator.clock();
+    assert_eq!(simulator.get_input_value(&out), 0.into());
+}